@@ -0,0 +1,346 @@
+use crate::dates::{parse_timestamp, Zone};
+use crate::intent::cosine_similarity;
+use crate::personalization::ActivityPatterns;
+use crate::types::Note;
+use crate::usage_log::UsageEvent;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+
+/// Days of notes and commands `personalize learn` looks at.
+pub const LEARN_DAYS: i64 = 90;
+/// Most notes fetched for learning.
+pub const LEARN_NOTES: usize = 1000;
+/// Similarity a note needs to an existing topic to join it.
+const TOPIC_SIMILARITY: f32 = 0.75;
+/// Notes a cluster needs before it counts as a topic.
+const MIN_TOPIC_NOTES: usize = 3;
+/// Topics kept, largest first.
+const MAX_TOPICS: usize = 5;
+/// Words that say nothing about a topic.
+const STOP_WORDS: [&str; 40] = [
+    "about", "after", "again", "also", "because", "been", "before", "being", "could", "does",
+    "doing", "done", "from", "have", "having", "into", "just", "like", "make", "more", "need",
+    "needs", "only", "over", "should", "some", "still", "that", "their", "them", "then", "there",
+    "these", "they", "this", "today", "very", "what", "when", "with",
+];
+
+/// Time-of-day slots offered in setup, as `(name, first hour, end hour)`.
+const TIME_SLOTS: [(&str, u32, u32); 6] = [
+    ("early_morning", 6, 9),
+    ("morning", 9, 12),
+    ("afternoon", 12, 15),
+    ("late_afternoon", 15, 18),
+    ("evening", 18, 21),
+    ("night", 21, 30),
+];
+
+/// Activity learned from note and command timestamps in the user's time zone.
+#[derive(Debug, PartialEq)]
+pub struct LearnedActivity {
+    /// Notes and commands per hour of day.
+    pub hours: [usize; 24],
+    /// Notes and commands per weekday, Monday first.
+    pub weekdays: [usize; 7],
+    pub notes: usize,
+    pub commands: usize,
+    pub notes_per_week: f32,
+    /// Average number of days with notes in weeks that had any.
+    pub active_days_per_week: f32,
+    /// Labels of note clusters, largest first.
+    pub topics: Vec<String>,
+}
+
+impl LearnedActivity {
+    /// Learns from `notes` and `events` covering the `days` before `now`. Notes without
+    /// an embedding count towards timing but not topics.
+    pub fn learn(
+        notes: &[Note],
+        events: &[UsageEvent],
+        zone: Zone,
+        now: DateTime<Utc>,
+        days: i64,
+    ) -> Self {
+        let note_times: Vec<DateTime<Utc>> = notes
+            .iter()
+            .filter_map(|n| parse_timestamp(&n.created_at))
+            .collect();
+        let mut hours = [0; 24];
+        let mut weekdays = [0; 7];
+        for time in note_times.iter().chain(events.iter().map(|e| &e.at)) {
+            let local = zone.local(*time);
+            hours[local.hour() as usize] += 1;
+            weekdays[local.weekday().num_days_from_monday() as usize] += 1;
+        }
+
+        // Measure from the first recorded activity so a new user isn't averaged over
+        // weeks before they started
+        let first = note_times
+            .iter()
+            .chain(events.iter().map(|e| &e.at))
+            .min()
+            .copied()
+            .unwrap_or(now)
+            .max(now - Duration::days(days));
+        let weeks = ((now - first).num_hours() as f32 / (24.0 * 7.0)).max(1.0);
+
+        let mut days_by_week: HashMap<(i32, u32), BTreeSet<NaiveDate>> = HashMap::new();
+        for time in &note_times {
+            let date = zone.local(*time).date();
+            let week = date.iso_week();
+            days_by_week
+                .entry((week.year(), week.week()))
+                .or_default()
+                .insert(date);
+        }
+        let active_days_per_week = if days_by_week.is_empty() {
+            0.0
+        } else {
+            days_by_week.values().map(BTreeSet::len).sum::<usize>() as f32
+                / days_by_week.len() as f32
+        };
+
+        Self {
+            hours,
+            weekdays,
+            notes: note_times.len(),
+            commands: events.len(),
+            notes_per_week: note_times.len() as f32 / weeks,
+            active_days_per_week,
+            topics: cluster_topics(notes),
+        }
+    }
+
+    /// Setup's time-of-day slots holding at least half as much activity as the busiest.
+    pub fn most_active_times(&self) -> Vec<String> {
+        let counts: Vec<(&str, usize)> = TIME_SLOTS
+            .iter()
+            .map(|(name, start, end)| {
+                let count = (*start..*end).map(|h| self.hours[(h % 24) as usize]).sum();
+                (*name, count)
+            })
+            .collect();
+        let busiest = counts.iter().map(|(_, c)| *c).max().unwrap_or(0);
+        if busiest == 0 {
+            return vec![];
+        }
+        let mut active: Vec<(&str, usize)> = counts
+            .into_iter()
+            .filter(|(_, c)| *c * 2 >= busiest)
+            .collect();
+        active.sort_by_key(|(_, c)| Reverse(*c));
+        active
+            .into_iter()
+            .take(3)
+            .map(|(n, _)| n.to_string())
+            .collect()
+    }
+
+    /// `high`, `moderate` or `low`, from notes per week.
+    pub fn note_taking_frequency(&self) -> &'static str {
+        match self.notes_per_week {
+            n if n >= 7.0 => "high",
+            n if n >= 2.0 => "moderate",
+            _ => "low",
+        }
+    }
+
+    /// `fast`, `moderate` or `slow`, from how many days a week notes are written.
+    pub fn learning_pace(&self) -> &'static str {
+        match self.active_days_per_week {
+            d if d >= 4.0 => "fast",
+            d if d >= 2.0 => "moderate",
+            _ => "slow",
+        }
+    }
+
+    /// Writes what was learned into `patterns`. Answers from setup that there is no
+    /// evidence for (no activity, no topics) are kept.
+    pub fn apply(&self, patterns: &mut ActivityPatterns, now: DateTime<Utc>) {
+        let active_times = self.most_active_times();
+        if !active_times.is_empty() {
+            patterns.most_active_times = active_times;
+        }
+        if self.notes > 0 {
+            patterns.note_taking_frequency = self.note_taking_frequency().to_string();
+            patterns.learning_pace = self.learning_pace().to_string();
+        }
+        if !self.topics.is_empty() {
+            patterns.common_topics = self.topics.clone();
+        }
+        patterns.hour_histogram = self.hours.to_vec();
+        patterns.weekday_histogram = self.weekdays.to_vec();
+        patterns.notes_per_week = self.notes_per_week;
+        patterns.learned_at = Some(now);
+    }
+}
+
+/// One block character per count, scaled to the largest: `▁▂▅█`.
+pub fn sparkline(counts: &[usize]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = counts.iter().copied().max().unwrap_or(0);
+    counts
+        .iter()
+        .map(|&c| match c {
+            0 => ' ',
+            c => BLOCKS[c * 7 / max],
+        })
+        .collect()
+}
+
+/// Groups notes whose embeddings are close and names each group by its most shared words.
+fn cluster_topics(notes: &[Note]) -> Vec<String> {
+    // (running mean embedding, member notes)
+    let mut clusters: Vec<(Vec<f32>, Vec<&Note>)> = vec![];
+    for note in notes {
+        let Some(embedding) = note.embedding.as_deref().filter(|e| !e.is_empty()) else {
+            continue;
+        };
+        let nearest = clusters
+            .iter()
+            .enumerate()
+            .map(|(i, (centroid, _))| (i, cosine_similarity(centroid, embedding)))
+            .filter(|(_, s)| *s >= TOPIC_SIMILARITY)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match nearest {
+            Some((i, _)) => {
+                let (centroid, members) = &mut clusters[i];
+                let n = members.len() as f32;
+                for (c, e) in centroid.iter_mut().zip(embedding) {
+                    *c = (*c * n + e) / (n + 1.0);
+                }
+                members.push(note);
+            }
+            None => clusters.push((embedding.to_vec(), vec![note])),
+        }
+    }
+
+    clusters.retain(|(_, members)| members.len() >= MIN_TOPIC_NOTES);
+    clusters.sort_by_key(|(_, members)| Reverse(members.len()));
+    let mut topics: Vec<String> = vec![];
+    for (_, members) in clusters {
+        if let Some(label) = topic_label(&members) {
+            if !topics.contains(&label) {
+                topics.push(label);
+            }
+        }
+        if topics.len() == MAX_TOPICS {
+            break;
+        }
+    }
+    topics
+}
+
+/// The two words found in the most notes of a cluster, or `None` if no word repeats.
+fn topic_label(notes: &[&Note]) -> Option<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for note in notes {
+        let words: BTreeSet<String> = note
+            .content
+            .split(|c: char| !c.is_alphanumeric())
+            .map(str::to_lowercase)
+            .filter(|w| w.chars().count() >= 4 && !w.chars().all(|c| c.is_numeric()))
+            .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+            .collect();
+        for word in words {
+            *counts.entry(word).or_default() += 1;
+        }
+    }
+    let mut ranked: Vec<(String, usize)> = counts.into_iter().filter(|(_, c)| *c >= 2).collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let words: Vec<String> = ranked.into_iter().take(2).map(|(w, _)| w).collect();
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note_at(id: &str, content: &str, at: &str, embedding: Vec<f32>) -> Note {
+        Note {
+            id: id.to_string(),
+            content: content.to_string(),
+            created_at: at.to_string(),
+            embedding: Some(embedding),
+            project: None,
+        }
+    }
+
+    #[test]
+    fn test_learn_times_cadence_and_topics() {
+        let zone = Zone::parse("UTC").unwrap();
+        let now = parse_timestamp("2026-10-19T18:00:00Z").unwrap();
+        let deploy = vec![1.0, 0.0];
+        let hiring = vec![0.0, 1.0];
+        let notes = vec![
+            // Monday and Tuesday mornings, two weeks apart
+            note_at(
+                "1",
+                "Kubernetes deploy failed again",
+                "2026-10-05T09:15:00Z",
+                deploy.clone(),
+            ),
+            note_at(
+                "2",
+                "Fixed the kubernetes deploy probe",
+                "2026-10-06T10:30:00Z",
+                deploy.clone(),
+            ),
+            note_at(
+                "3",
+                "Deploy pipeline to kubernetes is green",
+                "2026-10-12T09:45:00Z",
+                deploy,
+            ),
+            note_at(
+                "4",
+                "Interview loop for backend role",
+                "2026-10-13T10:00:00Z",
+                hiring.clone(),
+            ),
+            note_at("5", "Hiring sync", "2026-10-19T11:00:00Z", hiring),
+        ];
+        let events = vec![UsageEvent {
+            command: "recent".to_string(),
+            at: parse_timestamp("2026-10-14T20:00:00Z").unwrap(),
+        }];
+
+        let learned = LearnedActivity::learn(&notes, &events, zone, now, LEARN_DAYS);
+        assert_eq!(learned.notes, 5);
+        assert_eq!(learned.commands, 1);
+        assert_eq!(learned.hours[9], 2);
+        assert_eq!(learned.hours[20], 1);
+        assert_eq!(learned.weekdays[0], 3);
+        assert_eq!(learned.most_active_times(), vec!["morning"]);
+        assert_eq!(learned.note_taking_frequency(), "moderate");
+        assert_eq!(learned.learning_pace(), "slow");
+        // Only the deploy cluster is large enough to be a topic
+        assert_eq!(learned.topics, vec!["deploy kubernetes"]);
+
+        let mut patterns = ActivityPatterns::default();
+        learned.apply(&mut patterns, now);
+        assert_eq!(patterns.most_active_times, vec!["morning"]);
+        assert_eq!(patterns.common_topics, vec!["deploy kubernetes"]);
+        assert_eq!(patterns.hour_histogram.len(), 24);
+        assert_eq!(patterns.learned_at, Some(now));
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0, 1, 4, 8]), " ▁▄█");
+        assert_eq!(sparkline(&[0, 0]), "  ");
+    }
+
+    #[test]
+    fn test_no_activity_keeps_setup_answers() {
+        let now = Utc::now();
+        let learned = LearnedActivity::learn(&[], &[], Zone::Local, now, LEARN_DAYS);
+        let mut patterns = ActivityPatterns {
+            most_active_times: vec!["evening".to_string()],
+            ..ActivityPatterns::default()
+        };
+        learned.apply(&mut patterns, now);
+        assert_eq!(patterns.most_active_times, vec!["evening"]);
+        assert_eq!(patterns.learning_pace, "moderate");
+    }
+}
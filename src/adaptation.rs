@@ -0,0 +1,252 @@
+use crate::personalization::InteractionHistory;
+use crate::suggestion_log::{Feedback, FeedbackReason, SuggestionRecord};
+use crate::types::Note;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Satisfaction (0.2 to 1.0) at or above which an intent counts as well received.
+const PREFERRED_SATISFACTION: f32 = 0.75;
+/// Satisfaction at or below which earlier suggestions for an intent count as poor.
+const POOR_SATISFACTION: f32 = 0.45;
+/// Ratings an intent needs before its satisfaction changes the prompt.
+const MIN_RATINGS: usize = 2;
+/// Latest rated suggestions whose complaints are considered.
+const REASON_WINDOW: usize = 5;
+/// Complaints of one kind within the window that trigger an adjustment.
+const REASON_VOTES: usize = 2;
+
+/// What past ratings imply for the next suggestion: which intents went well, how long
+/// answers should be, and which notes kept appearing in rejected suggestions.
+#[derive(Debug, Default)]
+pub struct Adaptation {
+    /// Number of rated suggestions.
+    pub rated: usize,
+    /// Share of rated suggestions marked helpful.
+    pub helpful_share: f32,
+    /// Satisfaction per intent with enough ratings, best first.
+    pub intent_scores: Vec<(String, f32)>,
+    /// Complaints raised often enough in recent ratings to act on.
+    pub reasons: Vec<FeedbackReason>,
+    /// Note ids with how many more rejected than liked suggestions they were used in,
+    /// worst first. Only notes with a positive balance are listed.
+    pub note_penalties: Vec<(String, usize)>,
+}
+
+impl Adaptation {
+    /// Combines the satisfaction scores kept in the profile with the rated suggestions
+    /// in the suggestion history (oldest first).
+    pub fn from_feedback(history: &InteractionHistory, records: &[SuggestionRecord]) -> Self {
+        let rated: Vec<(&SuggestionRecord, &Feedback)> = records
+            .iter()
+            .filter_map(|r| r.feedback.as_ref().map(|f| (r, f)))
+            .collect();
+        let helpful = rated.iter().filter(|(_, f)| f.helpful).count();
+
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for (record, _) in &rated {
+            *counts.entry(record.intent.as_str()).or_default() += 1;
+        }
+        let mut intent_scores: Vec<(String, f32)> = history
+            .feedback_patterns
+            .iter()
+            .filter(|(intent, _)| counts.get(intent.as_str()).copied().unwrap_or(0) >= MIN_RATINGS)
+            .map(|(intent, score)| (intent.clone(), *score))
+            .collect();
+        intent_scores.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let votes = |reason: FeedbackReason| {
+            rated
+                .iter()
+                .rev()
+                .take(REASON_WINDOW)
+                .filter(|(_, f)| f.reasons.contains(&reason))
+                .count()
+        };
+        let mut reasons: Vec<FeedbackReason> = [
+            FeedbackReason::TooLong,
+            FeedbackReason::TooShort,
+            FeedbackReason::TooGeneric,
+            FeedbackReason::OffTopic,
+        ]
+        .into_iter()
+        .filter(|r| votes(*r) >= REASON_VOTES)
+        .collect();
+        // Contradicting length complaints cancel out
+        if reasons.contains(&FeedbackReason::TooLong) && reasons.contains(&FeedbackReason::TooShort)
+        {
+            reasons.retain(|r| !matches!(r, FeedbackReason::TooLong | FeedbackReason::TooShort));
+        }
+
+        let mut balance: HashMap<&str, i32> = HashMap::new();
+        for (record, f) in &rated {
+            let delta = if f.is_rejection() {
+                1
+            } else if f.rating >= 4 {
+                -1
+            } else {
+                0
+            };
+            for id in &record.note_ids {
+                *balance.entry(id.as_str()).or_default() += delta;
+            }
+        }
+        let mut note_penalties: Vec<(String, usize)> = balance
+            .into_iter()
+            .filter(|(_, b)| *b > 0)
+            .map(|(id, b)| (id.to_string(), b as usize))
+            .collect();
+        note_penalties.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Self {
+            rated: rated.len(),
+            helpful_share: if rated.is_empty() {
+                0.0
+            } else {
+                helpful as f32 / rated.len() as f32
+            },
+            intent_scores,
+            reasons,
+            note_penalties,
+        }
+    }
+
+    fn penalty(&self, note_id: &str) -> usize {
+        self.note_penalties
+            .iter()
+            .find(|(id, _)| id == note_id)
+            .map_or(0, |(_, p)| *p)
+    }
+
+    /// Orders retrieved notes so those tied to rejected suggestions come last, keeping
+    /// the similarity order otherwise, and keeps the first `limit`.
+    pub fn rerank(&self, mut notes: Vec<Note>, limit: usize) -> Vec<Note> {
+        notes.sort_by_key(|n| self.penalty(&n.id));
+        notes.truncate(limit);
+        notes
+    }
+
+    fn score(&self, intent: &str) -> Option<f32> {
+        self.intent_scores
+            .iter()
+            .find(|(i, _)| i == intent)
+            .map(|(_, s)| *s)
+    }
+
+    /// Intents whose suggestions were rated well, best first.
+    pub fn preferred_intents(&self) -> Vec<&str> {
+        self.intent_scores
+            .iter()
+            .filter(|(_, s)| *s >= PREFERRED_SATISFACTION)
+            .map(|(i, _)| i.as_str())
+            .collect()
+    }
+
+    /// Variables for the `feedback` section of the `suggestion` prompt, for a query
+    /// classified as `intent`.
+    pub fn prompt_data(&self, intent: &str) -> Value {
+        let score = self.score(intent);
+        let intent_poor = score.is_some_and(|s| s <= POOR_SATISFACTION);
+        let intent_good = score.is_some_and(|s| s >= PREFERRED_SATISFACTION);
+        let preferred: Vec<String> = self
+            .preferred_intents()
+            .into_iter()
+            .filter(|i| *i != intent)
+            .map(|i| i.replace('_', " "))
+            .collect();
+        let has = |reason| self.reasons.contains(&reason);
+        json!({
+            "active": intent_poor || intent_good || !preferred.is_empty() || !self.reasons.is_empty(),
+            "intent_label": intent.replace('_', " "),
+            "intent_poor": intent_poor,
+            "intent_good": intent_good,
+            "has_preferred": !preferred.is_empty(),
+            "preferred": preferred,
+            "shorter": has(FeedbackReason::TooLong),
+            "longer": has(FeedbackReason::TooShort),
+            "specific": has(FeedbackReason::TooGeneric),
+            "on_topic": has(FeedbackReason::OffTopic),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::note;
+    use chrono::Utc;
+
+    fn rated(
+        intent: &str,
+        rating: u8,
+        reasons: &[FeedbackReason],
+        notes: &[&str],
+    ) -> SuggestionRecord {
+        let mut record = SuggestionRecord::new("q", intent, "work", "llama3", "text");
+        record.note_ids = notes.iter().map(|n| n.to_string()).collect();
+        record.feedback = Some(Feedback {
+            helpful: rating >= 3,
+            rating,
+            reasons: reasons.to_vec(),
+            comment: None,
+            rated_at: Utc::now(),
+        });
+        record
+    }
+
+    #[test]
+    fn test_feedback_steers_prompt_and_retrieval() {
+        let mut history = InteractionHistory::default();
+        history
+            .feedback_patterns
+            .insert("problem_solving".to_string(), 0.9);
+        history
+            .feedback_patterns
+            .insert("planning".to_string(), 0.3);
+        history
+            .feedback_patterns
+            .insert("career_growth".to_string(), 1.0);
+        let long = [FeedbackReason::TooLong];
+        let records = vec![
+            rated("problem_solving", 5, &[], &["a", "b"]),
+            rated("problem_solving", 4, &[], &["b"]),
+            rated("planning", 2, &long, &["c", "d"]),
+            rated("planning", 1, &long, &["c"]),
+            // Only one rating: not enough to act on
+            rated("career_growth", 5, &[], &[]),
+            SuggestionRecord::new("unrated", "planning", "work", "llama3", "text"),
+        ];
+        let adaptation = Adaptation::from_feedback(&history, &records);
+
+        assert_eq!(adaptation.rated, 5);
+        assert_eq!(adaptation.reasons, vec![FeedbackReason::TooLong]);
+        assert_eq!(adaptation.preferred_intents(), vec!["problem_solving"]);
+        assert_eq!(
+            adaptation.note_penalties,
+            vec![("c".to_string(), 2), ("d".to_string(), 1)]
+        );
+
+        let data = adaptation.prompt_data("planning");
+        assert_eq!(data["intent_poor"], true);
+        assert_eq!(data["shorter"], true);
+        assert_eq!(data["preferred"], json!(["problem solving"]));
+
+        let notes = vec![
+            note("c", "stale"),
+            note("a", "useful"),
+            note("d", "meh"),
+            note("e", "new"),
+        ];
+        let ids: Vec<String> = adaptation
+            .rerank(notes, 3)
+            .into_iter()
+            .map(|n| n.id)
+            .collect();
+        assert_eq!(ids, vec!["a", "e", "d"]);
+    }
+
+    #[test]
+    fn test_no_feedback_changes_nothing() {
+        let adaptation = Adaptation::from_feedback(&InteractionHistory::default(), &[]);
+        assert_eq!(adaptation.prompt_data("planning")["active"], false);
+    }
+}
@@ -0,0 +1,428 @@
+use colored::*;
+use serde_json::Value;
+use std::cmp::Reverse;
+
+use crate::prompts::Prompts;
+use crate::tokens;
+
+/// Tokens kept free for the model's answer when `numPredict` is not set.
+const ANSWER_RESERVE: usize = 1024;
+
+/// Template text around each list item (numbering, labels, line breaks).
+const ITEM_OVERHEAD: usize = 4;
+
+/// Fewest tokens worth keeping of an item that has to be shortened.
+const MIN_ITEM_TOKENS: usize = 32;
+
+/// Which end of a list goes first when it has to shrink.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DropFrom {
+    Front,
+    Back,
+}
+
+/// A list in a prompt's template data that can be trimmed to fit the context window.
+pub struct Section {
+    pub name: &'static str,
+    /// Key of the list in the template data; `has_<key>` is kept in step when present.
+    pub key: &'static str,
+    /// Sections with lower priority are trimmed first.
+    pub priority: u8,
+    /// Share of the space left by the fixed text that the section can count on.
+    pub share: f32,
+    pub drop_from: DropFrom,
+}
+
+/// Sections of the `suggestion` prompt. Notes come most relevant first, so the least
+/// relevant go first.
+pub const SUGGESTION_SECTIONS: [Section; 4] = [
+    Section {
+        name: "notes",
+        key: "notes",
+        priority: 3,
+        share: 0.5,
+        drop_from: DropFrom::Back,
+    },
+    Section {
+        name: "projects",
+        key: "projects",
+        priority: 2,
+        share: 0.2,
+        drop_from: DropFrom::Back,
+    },
+    Section {
+        name: "goals",
+        key: "goals",
+        priority: 2,
+        share: 0.2,
+        drop_from: DropFrom::Back,
+    },
+    Section {
+        name: "completed goals",
+        key: "completed_goals",
+        priority: 1,
+        share: 0.1,
+        drop_from: DropFrom::Back,
+    },
+];
+
+/// Sections of the `chat` prompt. The oldest turns of the conversation go first.
+pub const CHAT_SECTIONS: [Section; 2] = [
+    Section {
+        name: "notes",
+        key: "notes",
+        priority: 3,
+        share: 0.5,
+        drop_from: DropFrom::Back,
+    },
+    Section {
+        name: "history",
+        key: "history",
+        priority: 2,
+        share: 0.5,
+        drop_from: DropFrom::Front,
+    },
+];
+
+/// What one section kept and lost.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionUsage {
+    pub name: &'static str,
+    /// Estimated tokens of the kept items.
+    pub tokens: usize,
+    pub kept: usize,
+    pub dropped: usize,
+    pub shortened: usize,
+}
+
+/// How a prompt was fitted into the context window.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BudgetReport {
+    /// Tokens the prompt may use: the context window minus room for the answer.
+    pub budget: usize,
+    /// Estimated tokens of the assembled prompt.
+    pub used: usize,
+    /// Estimated tokens of what is never trimmed: instructions, profile and the query.
+    pub fixed: usize,
+    pub sections: Vec<SectionUsage>,
+}
+
+impl BudgetReport {
+    /// True when anything was dropped or shortened.
+    pub fn trimmed(&self) -> bool {
+        self.sections
+            .iter()
+            .any(|s| s.dropped > 0 || s.shortened > 0)
+    }
+
+    /// Prints the budget and what each section kept, for `--explain`.
+    pub fn print(&self) {
+        println!("\n{}", "📏 Context Budget".cyan().bold());
+        println!(
+            "  Prompt: ~{} of {} tokens (~{} for instructions, profile and query)",
+            self.used, self.budget, self.fixed
+        );
+        for section in &self.sections {
+            if section.kept + section.dropped == 0 {
+                continue;
+            }
+            let mut line = format!(
+                "  {:<16} {} kept, ~{} tokens",
+                section.name, section.kept, section.tokens
+            );
+            if section.dropped > 0 {
+                line.push_str(
+                    &format!(", {} dropped", section.dropped)
+                        .yellow()
+                        .to_string(),
+                );
+            }
+            if section.shortened > 0 {
+                line.push_str(
+                    &format!(", {} shortened", section.shortened)
+                        .yellow()
+                        .to_string(),
+                );
+            }
+            println!("{line}");
+        }
+        if self.used > self.budget {
+            println!(
+                "  {}",
+                "Still over budget: raise numCtx in the generation settings or shorten the prompt template."
+                    .red()
+            );
+        } else if !self.trimmed() {
+            println!("  {}", "Everything fit; nothing was left out.".green());
+        }
+    }
+}
+
+/// Prompt tokens available with `context_tokens` of context when up to `answer_tokens`
+/// may be generated. The answer never takes more than half the window.
+pub fn prompt_budget(context_tokens: usize, answer_tokens: Option<u32>) -> usize {
+    let reserve = answer_tokens
+        .map_or(ANSWER_RESERVE, |n| n as usize)
+        .min(context_tokens / 2);
+    context_tokens - reserve
+}
+
+/// Renders prompt `name` from `data`, trimming `sections` until it fits `budget` tokens.
+///
+/// Each section first gets its share of the space the fixed text leaves, and space one
+/// section does not need goes to the others by priority. Within a section the items at
+/// its `drop_from` end go first; a last item too big on its own is shortened. If the
+/// rendered prompt is still too long (a template may repeat a list), items are dropped
+/// from the lowest-priority sections until it fits or nothing is left.
+pub fn assemble(
+    prompts: &Prompts,
+    name: &str,
+    mut data: Value,
+    sections: &[Section],
+    budget: usize,
+) -> (String, BudgetReport) {
+    let mut lists: Vec<Vec<Value>> = sections
+        .iter()
+        .map(|s| match data.get_mut(s.key).map(Value::take) {
+            Some(Value::Array(items)) => items,
+            _ => vec![],
+        })
+        .collect();
+    let counts: Vec<usize> = lists.iter().map(Vec::len).collect();
+
+    set_lists(&mut data, sections, &vec![vec![]; sections.len()]);
+    let fixed = tokens::estimate(&prompts.render(name, &data));
+
+    let needs: Vec<usize> = lists.iter().map(|items| list_tokens(items)).collect();
+    let allowances = allocate(sections, &needs, budget.saturating_sub(fixed));
+    let mut shortened = vec![0; sections.len()];
+    for (i, section) in sections.iter().enumerate() {
+        shortened[i] = fit(&mut lists[i], allowances[i], section.drop_from);
+    }
+
+    let mut order: Vec<usize> = (0..sections.len()).collect();
+    order.sort_by_key(|&i| sections[i].priority);
+    let prompt = loop {
+        set_lists(&mut data, sections, &lists);
+        let prompt = prompts.render(name, &data);
+        if tokens::estimate(&prompt) <= budget {
+            break prompt;
+        }
+        match order.iter().find(|&&i| !lists[i].is_empty()) {
+            Some(&i) => drop_one(&mut lists[i], sections[i].drop_from),
+            None => break prompt,
+        }
+    };
+
+    let report = BudgetReport {
+        budget,
+        used: tokens::estimate(&prompt),
+        fixed,
+        sections: sections
+            .iter()
+            .enumerate()
+            .map(|(i, section)| SectionUsage {
+                name: section.name,
+                tokens: list_tokens(&lists[i]),
+                kept: lists[i].len(),
+                dropped: counts[i] - lists[i].len(),
+                shortened: shortened[i].min(lists[i].len()),
+            })
+            .collect(),
+    };
+    if report.trimmed() {
+        let dropped: Vec<String> = report
+            .sections
+            .iter()
+            .filter(|s| s.dropped > 0 || s.shortened > 0)
+            .map(|s| {
+                format!(
+                    "{} ({} dropped, {} shortened)",
+                    s.name, s.dropped, s.shortened
+                )
+            })
+            .collect();
+        log::info!(
+            "Trimmed the {name} prompt to fit {budget} tokens: {}",
+            dropped.join(", ")
+        );
+    }
+    (prompt, report)
+}
+
+/// Puts `lists` back into the template data, updating `has_<key>` flags.
+fn set_lists(data: &mut Value, sections: &[Section], lists: &[Vec<Value>]) {
+    for (section, items) in sections.iter().zip(lists) {
+        data[section.key] = Value::Array(items.clone());
+        let flag = format!("has_{}", section.key);
+        if data.get(&flag).is_some() {
+            data[flag] = Value::Bool(!items.is_empty());
+        }
+    }
+}
+
+/// Tokens each section may use: what it needs up to its share of `available`, then
+/// whatever is left over, highest priority first.
+fn allocate(sections: &[Section], needs: &[usize], available: usize) -> Vec<usize> {
+    let mut allowances: Vec<usize> = sections
+        .iter()
+        .zip(needs)
+        .map(|(section, &need)| need.min((available as f32 * section.share) as usize))
+        .collect();
+    let mut spare = available.saturating_sub(allowances.iter().sum());
+    let mut order: Vec<usize> = (0..sections.len()).collect();
+    order.sort_by_key(|&i| Reverse(sections[i].priority));
+    for i in order {
+        let extra = (needs[i] - allowances[i]).min(spare);
+        allowances[i] += extra;
+        spare -= extra;
+    }
+    allowances
+}
+
+/// Drops items until `items` fit `allowance`, shortening the last one rather than
+/// dropping it when enough of it would remain. Returns how many items were shortened.
+fn fit(items: &mut Vec<Value>, allowance: usize, drop_from: DropFrom) -> usize {
+    while list_tokens(items) > allowance {
+        if items.len() == 1 {
+            let room = allowance.saturating_sub(ITEM_OVERHEAD);
+            if room >= MIN_ITEM_TOKENS && shorten(&mut items[0], room) {
+                return 1;
+            }
+            items.clear();
+            break;
+        }
+        drop_one(items, drop_from);
+    }
+    0
+}
+
+fn drop_one(items: &mut Vec<Value>, drop_from: DropFrom) {
+    match drop_from {
+        DropFrom::Front => {
+            items.remove(0);
+        }
+        DropFrom::Back => {
+            items.pop();
+        }
+    }
+}
+
+fn list_tokens(items: &[Value]) -> usize {
+    items
+        .iter()
+        .map(|item| ITEM_OVERHEAD + text_tokens(item))
+        .sum()
+}
+
+/// Estimated tokens of the text in a template value.
+fn text_tokens(value: &Value) -> usize {
+    match value {
+        Value::String(text) => tokens::estimate(text),
+        Value::Array(items) => items.iter().map(text_tokens).sum(),
+        Value::Object(fields) => fields.values().map(text_tokens).sum(),
+        Value::Null => 0,
+        _ => 1,
+    }
+}
+
+/// Cuts the longest text in `item` so the item's text fits `limit` tokens, marking the
+/// cut with "…". False when nothing could be cut.
+fn shorten(item: &mut Value, limit: usize) -> bool {
+    let excess = text_tokens(item).saturating_sub(limit);
+    let Some(text) = longest_text(item) else {
+        return false;
+    };
+    let keep = text.chars().count().saturating_sub(excess * 4 + 1);
+    if keep == 0 {
+        return false;
+    }
+    let cut: String = text.chars().take(keep).collect();
+    *text = format!("{}…", cut.trim_end());
+    true
+}
+
+fn longest_text(value: &mut Value) -> Option<&mut String> {
+    match value {
+        Value::String(text) => Some(text),
+        Value::Array(items) => items
+            .iter_mut()
+            .filter_map(longest_text)
+            .max_by_key(|text| text.len()),
+        Value::Object(fields) => fields
+            .values_mut()
+            .filter_map(longest_text)
+            .max_by_key(|text| text.len()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn prompts(dir: &std::path::Path) -> Prompts {
+        Prompts::at(dir.to_path_buf())
+    }
+
+    fn history(turns: usize) -> Vec<Value> {
+        (1..=turns)
+            .map(|i| json!({ "user": format!("question {i}"), "assistant": "a".repeat(200) }))
+            .collect()
+    }
+
+    #[test]
+    fn test_assemble_keeps_everything_that_fits() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = json!({
+            "profile": {}, "has_notes": true, "notes": [{ "content": "Short note" }],
+            "history": history(2), "message": "Hi",
+        });
+        let (prompt, report) = assemble(&prompts(dir.path()), "chat", data, &CHAT_SECTIONS, 4000);
+        assert!(prompt.contains("1. Short note"));
+        assert!(prompt.contains("User: question 1"));
+        assert!(!report.trimmed());
+        assert_eq!(report.used, tokens::estimate(&prompt));
+    }
+
+    #[test]
+    fn test_assemble_drops_oldest_turns_and_shortens_long_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = json!({
+            "profile": {}, "has_notes": true,
+            "notes": [{ "content": "x".repeat(4000) }, { "content": "Second note" }],
+            "history": history(6), "message": "Hi",
+        });
+        let (prompt, report) = assemble(&prompts(dir.path()), "chat", data, &CHAT_SECTIONS, 600);
+        assert!(report.used <= 600);
+        assert!(!prompt.contains("question 1\n"));
+        assert!(prompt.contains("User: question 6"));
+        assert!(prompt.contains("…"));
+
+        let notes = &report.sections[0];
+        assert_eq!((notes.kept, notes.dropped, notes.shortened), (1, 1, 1));
+        let history = &report.sections[1];
+        assert_eq!(history.kept + history.dropped, 6);
+        assert!(history.dropped > 0);
+    }
+
+    #[test]
+    fn test_assemble_clears_flags_of_emptied_sections() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = json!({
+            "profile": {}, "has_notes": true, "notes": [{ "content": "y".repeat(2000) }],
+            "history": [], "message": "Hi",
+        });
+        let (prompt, report) = assemble(&prompts(dir.path()), "chat", data, &CHAT_SECTIONS, 60);
+        assert!(!prompt.contains("Relevant Notes:"));
+        assert_eq!(report.sections[0].dropped, 1);
+    }
+
+    #[test]
+    fn test_prompt_budget_reserves_answer_space() {
+        assert_eq!(prompt_budget(4096, None), 3072);
+        assert_eq!(prompt_budget(4096, Some(512)), 3584);
+        assert_eq!(prompt_budget(2048, Some(8192)), 1024);
+    }
+}
@@ -1,23 +1,40 @@
 use colored::*;
-use indicatif::{ProgressBar, ProgressStyle};
-use reqwest::blocking::Client;
-use std::time::Duration;
+use serde_json::{json, Value};
 
-use crate::services::ollama::generate_suggestion;
-use crate::services::supabase::semantic_search_notes;
-use crate::utils::{load_profile, load_supabase_config};
+use crate::budget::{self, BudgetReport};
+use crate::config::Config;
+use crate::context::AppContext;
+use crate::prompts;
+use crate::thinking::Reply;
+use crate::types::Note;
+use crate::utils::spinner;
+
+/// Result of a chat turn.
+#[derive(Debug)]
+pub enum ChatReply {
+    /// Embedding-only mode: the notes most relevant to the message.
+    Notes(Vec<Note>),
+    /// Normal LLM mode: the assistant's answer and any reasoning before it, with how
+    /// the prompt was fitted into the context window.
+    Answer { reply: Reply, budget: BudgetReport },
+}
+
+/// Earlier turns of a conversation included in chat prompts.
+const HISTORY_TURNS: usize = 6;
+
+/// One exchange in an ongoing conversation.
+#[derive(Debug, Clone)]
+pub struct ChatTurn {
+    pub user: String,
+    pub assistant: String,
+    /// Reasoning behind the answer; kept for transcripts, never sent back to the model.
+    pub thinking: Vec<String>,
+}
 
 /// Chats with the assistant using the configured LLM, user profile, and recent notes.
-pub fn chat_with_assistant(message: &str) {
-    let profile = match load_profile() {
-        Ok(p) => p,
-        Err(e) => {
-            println!("{}", format!("Error loading profile: {e}").red());
-            println!("Please run 'logswise-cli setup' first.");
-            return;
-        }
-    };
-    let llm_name = profile["llmName"].as_str().unwrap_or("").to_lowercase();
+/// With `explain`, also shows what had to be left out of the prompt.
+pub fn chat_with_assistant(ctx: &AppContext, message: &str, explain: bool) {
+    let llm_name = ctx.llm_name();
     if llm_name.is_empty() {
         println!(
             "{}",
@@ -25,25 +42,7 @@ pub fn chat_with_assistant(message: &str) {
         );
         return;
     }
-    let ollama_base_url = profile["ollamaBaseUrl"]
-        .as_str()
-        .unwrap_or("http://localhost:11434");
-    let ollama_url = format!("{ollama_base_url}/api/generate");
-    let ollama_embedding_url = format!("{ollama_base_url}/api/embeddings");
-    let ollama_model = profile["embeddingModel"]
-        .as_str()
-        .unwrap_or("nomic-embed-text");
-    let embedding_models = [
-        "nomic-embed-text",
-        "bge-base-en",
-        "all-minilm",
-        // Add more known embedding models here if needed
-    ];
-    let is_embedding = embedding_models
-        .iter()
-        .any(|m| llm_name == *m || llm_name.starts_with(m));
-    if is_embedding {
-        // Only perform embedding and semantic search, print results, and exit (no LLM generation)
+    if ctx.is_embedding_only() {
         println!(
             "⚡ Running in embedding-only mode (semantic search, no LLM generation). Model: {}",
             llm_name.cyan()
@@ -54,85 +53,30 @@ pub fn chat_with_assistant(message: &str) {
             llm_name.cyan()
         );
     }
-    let spinner = ProgressBar::new_spinner();
-    spinner.set_style(
-        ProgressStyle::default_spinner()
-            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    spinner.enable_steady_tick(Duration::from_millis(100));
-    spinner.set_message("Loading profile and preparing chat context...");
-
-    // Load Supabase config
-    let config = match load_supabase_config() {
-        Ok(cfg) => cfg,
-        Err(e) => {
-            println!("{}", format!("Error loading Supabase config: {e}").red());
-            println!("Please run 'logswise-cli setup' first.");
-            return;
-        }
-    };
-    let client = Client::new();
+    let spinner = spinner("Loading profile and preparing chat context...");
 
-    // Generate embedding for the chat message
-    let query_embedding = match crate::services::ollama::generate_embedding(
-        &client,
-        &ollama_embedding_url,
-        ollama_model,
-        message,
-    ) {
-        Ok(embedding) => embedding,
-        Err(msg) => {
+    match chat(ctx, message, &[], |msg| {
+        spinner.set_message(msg.to_string())
+    }) {
+        Ok(ChatReply::Notes(notes)) => {
             spinner.finish_and_clear();
-            println!(
-                "{}",
-                "❌ Could not generate embedding for chat message.".red()
-            );
-            println!("{}", msg.yellow());
-            return;
-        }
-    };
-
-    // Query Supabase for most similar notes (top 5)
-    let notes = semantic_search_notes(&client, &config, &query_embedding, 5);
-    let mut notes_context = String::new();
-    if !notes.is_empty() {
-        notes_context.push_str("\nRelevant Notes:");
-        for (i, content) in notes.iter().enumerate() {
-            notes_context.push_str(&format!("\n{}. {}", i + 1, content));
-        }
-    }
-
-    // If embedding-only mode, just show notes and return
-    if is_embedding {
-        spinner.finish_and_clear();
-        if !notes.is_empty() {
-            println!("\nRelevant Notes:");
-            for (i, content) in notes.iter().enumerate() {
-                println!("{}. {}", i + 1, content);
+            if !notes.is_empty() {
+                println!("\nRelevant Notes:");
+                for (i, note) in notes.iter().enumerate() {
+                    println!("{}. {}", i + 1, note.content);
+                }
+            } else {
+                println!("No relevant notes found.");
             }
-        } else {
-            println!("No relevant notes found.");
         }
-        return;
-    }
-
-    // Normal LLM mode: prepare context and generate response
-    let user_info = format!(
-        "User Info:\n- Profession: {}\n- Job Title: {}\n- Company Name: {}\n- Company Size: {}",
-        profile["profession"].as_str().unwrap_or(""),
-        profile["jobTitle"].as_str().unwrap_or(""),
-        profile["companyName"].as_str().unwrap_or(""),
-        profile["companySize"].as_str().unwrap_or("")
-    );
-    // Compose the full prompt for Ollama
-    let full_prompt = format!("{user_info}{notes_context}\n\nUser: {message}\nAssistant:");
-    spinner.set_message("Ollama: Sending request...");
-    match generate_suggestion(&client, &ollama_url, &llm_name, &full_prompt) {
-        Ok(response) => {
+        Ok(ChatReply::Answer { reply, budget }) => {
             spinner.finish_and_clear();
-            println!("{}", response.cyan());
+            if explain {
+                budget.print();
+                println!();
+            }
+            reply.print_thinking(ctx.generation.thinking.unwrap_or_default());
+            println!("{}", reply.answer.cyan());
         }
         Err(msg) => {
             spinner.finish_and_clear();
@@ -140,3 +84,138 @@ pub fn chat_with_assistant(message: &str) {
         }
     }
 }
+
+/// Runs one chat turn: retrieves relevant notes and, unless in embedding-only mode,
+/// asks the LLM for an answer. The last few turns of `history` are included so
+/// follow-up questions keep their context. `progress` receives status updates for the UI.
+pub fn chat<F: Fn(&str)>(
+    ctx: &AppContext,
+    message: &str,
+    history: &[ChatTurn],
+    progress: F,
+) -> Result<ChatReply, String> {
+    // Generate embedding for the chat message
+    let query_embedding = ctx
+        .llm
+        .embed(message)
+        .map_err(|msg| format!("❌ Could not generate embedding for chat message.\n{msg}"))?;
+
+    // Query Supabase for most similar notes (top 5), within the active project if any
+    let notes = ctx
+        .store
+        .semantic_search(&query_embedding, 5, ctx.active_project())
+        .unwrap_or_default();
+
+    // If embedding-only mode, just return the notes
+    if ctx.is_embedding_only() {
+        return Ok(ChatReply::Notes(notes));
+    }
+
+    // Normal LLM mode: fit the context into the model's window and generate a response
+    ctx.check_generation()?;
+    let (full_prompt, budget) = budget::assemble(
+        &ctx.prompts,
+        "chat",
+        chat_prompt_data(&ctx.config, &notes, history, message),
+        &budget::CHAT_SECTIONS,
+        ctx.prompt_budget(),
+    );
+    progress("Ollama: Sending request...");
+    ctx.llm
+        .generate(&full_prompt)
+        .map(|text| ChatReply::Answer {
+            reply: Reply::parse(&text),
+            budget,
+        })
+}
+
+/// Variables for the `chat` prompt template.
+fn chat_prompt_data(config: &Config, notes: &[Note], history: &[ChatTurn], message: &str) -> Value {
+    let notes: Vec<Value> = notes
+        .iter()
+        .map(|n| json!({ "content": n.content }))
+        .collect();
+    let recent = &history[history.len().saturating_sub(HISTORY_TURNS)..];
+    let history: Vec<Value> = recent
+        .iter()
+        .map(|t| json!({ "user": t.user, "assistant": t.assistant }))
+        .collect();
+    json!({
+        "profile": prompts::profile_data(config),
+        "has_notes": !notes.is_empty(),
+        "notes": notes,
+        "history": history,
+        "message": message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::{context, fake_llm, note, FakeStore};
+
+    #[test]
+    fn test_chat_prompt_includes_profile_and_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let llm = fake_llm(
+            "llama3",
+            "<think>They use println today.</think>\nUse structured logging.",
+        );
+        let prompts = llm.prompts.clone();
+        let store = FakeStore::default();
+        store
+            .notes
+            .borrow_mut()
+            .push(note("1", "Switched to tracing crate"));
+        let ctx = context(llm, store, dir.path());
+
+        let reply = chat(&ctx, "How should I log?", &[], |_| {}).unwrap();
+        let ChatReply::Answer { reply, .. } = reply else {
+            panic!("expected an answer");
+        };
+        assert_eq!(reply.answer, "Use structured logging.");
+        assert_eq!(reply.thinking, vec!["They use println today."]);
+        let prompt = &prompts.borrow()[0];
+        assert!(prompt.contains("- Company Name: TestCo"));
+        assert!(prompt.contains("1. Switched to tracing crate"));
+        assert!(prompt.ends_with("User: How should I log?\nAssistant:"));
+    }
+
+    #[test]
+    fn test_chat_prompt_keeps_recent_turns() {
+        let dir = tempfile::tempdir().unwrap();
+        let llm = fake_llm("llama3", "Friday.");
+        let prompts = llm.prompts.clone();
+        let ctx = context(llm, FakeStore::default(), dir.path());
+        let history: Vec<ChatTurn> = (1..=8)
+            .map(|i| ChatTurn {
+                user: format!("question {i}"),
+                assistant: format!("answer {i}"),
+                thinking: vec![],
+            })
+            .collect();
+
+        chat(&ctx, "And when is it due?", &history, |_| {}).unwrap();
+        let prompt = &prompts.borrow()[0];
+        assert!(!prompt.contains("question 2\n"));
+        assert!(prompt.contains("User: question 3\nAssistant: answer 3\n"));
+        assert!(prompt.ends_with("Assistant: answer 8\nUser: And when is it due?\nAssistant:"));
+    }
+
+    #[test]
+    fn test_chat_embedding_only_mode_skips_generation() {
+        let dir = tempfile::tempdir().unwrap();
+        let llm = fake_llm("nomic-embed-text", "unused");
+        let prompts = llm.prompts.clone();
+        let store = FakeStore::default();
+        store
+            .notes
+            .borrow_mut()
+            .push(note("1", "Release checklist"));
+        let ctx = context(llm, store, dir.path());
+
+        let reply = chat(&ctx, "release", &[], |_| {}).unwrap();
+        assert!(matches!(reply, ChatReply::Notes(ref n) if n.len() == 1));
+        assert!(prompts.borrow().is_empty());
+    }
+}
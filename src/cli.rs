@@ -1,4 +1,10 @@
-use clap::{Parser, Subcommand};
+use crate::dates::Period;
+use crate::export::ExportFormat;
+use crate::generation::GenerationOptions;
+use crate::suggestion_log::FeedbackReason;
+use crate::thinking::ThinkingMode;
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "logswise-cli")]
@@ -10,6 +16,15 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    /// Print how long model and storage calls took after the command finishes
+    #[arg(long, global = true)]
+    pub timings: bool,
+    /// Show more diagnostic output (-v info, -vv debug, -vvv trace)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+    /// Only show errors
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
 }
 
 #[derive(Subcommand)]
@@ -20,10 +35,292 @@ pub enum PersonalizeAction {
     Update,
     /// Show current personalization settings
     Show,
-    /// Provide feedback on suggestions
+    /// Rate a suggestion (same as 'feedback')
     Feedback {
-        /// Category of the suggestion to provide feedback on
+        /// Suggestion id (or the start of it); the latest suggestion when omitted
+        id: Option<String>,
+    },
+    /// Learn active hours, note cadence and topics from your notes and command history
+    Learn,
+    /// Show how suggestion ratings change prompts and note retrieval
+    Explain {
+        /// Request to preview the prompt changes for
+        query: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SuggestionAction {
+    /// List past suggestions with their ids and ratings
+    History {
+        /// Number of suggestions to show
+        #[arg(short, long, default_value_t = 10)]
+        count: usize,
+        /// Only show suggestions that have not been rated
+        #[arg(long)]
+        unrated: bool,
+    },
+    /// Print a past suggestion in full
+    Show {
+        /// Suggestion id (or the start of it); the latest suggestion when omitted
+        id: Option<String>,
+    },
+}
+
+/// Model and sampling overrides for one `chat` or `suggestion` run.
+#[derive(Args, Default)]
+pub struct GenerationArgs {
+    /// Model to generate with instead of the configured one
+    #[arg(long)]
+    pub model: Option<String>,
+    /// Sampling preset: precise, balanced or creative
+    #[arg(long)]
+    pub preset: Option<String>,
+    /// Sampling temperature (0-2; lower is more focused)
+    #[arg(long)]
+    pub temperature: Option<f32>,
+    /// Most tokens to generate
+    #[arg(long)]
+    pub max_tokens: Option<u32>,
+    /// Seed for reproducible output
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Turn reasoning of thinking models on or off (Ollama's `think` option)
+    #[arg(long)]
+    pub think: Option<bool>,
+    /// How to print the model's reasoning
+    #[arg(long, value_enum)]
+    pub thinking: Option<ThinkingMode>,
+}
+
+impl GenerationArgs {
+    pub fn options(&self) -> GenerationOptions {
+        GenerationOptions {
+            model: self.model.clone(),
+            preset: self.preset.clone(),
+            temperature: self.temperature,
+            num_predict: self.max_tokens,
+            seed: self.seed,
+            think: self.think,
+            thinking: self.thinking,
+            ..GenerationOptions::default()
+        }
+    }
+}
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct SuggestionArgs {
+    /// The query to get suggestions for
+    #[arg(required = true)]
+    pub query: Option<String>,
+    /// Show the detected intent, how it was classified and what was left out of the prompt
+    #[arg(long)]
+    pub explain: bool,
+    #[command(flatten)]
+    pub generation: GenerationArgs,
+    #[command(subcommand)]
+    pub action: Option<SuggestionAction>,
+}
+
+#[derive(Subcommand)]
+pub enum PromptAction {
+    /// List prompt templates and whether they are customized
+    List,
+    /// Print the template currently in effect
+    Show {
+        /// Prompt name (e.g. chat, suggestion)
+        name: String,
+        /// Print the built-in template even if it was customized
+        #[arg(long)]
+        default: bool,
+    },
+    /// Open a template in $EDITOR, creating a copy of the built-in one first
+    Edit {
+        /// Prompt name (e.g. chat, suggestion)
+        name: String,
+    },
+    /// Discard customizations and go back to the built-in template
+    Reset {
+        /// Prompt name (e.g. chat, suggestion)
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GoalAction {
+    /// Add a goal
+    Add {
+        /// What you want to achieve
+        description: String,
+        /// Category (career, skill, project, personal, ...)
+        #[arg(long, default_value = "personal")]
+        category: String,
+        /// Timeline: short_term, medium_term or long_term
+        #[arg(long, default_value = "medium_term")]
+        timeline: String,
+    },
+    /// List goals
+    List {
+        /// Include completed goals
+        #[arg(long)]
+        all: bool,
+    },
+    /// Update a goal's progress or details
+    Update {
+        /// Goal number from 'goal list' or its id
+        goal: String,
+        /// Progress in percent (0-100)
+        #[arg(long, value_parser = clap::value_parser!(u32).range(0..=100))]
+        progress: Option<u32>,
+        /// Let the LLM estimate progress from the notes linked to the goal
+        #[arg(long, conflicts_with = "progress")]
+        estimate: bool,
+        /// New description
+        #[arg(long)]
+        description: Option<String>,
+        /// New category
+        #[arg(long)]
         category: Option<String>,
+        /// New timeline
+        #[arg(long)]
+        timeline: Option<String>,
+    },
+    /// Mark a goal as completed
+    Complete {
+        /// Goal number from 'goal list' or its id
+        goal: String,
+    },
+    /// Delete a goal
+    Remove {
+        /// Goal number from 'goal list' or its id
+        goal: String,
+    },
+    /// Link an existing note to a goal
+    Link {
+        /// Goal number from 'goal list' or its id
+        goal: String,
+        /// Note id (or the start of it) as shown by 'recent'
+        note: String,
+    },
+    /// Summarize progress across all goals
+    Report,
+}
+
+#[derive(Subcommand)]
+pub enum ModelsAction {
+    /// List installed models and what they can do (the default)
+    List {
+        /// Query Ollama again instead of using cached model information
+        #[arg(long)]
+        refresh: bool,
+    },
+    /// Download a model from the Ollama library
+    Pull {
+        /// Model name, e.g. llama3 or nomic-embed-text
+        name: String,
+    },
+    /// Delete an installed model
+    Remove {
+        /// Model name
+        name: String,
+        /// Do not ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Show a model's details
+    Info {
+        /// Model name
+        name: String,
+    },
+    /// Choose the models used for generation and embeddings
+    #[command(group(ArgGroup::new("role").required(true).multiple(true).args(["llm", "embedding"])))]
+    Set {
+        /// Model for chat and suggestions
+        #[arg(long)]
+        llm: Option<String>,
+        /// Model for embeddings (semantic search)
+        #[arg(long)]
+        embedding: Option<String>,
+        /// Do not ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProjectAction {
+    /// Add a project
+    Add {
+        /// Project name
+        name: String,
+        /// Technologies used, comma-separated
+        #[arg(long, value_delimiter = ',')]
+        tech: Option<Vec<String>>,
+        /// Number of people on the project
+        #[arg(long)]
+        team_size: Option<u32>,
+        /// Deadline pressure: low, medium or high
+        #[arg(long)]
+        pressure: Option<String>,
+        /// A current challenge (repeatable)
+        #[arg(long = "challenge")]
+        challenges: Vec<String>,
+    },
+    /// List projects
+    List {
+        /// Include archived projects
+        #[arg(long)]
+        all: bool,
+    },
+    /// Show a project's details and recent notes (defaults to the active project)
+    Show {
+        /// Project name
+        name: Option<String>,
+    },
+    /// Change a project's details
+    Edit {
+        /// Project name
+        name: String,
+        /// New name
+        #[arg(long)]
+        rename: Option<String>,
+        /// Technologies used, comma-separated (replaces the current list)
+        #[arg(long, value_delimiter = ',')]
+        tech: Option<Vec<String>>,
+        /// Number of people on the project
+        #[arg(long)]
+        team_size: Option<u32>,
+        /// Deadline pressure: low, medium or high
+        #[arg(long)]
+        pressure: Option<String>,
+        /// Record a challenge (repeatable)
+        #[arg(long = "add-challenge")]
+        add_challenges: Vec<String>,
+        /// Remove a challenge (repeatable)
+        #[arg(long = "remove-challenge")]
+        remove_challenges: Vec<String>,
+    },
+    /// Archive a project so it no longer shapes suggestions
+    Archive {
+        /// Project name
+        name: String,
+        /// Restore an archived project
+        #[arg(long)]
+        undo: bool,
+    },
+    /// Set the active project that notes are tagged with and retrieved from
+    Use {
+        /// Project name (omit to show the active project)
+        name: Option<String>,
+        /// Stop scoping notes to a project
+        #[arg(long, conflicts_with = "name")]
+        clear: bool,
+    },
+    /// Extract current challenges from the project's recent notes
+    Challenges {
+        /// Project name (defaults to the active project)
+        name: Option<String>,
     },
 }
 
@@ -35,47 +332,135 @@ pub enum Commands {
     Note {
         /// The content of the note to add
         content: String,
+        /// Link the note to a goal (number from 'goal list' or id)
+        #[arg(long)]
+        goal: Option<String>,
     },
     /// Add a note to your collection (alias for 'note')
     N {
         /// The content of the note to add
         content: String,
+        /// Link the note to a goal (number from 'goal list' or id)
+        #[arg(long)]
+        goal: Option<String>,
     },
-    /// Get context-aware suggestions for a query
-    Suggestion {
-        /// The query to get suggestions for
-        query: String,
-    },
+    /// Get context-aware suggestions for a query, or browse past ones
+    Suggestion(SuggestionArgs),
     /// Get context-aware suggestions for a query (alias for 'suggestion')
-    S {
-        /// The query to get suggestions for
-        query: String,
+    S(SuggestionArgs),
+    /// Rate a suggestion; the ratings tune future suggestions
+    Feedback {
+        /// Suggestion id (or the start of it) from 'suggestion history'; the latest
+        /// suggestion when omitted
+        id: Option<String>,
+        /// Rating from 1 (not useful) to 5 (very useful); skips the questions
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=5))]
+        rating: Option<u8>,
+        /// Mark the suggestion as helpful (default: rating of 4 or more)
+        #[arg(long, conflicts_with = "not_helpful")]
+        helpful: bool,
+        /// Mark the suggestion as not helpful
+        #[arg(long)]
+        not_helpful: bool,
+        /// What was wrong with the suggestion (repeatable)
+        #[arg(long = "reason", value_enum)]
+        reasons: Vec<FeedbackReason>,
+        /// Comment to keep with the rating
+        #[arg(long)]
+        comment: Option<String>,
     },
     /// Chat with the AI assistant
     Chat {
         /// The message to send to the assistant
         message: String,
+        /// Show how the prompt was fitted into the model's context window
+        #[arg(long)]
+        explain: bool,
+        #[command(flatten)]
+        generation: GenerationArgs,
     },
     /// Chat with the AI assistant (alias for 'chat')
     C {
         /// The message to send to the assistant
         message: String,
+        /// Show how the prompt was fitted into the model's context window
+        #[arg(long)]
+        explain: bool,
+        #[command(flatten)]
+        generation: GenerationArgs,
     },
-    /// Start interactive mode for continuous note-taking and chatting
+    /// Start an interactive prompt: chat with memory of earlier turns and /commands
     Interactive,
+    /// Browse, search, edit and chat about notes in a full-screen terminal UI
+    Tui,
     /// Set up enhanced personalization for better suggestions
     Personalize {
         #[command(subcommand)]
         action: Option<PersonalizeAction>,
     },
+    /// Manage projects and the active project
+    Project {
+        #[command(subcommand)]
+        action: ProjectAction,
+    },
+    /// Track goals and their progress
+    Goal {
+        #[command(subcommand)]
+        action: GoalAction,
+    },
+    /// Summarize your notes for a period into a Markdown accomplishments report
+    Report {
+        /// Period to cover, counted back from today (default: week, or custom with --from)
+        #[arg(long, value_enum)]
+        period: Option<Period>,
+        /// First day of a custom period (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<String>,
+        /// Last day of a custom period (YYYY-MM-DD, default: today)
+        #[arg(long)]
+        to: Option<String>,
+        /// Only include notes tagged with this project
+        #[arg(long)]
+        project: Option<String>,
+        /// Markdown file to write (default: logswise-report-<from>_<to>.md)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Draft a Yesterday/Today/Blockers standup from notes since the last working day
+    Standup {
+        /// Format the notes as they are instead of asking the LLM
+        #[arg(long)]
+        no_llm: bool,
+        /// Include notes from every project, not just the active one
+        #[arg(long)]
+        all: bool,
+    },
+    /// Manage the prompt templates used for chat and suggestions
+    Prompt {
+        #[command(subcommand)]
+        action: PromptAction,
+    },
     /// Show information about Logswise CLI
     About,
-    /// Display your profile and configuration stats
-    Stats,
+    /// Show note, model and suggestion usage statistics
+    Stats {
+        /// Only count activity from this time on (e.g. monday, 30d, 2026-09-01)
+        #[arg(long)]
+        since: Option<String>,
+        /// Print the statistics as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Explain how Logswise works
     How,
-    /// Show information about embedding models vs LLMs
-    Models,
+    /// List, download, remove and choose Ollama models
+    Models {
+        #[command(subcommand)]
+        action: Option<ModelsAction>,
+        /// Query Ollama again instead of using cached model information
+        #[arg(long)]
+        refresh: bool,
+    },
     /// Show troubleshooting tips for model configuration
     Troubleshoot,
     /// Explain how context is used in suggestions and chat
@@ -83,13 +468,70 @@ pub enum Commands {
     /// Show detailed help and examples  
     Guide,
     /// Check configuration health and connectivity
-    Doctor,
+    Doctor {
+        /// Apply safe fixes: fill default settings, pull missing models, create the notes
+        /// table, remove leftover test notes and sync queued notes
+        #[arg(long)]
+        fix: bool,
+        /// Print the results as JSON with a stable id per check
+        #[arg(long)]
+        json: bool,
+        /// Write a bug report bundle with version, redacted config and recent debug logs
+        #[arg(long)]
+        bundle: bool,
+    },
     /// Show recent notes
     Recent {
         /// Number of recent notes to show (default: 5)
         #[arg(short, long, default_value = "5")]
         count: usize,
+        /// Include notes from every project, not just the active one
+        #[arg(long)]
+        all: bool,
+        /// Only notes from this time on (e.g. yesterday, monday, 3d, 2026-09-01)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only notes up to this time; a date includes the whole day
+        #[arg(long)]
+        until: Option<String>,
+    },
+    /// Find notes containing some text
+    Search {
+        /// Text to look for (case-insensitive)
+        text: String,
+        /// Maximum number of notes to show
+        #[arg(short, long, default_value = "20")]
+        count: usize,
+        /// Search every project, not just the active one
+        #[arg(long)]
+        all: bool,
+        /// Only notes from this time on (e.g. yesterday, monday, 3d, 2026-09-01)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only notes up to this time; a date includes the whole day
+        #[arg(long)]
+        until: Option<String>,
+    },
+    /// Export notes as JSON or Markdown
+    Export {
+        /// Output format
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: ExportFormat,
+        /// File to write (default: print to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Export every project, not just the active one
+        #[arg(long)]
+        all: bool,
+        /// Only notes from this time on (e.g. yesterday, monday, 3d, 2026-09-01)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only notes up to this time; a date includes the whole day
+        #[arg(long)]
+        until: Option<String>,
     },
+    /// Send notes queued while Supabase was unreachable
+    Sync,
     /// Generate shell completions
     Completions {
         /// Shell type (bash, zsh, fish, powershell)
@@ -0,0 +1,256 @@
+use crate::generation::GenerationSettings;
+use crate::logging::{self, LogSettings};
+use crate::services::http::HttpSettings;
+use crate::types::SupabaseConfig;
+use crate::utils::backup_file;
+use colored::*;
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Version of the `setup.json` layout written by this build.
+pub const CONFIG_SCHEMA_VERSION: u32 = 1;
+
+pub const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434";
+pub const DEFAULT_EMBEDDING_MODEL: &str = "nomic-embed-text";
+
+/// User profile and service settings stored in `~/.logswise/setup.json`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Config {
+    pub schema_version: u32,
+    pub profession: String,
+    pub job_title: String,
+    pub company_name: String,
+    pub company_size: String,
+    pub years_experience: String,
+    pub preferred_language: String,
+    pub work_mode: String,
+    pub llm_name: String,
+    pub ollama_base_url: String,
+    pub embedding_model: String,
+    pub supabase_url: String,
+    pub supabase_api_key: String,
+    /// Project that new notes are tagged with and retrieval is scoped to (empty for none).
+    pub active_project: String,
+    /// IANA time zone for standups and reports (e.g. `Europe/Berlin`); empty uses the system's.
+    pub timezone: String,
+    pub http: HttpSettings,
+    /// Sampling settings and per-command model overrides for text generation.
+    pub generation: GenerationSettings,
+    pub logging: LogSettings,
+    /// Keys this version does not know about, kept so saving never drops them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            schema_version: CONFIG_SCHEMA_VERSION,
+            profession: String::new(),
+            job_title: String::new(),
+            company_name: String::new(),
+            company_size: String::new(),
+            years_experience: String::new(),
+            preferred_language: String::new(),
+            work_mode: String::new(),
+            llm_name: String::new(),
+            ollama_base_url: DEFAULT_OLLAMA_BASE_URL.to_string(),
+            embedding_model: DEFAULT_EMBEDDING_MODEL.to_string(),
+            supabase_url: String::new(),
+            supabase_api_key: String::new(),
+            active_project: String::new(),
+            timezone: String::new(),
+            http: HttpSettings::default(),
+            generation: GenerationSettings::default(),
+            logging: LogSettings::default(),
+            extra: Map::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Location of the configuration file (`~/.logswise/setup.json`).
+    pub fn path() -> Result<PathBuf, String> {
+        let mut path = home_dir().ok_or("Could not determine home directory")?;
+        path.push(".logswise/setup.json");
+        Ok(path)
+    }
+
+    /// Loads the configuration, migrating files written by older versions.
+    ///
+    /// The API key is registered for redaction so it never reaches the logs.
+    pub fn load() -> Result<Self, String> {
+        let config = Self::load_from(&Self::path()?)?;
+        logging::configure(&config.logging, &[&config.supabase_api_key]);
+        Ok(config)
+    }
+
+    /// Loads the configuration from `path`.
+    ///
+    /// Files with an older `schemaVersion` are upgraded in place after a copy of the
+    /// original has been written next to them (e.g. `setup.json.v0.bak`).
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let data = fs::read_to_string(path)
+            .map_err(|_| "Setup not found. Please run 'logswise-cli setup' first.".to_string())?;
+        let value: Value = serde_json::from_str(&data)
+            .map_err(|_| "Failed to parse setup.json. Please check the file format.".to_string())?;
+
+        let (value, migrated_from) = migrate(value)?;
+        let config: Self =
+            serde_json::from_value(value).map_err(|e| format!("Invalid setup.json: {e}"))?;
+
+        if let Some(version) = migrated_from {
+            let backup = backup_file(path, version)?;
+            config.save_to(path)?;
+            // stderr, so the first run after an upgrade keeps `--json` output parseable
+            eprintln!(
+                "{}",
+                format!(
+                    "ℹ️  Upgraded setup.json to schema v{CONFIG_SCHEMA_VERSION} (backup: {})",
+                    backup.display()
+                )
+                .cyan()
+            );
+        }
+        Ok(config)
+    }
+
+    /// Writes the configuration to `~/.logswise/setup.json`.
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to(&Self::path()?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+        }
+        let mut config = self.clone();
+        config.schema_version = CONFIG_SCHEMA_VERSION;
+        let data = serde_json::to_string_pretty(&config)
+            .map_err(|e| format!("Failed to encode setup.json: {e}"))?;
+        fs::write(path, data).map_err(|e| format!("Failed to save setup.json: {e}"))
+    }
+
+    /// Supabase connection settings, which every note command requires.
+    pub fn supabase(&self) -> Result<SupabaseConfig, String> {
+        if self.supabase_url.trim().is_empty() {
+            return Err("Missing 'supabaseUrl' in setup.json".to_string());
+        }
+        if self.supabase_api_key.trim().is_empty() {
+            return Err("Missing 'supabaseApiKey' in setup.json".to_string());
+        }
+        Ok(SupabaseConfig {
+            project_url: self.supabase_url.clone(),
+            api_key: self.supabase_api_key.clone(),
+        })
+    }
+}
+
+/// Upgrades a raw `setup.json` document to [`CONFIG_SCHEMA_VERSION`].
+///
+/// Returns the version the document started at when anything had to change.
+fn migrate(mut value: Value) -> Result<(Value, Option<u32>), String> {
+    let obj = value
+        .as_object_mut()
+        .ok_or("Failed to parse setup.json. Please check the file format.")?;
+    let original = obj
+        .get("schemaVersion")
+        .and_then(Value::as_u64)
+        .unwrap_or(0) as u32;
+    if original > CONFIG_SCHEMA_VERSION {
+        return Err(format!(
+            "setup.json uses schema v{original}, but this version of logswise-cli only understands up to v{CONFIG_SCHEMA_VERSION}. Please upgrade logswise-cli."
+        ));
+    }
+
+    let mut version = original;
+    while version < CONFIG_SCHEMA_VERSION {
+        match version {
+            0 => migrate_v0_to_v1(obj),
+            _ => unreachable!("no migration from schema v{version}"),
+        }
+        version += 1;
+    }
+    obj.insert("schemaVersion".to_string(), Value::from(version));
+
+    let migrated = (original != CONFIG_SCHEMA_VERSION).then_some(original);
+    Ok((value, migrated))
+}
+
+/// v0 files were written without a version. Null values are dropped so defaults apply,
+/// and trailing slashes are stripped from service URLs to avoid `//api/...` paths.
+fn migrate_v0_to_v1(obj: &mut Map<String, Value>) {
+    obj.retain(|_, v| !v.is_null());
+    for key in ["ollamaBaseUrl", "supabaseUrl"] {
+        if let Some(Value::String(url)) = obj.get_mut(key) {
+            *url = url.trim().trim_end_matches('/').to_string();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_load_migrates_v0_file_with_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("setup.json");
+        let v0 = json!({
+            "profession": "Software Developer",
+            "llmName": "llama3",
+            "ollamaBaseUrl": "http://localhost:11434/",
+            "supabaseUrl": "https://test.supabase.co",
+            "supabaseApiKey": "test-key-1234567890",
+            "embeddingModel": null,
+            "customSetting": true
+        });
+        fs::write(&path, v0.to_string()).unwrap();
+
+        let config = Config::load_from(&path).unwrap();
+        assert_eq!(config.schema_version, CONFIG_SCHEMA_VERSION);
+        assert_eq!(config.ollama_base_url, "http://localhost:11434");
+        assert_eq!(config.embedding_model, DEFAULT_EMBEDDING_MODEL);
+        assert_eq!(config.supabase().unwrap().api_key, "test-key-1234567890");
+
+        let backup: Value = serde_json::from_str(
+            &fs::read_to_string(dir.path().join("setup.json.v0.bak")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(backup, v0);
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["schemaVersion"], CONFIG_SCHEMA_VERSION);
+        assert_eq!(saved["customSetting"], true);
+        assert_eq!(saved["llmName"], "llama3");
+
+        // Loading again is a no-op
+        assert_eq!(Config::load_from(&path).unwrap(), config);
+    }
+
+    #[test]
+    fn test_load_rejects_newer_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("setup.json");
+        fs::write(&path, json!({ "schemaVersion": 99 }).to_string()).unwrap();
+        let err = Config::load_from(&path).unwrap_err();
+        assert!(err.contains("schema v99"));
+    }
+
+    #[test]
+    fn test_supabase_requires_url_and_key() {
+        let config = Config {
+            supabase_url: "https://test.supabase.co".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(
+            config.supabase().unwrap_err(),
+            "Missing 'supabaseApiKey' in setup.json"
+        );
+    }
+}
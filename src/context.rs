@@ -0,0 +1,398 @@
+use crate::budget;
+use crate::config::Config;
+use crate::dates::Zone;
+use crate::generation::GenerationOptions;
+use crate::intent::IntentClassifier;
+use crate::metrics::MetricsLog;
+use crate::models::ModelCatalog;
+use crate::outbox::Outbox;
+use crate::prompts::Prompts;
+use crate::services::http::HttpClient;
+use crate::services::ollama::{LlmProvider, OllamaProvider};
+use crate::services::supabase::{NoteStore, SupabaseStore};
+use crate::suggestion_log::SuggestionLog;
+use crate::tokens::DEFAULT_CONTEXT_TOKENS;
+use crate::usage_log::UsageLog;
+use std::cell::OnceCell;
+
+/// Everything a command needs to talk to the configured services.
+///
+/// Built once per process by the router and passed to handlers, so configuration is
+/// loaded a single time and tests can substitute fake services.
+pub struct AppContext {
+    pub config: Config,
+    /// Shared client for API calls not covered by the provider and store traits.
+    pub http: HttpClient,
+    pub llm: Box<dyn LlmProvider>,
+    pub store: Box<dyn NoteStore>,
+    pub outbox: Outbox,
+    pub prompts: Prompts,
+    pub intents: IntentClassifier,
+    pub suggestions: SuggestionLog,
+    pub usage: UsageLog,
+    /// Latency and token counts of model calls.
+    pub metrics: MetricsLog,
+    /// Model override and sampling settings in effect for generation.
+    pub generation: GenerationOptions,
+    /// Installed Ollama models, discovered on first use.
+    models: OnceCell<ModelCatalog>,
+    /// Why the `generation` settings in setup.json could not be applied.
+    generation_error: Option<String>,
+}
+
+impl AppContext {
+    /// Loads configuration from `~/.logswise/setup.json` and connects the services.
+    pub fn load() -> Result<Self, String> {
+        let config = Config::load()?;
+        let supabase = config.supabase()?;
+        let http = HttpClient::from_config(&config);
+        let metrics = MetricsLog::open_default()?;
+        // Invalid settings only stop the commands that generate (see `check_generation`)
+        let (generation, generation_error) = match config
+            .generation
+            .resolve(None, &GenerationOptions::default())
+        {
+            Ok(generation) => (generation, None),
+            Err(e) => {
+                log::warn!("{e}. Using the default generation settings.");
+                (GenerationOptions::default(), Some(e))
+            }
+        };
+
+        let model = generation.model.as_deref().unwrap_or(&config.llm_name);
+        let llm = OllamaProvider::new(
+            http.clone(),
+            &config.ollama_base_url,
+            &model.to_lowercase(),
+            &config.embedding_model,
+        )
+        .with_options(generation.clone())
+        .with_metrics(metrics.clone());
+        let store = SupabaseStore::new(http.clone(), supabase).with_metrics(metrics.clone());
+
+        Ok(Self {
+            config,
+            http,
+            llm: Box::new(llm),
+            store: Box::new(store),
+            outbox: Outbox::open_default()?,
+            prompts: Prompts::open_default()?,
+            intents: IntentClassifier::open_default()?,
+            suggestions: SuggestionLog::open_default()?,
+            usage: UsageLog::open_default()?,
+            metrics,
+            generation,
+            models: OnceCell::new(),
+            generation_error,
+        })
+    }
+
+    /// Project that note capture and retrieval are scoped to, if one is active.
+    pub fn active_project(&self) -> Option<&str> {
+        Some(self.config.active_project.trim()).filter(|p| !p.is_empty())
+    }
+
+    /// Time zone dates are shown and counted in, from the `timezone` setting. An invalid
+    /// setting falls back to the system time zone; `doctor` reports it.
+    pub fn zone(&self) -> Zone {
+        Zone::parse(&self.config.timezone).unwrap_or(Zone::Local)
+    }
+
+    /// Name of the configured generation model (lowercase, may be empty).
+    pub fn llm_name(&self) -> &str {
+        self.llm.model()
+    }
+
+    /// Switches generation to `model` for the rest of this process. `setup.json` keeps
+    /// the configured default.
+    pub fn use_model(&mut self, model: &str) {
+        self.config.llm_name = model.to_string();
+        self.llm = Box::new(
+            OllamaProvider::new(
+                self.http.clone(),
+                &self.config.ollama_base_url,
+                &model.to_lowercase(),
+                &self.config.embedding_model,
+            )
+            .with_options(self.generation.clone())
+            .with_metrics(self.metrics.clone()),
+        );
+    }
+
+    /// Applies the `generation` settings for `command` (its section in setup.json, then
+    /// the command-line `flags`) for the rest of this process.
+    pub fn configure_generation(
+        &mut self,
+        command: &str,
+        flags: &GenerationOptions,
+    ) -> Result<(), String> {
+        self.generation = self.config.generation.resolve(Some(command), flags)?;
+        self.generation_error = None;
+        let model = match &self.generation.model {
+            Some(model) => model.clone(),
+            None => self.config.llm_name.clone(),
+        };
+        self.use_model(&model);
+        Ok(())
+    }
+
+    /// Fails when the `generation` settings in setup.json are invalid, so text is never
+    /// generated with settings other than the configured ones.
+    pub fn check_generation(&self) -> Result<(), String> {
+        match &self.generation_error {
+            Some(e) => Err(format!("{e}. Fix the generation section in setup.json.")),
+            None => Ok(()),
+        }
+    }
+
+    /// Models installed on the configured Ollama server (cached between runs).
+    pub fn models(&self) -> &ModelCatalog {
+        self.models
+            .get_or_init(|| ModelCatalog::load(&self.http, &self.config.ollama_base_url))
+    }
+
+    /// Context window prompts can use: the model's own limit, capped at what Ollama
+    /// allocates (its default, or `numCtx` from the generation settings).
+    pub fn context_tokens(&self) -> usize {
+        let allocated = self
+            .generation
+            .num_ctx
+            .map_or(DEFAULT_CONTEXT_TOKENS, |n| n as usize);
+        self.models()
+            .find(self.llm_name())
+            .and_then(|m| m.context_length)
+            .map_or(allocated, |n| (n as usize).min(allocated))
+    }
+
+    /// Tokens a prompt may use: the context window minus room for the answer.
+    pub fn prompt_budget(&self) -> usize {
+        budget::prompt_budget(self.context_tokens(), self.generation.num_predict)
+    }
+
+    /// True when the configured LLM can only produce embeddings, so only semantic search
+    /// is possible.
+    pub fn is_embedding_only(&self) -> bool {
+        self.models().is_embedding_only(self.llm_name())
+    }
+}
+
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+    use crate::services::supabase::{NoteChanges, NoteQuery, StoreError};
+    use crate::types::Note;
+    use chrono::{DateTime, Utc};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// LLM stand-in that returns a fixed embedding and echoes a canned reply.
+    pub struct FakeLlm {
+        pub model: String,
+        pub reply: Result<String, String>,
+        pub prompts: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl LlmProvider for FakeLlm {
+        fn model(&self) -> &str {
+            &self.model
+        }
+
+        fn embedding_model(&self) -> &str {
+            "fake-embed"
+        }
+
+        fn embed(&self, _text: &str) -> Result<Vec<f32>, String> {
+            Ok(vec![0.5, 0.5])
+        }
+
+        fn generate(&self, prompt: &str) -> Result<String, String> {
+            self.prompts.borrow_mut().push(prompt.to_string());
+            self.reply.clone()
+        }
+    }
+
+    /// In-memory note store; `offline` makes every write fail like an unreachable backend,
+    /// `rejecting` makes inserts fail like a request Supabase refuses, and
+    /// `without_project_column` like a notes table created before projects existed.
+    #[derive(Default)]
+    pub struct FakeStore {
+        pub notes: Rc<RefCell<Vec<Note>>>,
+        pub offline: bool,
+        pub rejecting: bool,
+        pub without_project_column: bool,
+    }
+
+    impl NoteStore for FakeStore {
+        fn insert_note(&self, note: &Note) -> Result<(), StoreError> {
+            if self.offline {
+                return Err(StoreError {
+                    status: None,
+                    message: "Network error: connection refused".to_string(),
+                });
+            }
+            if self.rejecting {
+                return Err(StoreError {
+                    status: Some(400),
+                    message: "HTTP 400 Bad Request: invalid input syntax".to_string(),
+                });
+            }
+            if self.without_project_column && note.project.is_some() {
+                return Err(StoreError {
+                    status: Some(400),
+                    message: r#"HTTP 400 Bad Request: {"code":"PGRST204","message":"Could not find the 'project' column of 'notes' in the schema cache"}"#.to_string(),
+                });
+            }
+            self.notes.borrow_mut().push(note.clone());
+            Ok(())
+        }
+
+        fn update_note(&self, id: &str, changes: &NoteChanges) -> Result<(), String> {
+            if self.offline {
+                return Err("Network error: connection refused".to_string());
+            }
+            let mut notes = self.notes.borrow_mut();
+            let note = notes
+                .iter_mut()
+                .find(|n| n.id == id)
+                .ok_or("HTTP 404: note not found")?;
+            if let Some((content, embedding)) = &changes.content {
+                note.content = content.clone();
+                note.embedding = embedding.clone();
+            }
+            if let Some(project) = &changes.project {
+                note.project = project.clone();
+            }
+            Ok(())
+        }
+
+        fn delete_note(&self, id: &str) -> Result<(), String> {
+            if self.offline {
+                return Err("Network error: connection refused".to_string());
+            }
+            self.notes.borrow_mut().retain(|n| n.id != id);
+            Ok(())
+        }
+
+        fn semantic_search(
+            &self,
+            _embedding: &[f32],
+            match_count: usize,
+            project: Option<&str>,
+        ) -> Result<Vec<Note>, String> {
+            Ok(self
+                .notes
+                .borrow()
+                .iter()
+                .filter(|n| project.is_none() || n.project.as_deref() == project)
+                .take(match_count)
+                .cloned()
+                .collect())
+        }
+
+        fn find_notes(&self, query: &NoteQuery) -> Result<Vec<Note>, String> {
+            Ok(self
+                .notes
+                .borrow()
+                .iter()
+                .rev()
+                .filter(|n| matches(n, query))
+                .take(query.limit)
+                .cloned()
+                .collect())
+        }
+
+        fn count_notes(&self, query: &NoteQuery) -> Result<usize, String> {
+            Ok(self
+                .notes
+                .borrow()
+                .iter()
+                .filter(|n| matches(n, query))
+                .count())
+        }
+
+        fn notes_between(
+            &self,
+            start: DateTime<Utc>,
+            end: DateTime<Utc>,
+            project: Option<&str>,
+        ) -> Result<Vec<Note>, String> {
+            Ok(self
+                .notes
+                .borrow()
+                .iter()
+                .filter(|n| {
+                    DateTime::parse_from_rfc3339(&n.created_at).is_ok_and(|t| t >= start && t < end)
+                })
+                .filter(|n| project.is_none() || n.project.as_deref() == project)
+                .cloned()
+                .collect())
+        }
+
+        fn notes_by_ids(&self, ids: &[String]) -> Result<Vec<Note>, String> {
+            Ok(self
+                .notes
+                .borrow()
+                .iter()
+                .filter(|n| ids.contains(&n.id))
+                .cloned()
+                .collect())
+        }
+    }
+
+    fn matches(note: &Note, query: &NoteQuery) -> bool {
+        let created = DateTime::parse_from_rfc3339(&note.created_at).ok();
+        let text = query.text.map(str::to_lowercase);
+        (query.project.is_none() || note.project.as_deref() == query.project)
+            && query.since.is_none_or(|s| created.is_some_and(|t| t >= s))
+            && query.until.is_none_or(|u| created.is_some_and(|t| t < u))
+            && text
+                .as_ref()
+                .is_none_or(|t| note.content.to_lowercase().contains(t))
+            && query.embedded.is_none_or(|e| note.embedding.is_some() == e)
+    }
+
+    /// Builds a context around fake services with its outbox in `dir`.
+    pub fn context(llm: FakeLlm, store: FakeStore, dir: &std::path::Path) -> AppContext {
+        AppContext {
+            config: Config {
+                profession: "Software Developer".to_string(),
+                job_title: "Senior".to_string(),
+                company_name: "TestCo".to_string(),
+                company_size: "10-100".to_string(),
+                llm_name: llm.model.clone(),
+                ..Config::default()
+            },
+            http: HttpClient::default(),
+            llm: Box::new(llm),
+            store: Box::new(store),
+            outbox: Outbox::at(dir.join("outbox.jsonl")),
+            prompts: Prompts::at(dir.join("prompts")),
+            intents: IntentClassifier::at(dir),
+            suggestions: SuggestionLog::at(dir.join("suggestions.jsonl")),
+            usage: UsageLog::at(dir.join("usage.jsonl")),
+            metrics: MetricsLog::at(dir.join("metrics.jsonl")),
+            generation: GenerationOptions::default(),
+            // No Ollama in tests: model capabilities are judged by name
+            models: OnceCell::from(ModelCatalog::default()),
+            generation_error: None,
+        }
+    }
+
+    pub fn fake_llm(model: &str, reply: &str) -> FakeLlm {
+        FakeLlm {
+            model: model.to_string(),
+            reply: Ok(reply.to_string()),
+            prompts: Rc::new(RefCell::new(vec![])),
+        }
+    }
+
+    pub fn note(id: &str, content: &str) -> Note {
+        Note {
+            id: id.to_string(),
+            content: content.to_string(),
+            created_at: "2026-10-01T09:00:00Z".to_string(),
+            embedding: None,
+            project: None,
+        }
+    }
+}
@@ -0,0 +1,375 @@
+use chrono::{
+    DateTime, Datelike, Days, Duration, Local, Months, NaiveDate, NaiveDateTime, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
+use clap::ValueEnum;
+
+/// Reporting windows, counted back from today (inclusive).
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Period {
+    /// The last 7 days
+    Week,
+    /// The last month
+    Month,
+    /// The last 3 months
+    Quarter,
+    /// Between --from and --to
+    Custom,
+}
+
+/// Time zone that calendar days are counted in: the system's, or one set in setup.json.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zone {
+    Local,
+    Named(Tz),
+}
+
+impl Zone {
+    /// Parses an IANA name such as `Europe/Berlin`; empty means the system time zone.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Ok(Self::Local);
+        }
+        name.parse::<Tz>().map(Self::Named).map_err(|_| {
+            format!("Unknown time zone '{name}'. Use an IANA name such as Europe/Berlin.")
+        })
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.local(Utc::now()).date()
+    }
+
+    /// Wall-clock time of `time` in this zone.
+    pub fn local(&self, time: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Self::Local => time.with_timezone(&Local).naive_local(),
+            Self::Named(tz) => time.with_timezone(tz).naive_local(),
+        }
+    }
+
+    /// The instant a wall-clock time in this zone refers to.
+    pub fn utc_of(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        let time = match self {
+            Self::Local => Local
+                .from_local_datetime(&local)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+            Self::Named(tz) => tz
+                .from_local_datetime(&local)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+        };
+        // The time can be skipped by a DST change; treat it as UTC then
+        time.unwrap_or_else(|| Utc.from_utc_datetime(&local))
+    }
+
+    /// The instant `date` starts in this zone.
+    pub fn midnight_utc(&self, date: NaiveDate) -> DateTime<Utc> {
+        self.utc_of(date.and_hms_opt(0, 0, 0).expect("midnight is a valid time"))
+    }
+
+    /// Calendar day an RFC 3339 timestamp falls on in this zone.
+    pub fn date_of(&self, timestamp: &str) -> Option<NaiveDate> {
+        parse_timestamp(timestamp).map(|t| self.local(t).date())
+    }
+
+    /// `YYYY-MM-DD` of a stored timestamp in this zone, for prompts and exports.
+    pub fn day_label(&self, timestamp: &str) -> String {
+        match self.date_of(timestamp) {
+            Some(date) => date.to_string(),
+            None => timestamp.split('T').next().unwrap_or(timestamp).to_string(),
+        }
+    }
+
+    /// A stored timestamp as `Oct 19, 14:05 · 2h ago` in this zone.
+    ///
+    /// The year is shown for dates outside the current one; unparseable values are
+    /// returned as they are.
+    pub fn describe(&self, timestamp: &str, now: DateTime<Utc>) -> String {
+        let Some(time) = parse_timestamp(timestamp) else {
+            return timestamp.to_string();
+        };
+        let local = self.local(time);
+        let format = if local.year() == self.local(now).year() {
+            "%b %-d, %H:%M"
+        } else {
+            "%Y-%m-%d %H:%M"
+        };
+        format!("{} · {}", local.format(format), relative(time, now))
+    }
+}
+
+/// Parses a timestamp as stored by Supabase or the CLI (RFC 3339).
+pub fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// How long ago `time` was, e.g. `just now`, `5m ago`, `2h ago`, `yesterday`, `3w ago`.
+pub fn relative(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let elapsed = now - time;
+    let days = elapsed.num_days();
+    match elapsed {
+        e if e < Duration::minutes(1) => "just now".to_string(),
+        e if e < Duration::hours(1) => format!("{}m ago", e.num_minutes()),
+        e if e < Duration::days(1) => format!("{}h ago", e.num_hours()),
+        _ if days < 2 => "yesterday".to_string(),
+        _ if days < 7 => format!("{days}d ago"),
+        _ if days < 35 => format!("{}w ago", days / 7),
+        _ if days < 365 => format!("{}mo ago", days / 30),
+        _ => format!("{}y ago", days / 365),
+    }
+}
+
+/// Whether a date-only expression such as `yesterday` means the start or the end of
+/// that day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bound {
+    /// `--since`: from the start of the day
+    Start,
+    /// `--until`: up to and including the whole day
+    End,
+}
+
+/// Parses `--since`/`--until` values: `now`, `today`, `yesterday`, weekday names
+/// (`monday`, `last friday`), ages (`3d`, `12h`, `2 weeks ago`, `last month`),
+/// `YYYY-MM-DD`, `YYYY-MM-DD HH:MM` and RFC 3339 timestamps.
+pub fn parse_when(
+    expr: &str,
+    zone: Zone,
+    now: DateTime<Utc>,
+    bound: Bound,
+) -> Result<DateTime<Utc>, String> {
+    let text = expr.trim().to_lowercase();
+    let today = zone.local(now).date();
+    let day = |date: NaiveDate| match bound {
+        Bound::Start => zone.midnight_utc(date),
+        Bound::End => zone.midnight_utc(date + Days::new(1)),
+    };
+
+    match text.as_str() {
+        "now" => return Ok(now),
+        "today" => return Ok(day(today)),
+        "yesterday" => return Ok(day(today - Days::new(1))),
+        _ => {}
+    }
+    if let Some(weekday) = parse_weekday(text.strip_prefix("last ").unwrap_or(&text)) {
+        // The most recent such day before today
+        let back =
+            (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday() - 1) % 7
+                + 1;
+        return Ok(day(today - Days::new(back.into())));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&text, "%Y-%m-%d") {
+        return Ok(day(date));
+    }
+    if let Ok(local) = NaiveDateTime::parse_from_str(&text, "%Y-%m-%d %H:%M") {
+        return Ok(zone.utc_of(local));
+    }
+    if let Some(time) = parse_timestamp(expr.trim()) {
+        return Ok(time);
+    }
+    if let Some(time) = parse_age(&text, now) {
+        return Ok(time);
+    }
+    Err(format!(
+        "Could not understand the date '{expr}'. Try today, yesterday, monday, 3d, 2 weeks ago or 2026-09-01."
+    ))
+}
+
+fn parse_weekday(text: &str) -> Option<Weekday> {
+    let weekday = text.parse::<Weekday>().ok()?;
+    // chrono also accepts abbreviations like "mon"; so do we, but not other words
+    text.chars()
+        .all(|c| c.is_ascii_alphabetic())
+        .then_some(weekday)
+}
+
+/// `3d`, `3 days`, `3 days ago`, `last week` and so on, counted back from `now`.
+fn parse_age(text: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let text = text.strip_suffix(" ago").unwrap_or(text).trim();
+    let (amount, unit) = match text.strip_prefix("last ") {
+        Some(unit) => (1, unit.trim()),
+        None => {
+            let split = text.find(|c: char| !c.is_ascii_digit())?;
+            let amount = text[..split].parse::<u32>().ok()?;
+            (amount, text[split..].trim())
+        }
+    };
+    let amount = i64::from(amount);
+    let duration = match unit.trim_end_matches('s') {
+        "m" | "min" | "minute" => Duration::minutes(amount),
+        "h" | "hr" | "hour" => Duration::hours(amount),
+        "d" | "day" => Duration::days(amount),
+        "w" | "week" => Duration::weeks(amount),
+        "mo" | "month" => return now.checked_sub_months(Months::new(amount as u32)),
+        "y" | "year" => return now.checked_sub_months(Months::new(amount as u32 * 12)),
+        _ => return None,
+    };
+    now.checked_sub_signed(duration)
+}
+
+/// The working day (Monday to Friday) before `today`.
+pub fn previous_working_day(today: NaiveDate) -> NaiveDate {
+    let back = match today.weekday() {
+        Weekday::Mon => 3,
+        Weekday::Sun => 2,
+        _ => 1,
+    };
+    today - Days::new(back)
+}
+
+/// An inclusive range of calendar days.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl DateRange {
+    /// Resolves `period` relative to `today`. `from` and `to` are required/used for
+    /// [`Period::Custom`] only; `to` defaults to today.
+    pub fn for_period(
+        period: Period,
+        today: NaiveDate,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<Self, String> {
+        let from = match period {
+            Period::Week => today - Days::new(6),
+            Period::Month => today - Months::new(1) + Days::new(1),
+            Period::Quarter => today - Months::new(3) + Days::new(1),
+            Period::Custom => {
+                let from = from.ok_or("--period custom needs --from YYYY-MM-DD")?;
+                let to = to.unwrap_or(today);
+                if from > to {
+                    return Err(format!("--from ({from}) is after --to ({to})"));
+                }
+                return Ok(Self { from, to });
+            }
+        };
+        Ok(Self { from, to: today })
+    }
+
+    /// Start of the first day in `zone`, as a UTC instant.
+    pub fn start_utc(&self, zone: Zone) -> DateTime<Utc> {
+        zone.midnight_utc(self.from)
+    }
+
+    /// Start of the day after the last one in `zone`, as a UTC instant (exclusive bound).
+    pub fn end_utc(&self, zone: Zone) -> DateTime<Utc> {
+        zone.midnight_utc(self.to + Days::new(1))
+    }
+
+    /// Human label such as `2026-10-13 to 2026-10-19`.
+    pub fn label(&self) -> String {
+        if self.from == self.to {
+            self.from.to_string()
+        } else {
+            format!("{} to {}", self.from, self.to)
+        }
+    }
+}
+
+/// Parses a `YYYY-MM-DD` date given on the command line.
+pub fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{value}'. Use YYYY-MM-DD."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_period_ranges() {
+        let today = parse_date("2026-03-31").unwrap();
+        let range = |period| DateRange::for_period(period, today, None, None).unwrap();
+        assert_eq!(range(Period::Week).from, parse_date("2026-03-25").unwrap());
+        assert_eq!(range(Period::Month).from, parse_date("2026-03-01").unwrap());
+        assert_eq!(
+            range(Period::Quarter).from,
+            parse_date("2026-01-01").unwrap()
+        );
+
+        let custom = DateRange::for_period(
+            Period::Custom,
+            today,
+            Some(parse_date("2026-02-01").unwrap()),
+            Some(parse_date("2026-02-14").unwrap()),
+        )
+        .unwrap();
+        assert_eq!(custom.label(), "2026-02-01 to 2026-02-14");
+        assert!(custom.start_utc(Zone::Local) < custom.end_utc(Zone::Local));
+        assert!(DateRange::for_period(Period::Custom, today, None, None).is_err());
+        assert!(parse_date("14/02/2026").is_err());
+    }
+
+    #[test]
+    fn test_parse_when_and_relative() {
+        let zone = Zone::parse("UTC").unwrap();
+        // Monday
+        let now = parse_timestamp("2026-10-19T15:30:00Z").unwrap();
+        let when = |expr, bound| parse_when(expr, zone, now, bound).unwrap().to_rfc3339();
+
+        assert_eq!(when("yesterday", Bound::Start), "2026-10-18T00:00:00+00:00");
+        assert_eq!(when("Yesterday", Bound::End), "2026-10-19T00:00:00+00:00");
+        assert_eq!(when("2026-09-01", Bound::End), "2026-09-02T00:00:00+00:00");
+        assert_eq!(when("friday", Bound::Start), "2026-10-16T00:00:00+00:00");
+        assert_eq!(
+            when("last monday", Bound::Start),
+            "2026-10-12T00:00:00+00:00"
+        );
+        assert_eq!(when("3d", Bound::Start), "2026-10-16T15:30:00+00:00");
+        assert_eq!(
+            when("2 weeks ago", Bound::Start),
+            "2026-10-05T15:30:00+00:00"
+        );
+        assert_eq!(
+            when("last month", Bound::Start),
+            "2026-09-19T15:30:00+00:00"
+        );
+        assert_eq!(
+            when("2026-10-01 09:15", Bound::Start),
+            "2026-10-01T09:15:00+00:00"
+        );
+        assert!(parse_when("someday", zone, now, Bound::Start).is_err());
+        assert!(parse_when("5 parsecs", zone, now, Bound::Start).is_err());
+
+        let ago = |ts| relative(parse_timestamp(ts).unwrap(), now);
+        assert_eq!(ago("2026-10-19T15:29:30Z"), "just now");
+        assert_eq!(ago("2026-10-19T13:10:00Z"), "2h ago");
+        assert_eq!(ago("2026-10-18T09:00:00Z"), "yesterday");
+        assert_eq!(ago("2026-10-01T09:00:00Z"), "2w ago");
+        assert_eq!(
+            zone.describe("2026-10-19T13:10:00Z", now),
+            "Oct 19, 13:10 · 2h ago"
+        );
+        assert_eq!(zone.describe("not a date", now), "not a date");
+    }
+
+    #[test]
+    fn test_zones_and_working_days() {
+        let day = |s| parse_date(s).unwrap();
+        // 2026-10-19 is a Monday
+        assert_eq!(previous_working_day(day("2026-10-19")), day("2026-10-16"));
+        assert_eq!(previous_working_day(day("2026-10-18")), day("2026-10-16"));
+        assert_eq!(previous_working_day(day("2026-10-17")), day("2026-10-16"));
+        assert_eq!(previous_working_day(day("2026-10-21")), day("2026-10-20"));
+
+        let tokyo = Zone::parse("Asia/Tokyo").unwrap();
+        assert_eq!(
+            tokyo.midnight_utc(day("2026-10-19")).to_rfc3339(),
+            "2026-10-18T15:00:00+00:00"
+        );
+        assert_eq!(
+            tokyo.date_of("2026-10-18T16:30:00Z"),
+            Some(day("2026-10-19"))
+        );
+        assert_eq!(Zone::parse(" ").unwrap(), Zone::Local);
+        assert!(Zone::parse("Mars/Olympus").is_err());
+    }
+}
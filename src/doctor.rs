@@ -0,0 +1,611 @@
+//! Health checks behind `doctor`, with optional safe remediations (`--fix`).
+//!
+//! Every check has a stable id (e.g. `ollama.embedding_model`) so scripts can rely on
+//! the `--json` report. Checks that need a failed one to pass are reported as skipped.
+
+use crate::config::{Config, DEFAULT_EMBEDDING_MODEL, DEFAULT_OLLAMA_BASE_URL};
+use crate::dates::Zone;
+use crate::generation::GenerationOptions;
+use crate::models;
+use crate::outbox::Outbox;
+use crate::services::http::HttpClient;
+use crate::services::ollama::{self, ModelInfo};
+use crate::services::supabase::{self, SupabaseStore, WriteAccess, SETUP_TEST_NOTE};
+use crate::types::SupabaseConfig;
+use crate::validation::{validate_api_key, validate_url};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Content of the test note older versions wrote and removed again to check write
+/// access; leftovers are found and deleted.
+pub const DOCTOR_TEST_NOTE: &str = "Doctor health check test note";
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+    Skip,
+}
+
+/// Outcome of one check.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Check {
+    pub id: &'static str,
+    pub status: Status,
+    pub message: String,
+    /// What to do about a warning or failure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    /// What `--fix` changed to get this result.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixed: Option<String>,
+}
+
+impl Check {
+    fn new(id: &'static str, status: Status, message: impl Into<String>) -> Self {
+        Self {
+            id,
+            status,
+            message: message.into(),
+            hint: None,
+            fixed: None,
+        }
+    }
+
+    fn pass(id: &'static str, message: impl Into<String>) -> Self {
+        Self::new(id, Status::Pass, message)
+    }
+
+    fn warn(id: &'static str, message: impl Into<String>) -> Self {
+        Self::new(id, Status::Warn, message)
+    }
+
+    fn fail(id: &'static str, message: impl Into<String>) -> Self {
+        Self::new(id, Status::Fail, message)
+    }
+
+    fn skip(id: &'static str, message: impl Into<String>) -> Self {
+        Self::new(id, Status::Skip, message)
+    }
+
+    fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    fn fixed(mut self, fixed: impl Into<String>) -> Self {
+        self.fixed = Some(fixed.into());
+        self
+    }
+}
+
+/// Everything `doctor` found, in the order the checks ran.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    pub version: &'static str,
+    pub checked_at: DateTime<Utc>,
+    pub fix: bool,
+    pub summary: Summary,
+    pub checks: Vec<Check>,
+}
+
+/// Number of checks per status.
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct Summary {
+    pub pass: usize,
+    pub warn: usize,
+    pub fail: usize,
+    pub skip: usize,
+}
+
+impl Summary {
+    fn of(checks: &[Check]) -> Self {
+        let count = |status| checks.iter().filter(|c| c.status == status).count();
+        Self {
+            pass: count(Status::Pass),
+            warn: count(Status::Warn),
+            fail: count(Status::Fail),
+            skip: count(Status::Skip),
+        }
+    }
+}
+
+impl HealthReport {
+    pub fn healthy(&self) -> bool {
+        self.summary.fail == 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DoctorOptions {
+    /// Apply safe remediations instead of only reporting problems.
+    pub fix: bool,
+    /// Draw progress bars for long fixes such as model downloads.
+    pub progress: bool,
+}
+
+/// Runs every check, calling `on_check` as each one finishes.
+pub fn run(options: DoctorOptions, mut on_check: impl FnMut(&Check)) -> HealthReport {
+    let mut checks = vec![];
+    let mut record = |check: Check| {
+        on_check(&check);
+        checks.push(check);
+    };
+
+    let mut config = match Config::load() {
+        Ok(config) => {
+            record(Check::pass(
+                "config.file",
+                "Configuration file found and valid",
+            ));
+            Some(config)
+        }
+        Err(e) => {
+            record(Check::fail("config.file", e).hint("Run 'logswise-cli setup' to create it"));
+            None
+        }
+    };
+
+    if let Some(config) = config.as_mut() {
+        let (config_checks, changed) = check_config(config, options.fix);
+        if changed {
+            let result = config.save();
+            for check in config_checks {
+                record(match &result {
+                    Err(e) if check.fixed.is_some() => Check {
+                        status: Status::Fail,
+                        fixed: None,
+                        hint: Some(format!("Could not save the fix: {e}")),
+                        ..check
+                    },
+                    _ => check,
+                });
+            }
+        } else {
+            config_checks.into_iter().for_each(&mut record);
+        }
+
+        let client = HttpClient::from_config(config);
+        for check in check_ollama(&client, config, options) {
+            record(check);
+        }
+        match config.supabase() {
+            Ok(supabase_config) => {
+                for check in check_supabase(&client, &supabase_config, options.fix) {
+                    record(check);
+                }
+            }
+            Err(e) => record(Check::skip("supabase.connection", e)),
+        }
+    }
+
+    let outbox = Outbox::open_default()
+        .map(|o| (o.len(), o))
+        .ok()
+        .filter(|(pending, _)| *pending > 0);
+    record(match outbox {
+        None => Check::pass("outbox.pending", "No notes waiting to be synced"),
+        Some((pending, outbox)) => check_outbox(&outbox, pending, config.as_ref(), options.fix),
+    });
+
+    HealthReport {
+        version: env!("CARGO_PKG_VERSION"),
+        checked_at: Utc::now(),
+        fix: options.fix,
+        summary: Summary::of(&checks),
+        checks,
+    }
+}
+
+/// Checks required fields, URL and key formats and the time zone. With `fix`, fields
+/// that have a safe default are filled in; the second value says whether any were.
+pub fn check_config(config: &mut Config, fix: bool) -> (Vec<Check>, bool) {
+    let mut checks = vec![];
+    let mut changed = false;
+
+    let required = [
+        ("config.profession", "Profession", &config.profession),
+        ("config.job_title", "Job title", &config.job_title),
+        ("config.llm_name", "LLM name", &config.llm_name),
+    ];
+    for (id, name, value) in required {
+        checks.push(if value.trim().is_empty() {
+            Check::warn(id, format!("{name} is empty")).hint("Run 'logswise-cli setup'")
+        } else {
+            Check::pass(id, format!("{name}: {value}"))
+        });
+    }
+
+    let url = config.ollama_base_url.trim().to_string();
+    checks.push(if validate_url(&url) {
+        Check::pass("config.ollama_url", format!("Ollama URL: {url}"))
+    } else if fix {
+        // A bare host:port is by far the most common mistake
+        config.ollama_base_url = if url.is_empty() {
+            DEFAULT_OLLAMA_BASE_URL.to_string()
+        } else {
+            format!("http://{url}")
+        };
+        changed = true;
+        Check::pass(
+            "config.ollama_url",
+            format!("Ollama URL: {}", config.ollama_base_url),
+        )
+        .fixed(format!("Set ollamaBaseUrl to {}", config.ollama_base_url))
+    } else {
+        Check::fail("config.ollama_url", format!("Invalid Ollama URL: '{url}'"))
+            .hint("Use a full URL such as http://localhost:11434 (doctor --fix can set it)")
+    });
+
+    checks.push(if !config.embedding_model.trim().is_empty() {
+        Check::pass(
+            "config.embedding_model",
+            format!("Embedding model: {}", config.embedding_model),
+        )
+    } else if fix {
+        config.embedding_model = DEFAULT_EMBEDDING_MODEL.to_string();
+        changed = true;
+        Check::pass(
+            "config.embedding_model",
+            format!("Embedding model: {DEFAULT_EMBEDDING_MODEL}"),
+        )
+        .fixed(format!("Set embeddingModel to {DEFAULT_EMBEDDING_MODEL}"))
+    } else {
+        Check::warn("config.embedding_model", "Embedding model is empty")
+            .hint("doctor --fix sets the default (nomic-embed-text)")
+    });
+
+    checks.push(if validate_url(&config.supabase_url) {
+        Check::pass("config.supabase_url", "Supabase URL format valid")
+    } else {
+        Check::fail("config.supabase_url", "Missing or invalid Supabase URL")
+            .hint("Run 'logswise-cli setup'")
+    });
+    checks.push(if validate_api_key(&config.supabase_api_key) {
+        Check::pass("config.supabase_key", "Supabase API key format valid")
+    } else {
+        Check::fail("config.supabase_key", "Missing or invalid Supabase API key")
+            .hint("Run 'logswise-cli setup'")
+    });
+
+    checks.push(match Zone::parse(&config.timezone) {
+        Ok(_) => Check::pass("config.timezone", "Time zone valid"),
+        Err(_) if fix => {
+            config.timezone.clear();
+            changed = true;
+            Check::pass("config.timezone", "Using the system time zone")
+                .fixed("Cleared the unknown timezone")
+        }
+        Err(e) => Check::warn("config.timezone", e)
+            .hint("The system time zone is used until 'timezone' is fixed"),
+    });
+
+    (checks, changed)
+}
+
+fn check_ollama(client: &HttpClient, config: &Config, options: DoctorOptions) -> Vec<Check> {
+    let base_url = &config.ollama_base_url;
+    let installed = match ollama::list_models(client, base_url) {
+        Ok(installed) => installed,
+        Err(e) => {
+            return vec![
+                Check::fail(
+                    "ollama.reachable",
+                    format!("Cannot reach Ollama at {base_url}"),
+                )
+                .hint(format!("Make sure Ollama is running (ollama serve): {e}")),
+                Check::skip("ollama.embedding_model", "Ollama is unreachable"),
+                Check::skip("ollama.llm", "Ollama is unreachable"),
+            ];
+        }
+    };
+    let mut checks = vec![Check::pass(
+        "ollama.reachable",
+        format!(
+            "Ollama is reachable ({} model(s) installed)",
+            installed.len()
+        ),
+    )];
+
+    let embedding_model = &config.embedding_model;
+    checks.push(check_model(
+        client,
+        base_url,
+        &installed,
+        "ollama.embedding_model",
+        embedding_model,
+        options,
+        || {
+            ollama::generate_embedding(
+                client,
+                &format!("{base_url}/api/embeddings"),
+                embedding_model,
+                "test",
+            )
+            .map(|_| ())
+        },
+    ));
+
+    let llm = &config.llm_name;
+    checks.push(if llm.trim().is_empty() {
+        Check::skip("ollama.llm", "No LLM configured")
+    } else {
+        check_model(
+            client,
+            base_url,
+            &installed,
+            "ollama.llm",
+            llm,
+            options,
+            || {
+                ollama::generate_suggestion(
+                    client,
+                    &format!("{base_url}/api/generate"),
+                    llm,
+                    "test",
+                    &GenerationOptions::default(),
+                )
+                .map(|_| ())
+            },
+        )
+    });
+    checks
+}
+
+/// Checks that `model` is installed (pulling it with `fix`) and that `try_model` works.
+fn check_model(
+    client: &HttpClient,
+    base_url: &str,
+    installed: &[ModelInfo],
+    id: &'static str,
+    model: &str,
+    options: DoctorOptions,
+    try_model: impl FnOnce() -> Result<(), String>,
+) -> Check {
+    let mut fixed = None;
+    if !installed.iter().any(|m| m.matches(model)) {
+        if !options.fix {
+            return Check::fail(id, format!("Model '{model}' is not installed"))
+                .hint(format!("Run 'ollama pull {model}' or doctor --fix"));
+        }
+        if let Err(e) = models::pull(client, base_url, model, options.progress) {
+            return Check::fail(id, format!("Model '{model}' is not installed"))
+                .hint(format!("Downloading it failed: {e}"));
+        }
+        fixed = Some(format!("Pulled '{model}'"));
+    }
+    let check = match try_model() {
+        Ok(()) => Check::pass(id, format!("Model '{model}' is working")),
+        Err(e) => Check::fail(id, format!("Model '{model}' failed: {e}")),
+    };
+    Check { fixed, ..check }
+}
+
+fn check_supabase(client: &HttpClient, config: &SupabaseConfig, fix: bool) -> Vec<Check> {
+    let dependents = [
+        "supabase.notes_table",
+        "supabase.write_access",
+        "supabase.test_notes",
+    ];
+    let skip_rest = |checks: &mut Vec<Check>, from: usize, reason: &str| {
+        for id in &dependents[from..] {
+            checks.push(Check::skip(id, reason));
+        }
+    };
+
+    let mut checks = vec![];
+    if let Err(e) = supabase::test_connection(client, config) {
+        checks.push(
+            Check::fail(
+                "supabase.connection",
+                format!("Supabase connection failed: {e}"),
+            )
+            .hint("Check your Supabase URL and API key"),
+        );
+        skip_rest(&mut checks, 0, "Supabase is unreachable");
+        return checks;
+    }
+    checks.push(Check::pass(
+        "supabase.connection",
+        "Supabase connection successful",
+    ));
+
+    // The REST API cannot run DDL, so a missing table is never fixed automatically
+    match supabase::check_notes_table_exists(client, config) {
+        Ok(true) => checks.push(Check::pass(
+            "supabase.notes_table",
+            "Notes table exists and is accessible",
+        )),
+        Ok(false) => {
+            checks.push(
+                Check::fail("supabase.notes_table", "Notes table does not exist").hint(format!(
+                    "Not fixable automatically. Run this in the Supabase SQL Editor, then the rest of the SQL shown by 'logswise-cli init':\n{}",
+                    supabase::NOTES_TABLE_SQL
+                )),
+            );
+            skip_rest(&mut checks, 1, "The notes table is missing");
+            return checks;
+        }
+        Err(e) => {
+            checks.push(
+                Check::fail(
+                    "supabase.notes_table",
+                    format!("Could not check the schema: {e}"),
+                )
+                .hint("This might indicate permission or connection issues"),
+            );
+            skip_rest(&mut checks, 1, "The schema could not be checked");
+            return checks;
+        }
+    }
+
+    // Probes with an insert the database refuses either way, so nothing is written
+    checks.push(match supabase::check_write_access(client, config) {
+        Ok(WriteAccess::Allowed) => {
+            Check::pass("supabase.write_access", "Database write access confirmed")
+        }
+        Ok(WriteAccess::Denied(e)) => Check::fail(
+            "supabase.write_access",
+            format!("Notes cannot be written: {e}"),
+        )
+        .hint("Check your RLS (Row Level Security) insert policy"),
+        Err(e) => Check::warn(
+            "supabase.write_access",
+            format!("Could not verify write access: {e}"),
+        ),
+    });
+
+    checks.push(check_test_notes(client, config, fix));
+    checks
+}
+
+/// Looks for notes left behind by older health checks and schema setup.
+fn check_test_notes(client: &HttpClient, config: &SupabaseConfig, fix: bool) -> Check {
+    let mut ids = vec![];
+    for content in [DOCTOR_TEST_NOTE, SETUP_TEST_NOTE] {
+        match supabase::find_notes_with_content(client, config, content) {
+            Ok(found) => ids.extend(found),
+            Err(e) => {
+                return Check::warn(
+                    "supabase.test_notes",
+                    format!("Could not look for test notes: {e}"),
+                )
+            }
+        }
+    }
+    if ids.is_empty() {
+        return Check::pass("supabase.test_notes", "No leftover test notes");
+    }
+    if !fix {
+        return Check::warn(
+            "supabase.test_notes",
+            format!("{} leftover test note(s) found", ids.len()),
+        )
+        .hint("doctor --fix deletes them");
+    }
+    let deleted = ids
+        .iter()
+        .filter(|id| supabase::delete_note(client, config, id).is_ok())
+        .count();
+    if deleted == ids.len() {
+        Check::pass("supabase.test_notes", "No leftover test notes")
+            .fixed(format!("Deleted {deleted} test note(s)"))
+    } else {
+        Check::warn(
+            "supabase.test_notes",
+            format!("{} test note(s) could not be deleted", ids.len() - deleted),
+        )
+    }
+}
+
+fn check_outbox(outbox: &Outbox, pending: usize, config: Option<&Config>, fix: bool) -> Check {
+    let waiting = Check::warn(
+        "outbox.pending",
+        format!("{pending} note(s) waiting to be synced"),
+    );
+    if !fix {
+        return waiting.hint("Run 'logswise-cli sync' once Supabase is reachable");
+    }
+    let store = config
+        .ok_or_else(|| "No configuration".to_string())
+        .and_then(|config| {
+            Ok(SupabaseStore::new(
+                HttpClient::from_config(config),
+                config.supabase()?,
+            ))
+        });
+    match store.and_then(|store| outbox.flush(&store)) {
+        Ok(report) if report.remaining == 0 && report.rejected == 0 => {
+            Check::pass("outbox.pending", "No notes waiting to be synced")
+                .fixed(format!("Synced {} note(s)", report.sent))
+        }
+        Ok(report) if report.remaining == 0 => Check::warn(
+            "outbox.pending",
+            format!(
+                "Synced {} note(s); Supabase refused {}",
+                report.sent, report.rejected
+            ),
+        )
+        .hint(format!(
+            "Refused notes were moved to {}: {}",
+            outbox.rejected_path().display(),
+            report.last_error.unwrap_or_default()
+        )),
+        Ok(report) => waiting.hint(format!(
+            "{} note(s) could not be synced: {}",
+            report.remaining,
+            report.last_error.unwrap_or_default()
+        )),
+        Err(e) => waiting.hint(format!("Sync failed: {e}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statuses(checks: &[Check]) -> Vec<(&str, Status)> {
+        checks.iter().map(|c| (c.id, c.status)).collect()
+    }
+
+    #[test]
+    fn test_check_config_reports_problems() {
+        let mut config = Config {
+            profession: "Engineer".to_string(),
+            ollama_base_url: "localhost:11434".to_string(),
+            embedding_model: String::new(),
+            timezone: "Mars/Olympus".to_string(),
+            ..Config::default()
+        };
+        let (checks, changed) = check_config(&mut config, false);
+        assert!(!changed);
+        assert_eq!(
+            statuses(&checks),
+            vec![
+                ("config.profession", Status::Pass),
+                ("config.job_title", Status::Warn),
+                ("config.llm_name", Status::Warn),
+                ("config.ollama_url", Status::Fail),
+                ("config.embedding_model", Status::Warn),
+                ("config.supabase_url", Status::Fail),
+                ("config.supabase_key", Status::Fail),
+                ("config.timezone", Status::Warn),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_config_fixes_safe_defaults() {
+        let mut config = Config {
+            ollama_base_url: "localhost:11434".to_string(),
+            embedding_model: String::new(),
+            timezone: "Mars/Olympus".to_string(),
+            ..Config::default()
+        };
+        let (checks, changed) = check_config(&mut config, true);
+        assert!(changed);
+        assert_eq!(config.ollama_base_url, "http://localhost:11434");
+        assert_eq!(config.embedding_model, DEFAULT_EMBEDDING_MODEL);
+        assert_eq!(config.timezone, "");
+        let fixed: Vec<&str> = checks
+            .iter()
+            .filter(|c| c.fixed.is_some())
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(
+            fixed,
+            vec![
+                "config.ollama_url",
+                "config.embedding_model",
+                "config.timezone"
+            ]
+        );
+        // Profile fields cannot be guessed
+        assert!(checks
+            .iter()
+            .any(|c| c.id == "config.job_title" && c.status == Status::Warn));
+    }
+}
@@ -296,11 +296,11 @@ fn setup_current_projects(context: &mut UserContext) -> Result<(), String> {
         };
 
         context.add_project(ProjectContext {
-            name,
             tech_stack,
             current_challenges,
             team_size,
             deadline_pressure,
+            ..ProjectContext::new(name)
         });
 
         println!();
@@ -0,0 +1,95 @@
+use crate::dates::Zone;
+use crate::types::Note;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ExportFormat {
+    /// A JSON array of notes with UTC timestamps
+    Json,
+    /// Markdown grouped by day, with local times
+    Markdown,
+}
+
+/// Renders `notes` (oldest first) for `export`.
+pub fn render(
+    notes: &[Note],
+    format: ExportFormat,
+    zone: Zone,
+    now: DateTime<Utc>,
+) -> Result<String, String> {
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(notes)
+            .map_err(|e| format!("Failed to serialize notes: {e}")),
+        ExportFormat::Markdown => Ok(markdown(notes, zone, now)),
+    }
+}
+
+fn markdown(notes: &[Note], zone: Zone, now: DateTime<Utc>) -> String {
+    let mut out = format!(
+        "# Logswise Notes\n\n_Exported {} · {} note(s)_\n",
+        zone.local(now).format("%Y-%m-%d %H:%M"),
+        notes.len()
+    );
+    let mut current_day = None;
+    for note in notes {
+        let local = crate::dates::parse_timestamp(&note.created_at).map(|t| zone.local(t));
+        let day = local.map(|t| t.date());
+        if day != current_day || day.is_none() {
+            let heading = match day {
+                Some(day) => day.format("%Y-%m-%d (%A)").to_string(),
+                None => "Unknown date".to_string(),
+            };
+            out.push_str(&format!("\n## {heading}\n\n"));
+            current_day = day;
+        }
+        let time = local
+            .map(|t| t.format("%H:%M").to_string())
+            .unwrap_or_else(|| note.created_at.clone());
+        let project = note
+            .project
+            .as_deref()
+            .map(|p| format!(" _({p})_"))
+            .unwrap_or_default();
+        // Keep multi-line notes inside their list item
+        let content = note.content.trim().replace('\n', "\n  ");
+        out.push_str(&format!("- **{time}** {content}{project}\n"));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::note;
+    use crate::dates::parse_timestamp;
+
+    #[test]
+    fn test_markdown_groups_by_local_day() {
+        let zone = Zone::parse("America/New_York").unwrap();
+        let at = |id, content, time: &str| {
+            let mut n = note(id, content);
+            n.created_at = time.to_string();
+            n
+        };
+        let mut tagged = at("2", "Deploy\nwent fine", "2026-10-02T13:30:00Z");
+        tagged.project = Some("Atlas".to_string());
+        let notes = vec![
+            // Still October 1st in New York
+            at("1", "Late fix", "2026-10-02T02:00:00Z"),
+            tagged,
+        ];
+        let now = parse_timestamp("2026-10-19T12:00:00Z").unwrap();
+
+        let out = render(&notes, ExportFormat::Markdown, zone, now).unwrap();
+        assert_eq!(
+            out,
+            "# Logswise Notes\n\n_Exported 2026-10-19 08:00 · 2 note(s)_\n\n## 2026-10-01 (Thursday)\n\n- **22:00** Late fix\n\n## 2026-10-02 (Friday)\n\n- **09:30** Deploy\n  went fine _(Atlas)_\n"
+        );
+
+        let json = render(&notes, ExportFormat::Json, zone, now).unwrap();
+        let parsed: Vec<Note> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1].project.as_deref(), Some("Atlas"));
+    }
+}
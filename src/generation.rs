@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use crate::thinking::ThinkingMode;
+
+/// Named sampling presets, from most to least deterministic.
+pub const PRESETS: [&str; 3] = ["precise", "balanced", "creative"];
+
+/// Model, sampling and reasoning settings for one generation request. Unset fields keep
+/// Ollama's defaults; `preset` fills in temperature and top-p before the explicit fields
+/// apply.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GenerationOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// Context window Ollama allocates, in tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<u32>,
+    /// Most tokens to generate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_predict: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+    /// Fixed seed for reproducible output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Ollama's `think` flag: turns reasoning of thinking models on or off. Only sent
+    /// when set, since models without thinking support reject it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub think: Option<bool>,
+    /// How reasoning in replies is printed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<ThinkingMode>,
+    /// Keep reasoning in saved session transcripts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save_thinking: Option<bool>,
+}
+
+/// The `generation` section of setup.json: defaults for every command, plus overrides
+/// per command under `commands` (e.g. `"chat": { "preset": "creative" }`).
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GenerationSettings {
+    #[serde(flatten)]
+    pub defaults: GenerationOptions,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub commands: BTreeMap<String, GenerationOptions>,
+}
+
+impl GenerationSettings {
+    /// Options for `command`: the defaults, then the command's section, then `flags`.
+    pub fn resolve(
+        &self,
+        command: Option<&str>,
+        flags: &GenerationOptions,
+    ) -> Result<GenerationOptions, String> {
+        let mut options = self.defaults.expanded()?;
+        if let Some(section) = command.and_then(|c| self.commands.get(c)) {
+            options = options.merged(&section.expanded()?);
+        }
+        let options = options.merged(&flags.expanded()?);
+        options.validate()?;
+        Ok(options)
+    }
+}
+
+impl GenerationOptions {
+    /// Sampling values of a preset.
+    fn preset(name: &str) -> Result<Self, String> {
+        let (temperature, top_p) = match name.trim().to_lowercase().as_str() {
+            "precise" => (0.2, 0.8),
+            "balanced" => (0.7, 0.9),
+            "creative" => (1.0, 0.95),
+            other => {
+                return Err(format!(
+                    "Unknown generation preset '{other}'. Available: {}",
+                    PRESETS.join(", ")
+                ))
+            }
+        };
+        Ok(Self {
+            preset: Some(name.trim().to_lowercase()),
+            temperature: Some(temperature),
+            top_p: Some(top_p),
+            ..Self::default()
+        })
+    }
+
+    /// These options with the preset's values filled in where no explicit value is set.
+    fn expanded(&self) -> Result<Self, String> {
+        match &self.preset {
+            Some(name) => Ok(Self::preset(name)?.merged(self)),
+            None => Ok(self.clone()),
+        }
+    }
+
+    /// `self` with every field that `other` sets replaced.
+    pub fn merged(&self, other: &Self) -> Self {
+        Self {
+            model: other.model.clone().or_else(|| self.model.clone()),
+            preset: other.preset.clone().or_else(|| self.preset.clone()),
+            temperature: other.temperature.or(self.temperature),
+            top_p: other.top_p.or(self.top_p),
+            num_ctx: other.num_ctx.or(self.num_ctx),
+            num_predict: other.num_predict.or(self.num_predict),
+            stop: if other.stop.is_empty() {
+                self.stop.clone()
+            } else {
+                other.stop.clone()
+            },
+            seed: other.seed.or(self.seed),
+            think: other.think.or(self.think),
+            thinking: other.thinking.or(self.thinking),
+            save_thinking: other.save_thinking.or(self.save_thinking),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(t) = self.temperature.filter(|t| !(0.0..=2.0).contains(t)) {
+            return Err(format!("Temperature must be between 0 and 2, got {t}"));
+        }
+        if let Some(p) = self.top_p.filter(|p| !(0.0..=1.0).contains(p)) {
+            return Err(format!("Top-p must be between 0 and 1, got {p}"));
+        }
+        if self.num_ctx == Some(0) || self.num_predict == Some(0) {
+            return Err("Token limits must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// The `options` object of an Ollama generate request (empty when nothing is set).
+    pub fn ollama_options(&self) -> Map<String, Value> {
+        let mut options = Map::new();
+        if let Some(temperature) = self.temperature {
+            options.insert("temperature".into(), Value::from(temperature));
+        }
+        if let Some(top_p) = self.top_p {
+            options.insert("top_p".into(), Value::from(top_p));
+        }
+        if let Some(num_ctx) = self.num_ctx {
+            options.insert("num_ctx".into(), Value::from(num_ctx));
+        }
+        if let Some(num_predict) = self.num_predict {
+            options.insert("num_predict".into(), Value::from(num_predict));
+        }
+        if !self.stop.is_empty() {
+            options.insert("stop".into(), Value::from(self.stop.clone()));
+        }
+        if let Some(seed) = self.seed {
+            options.insert("seed".into(), Value::from(seed));
+        }
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_resolve_layers_defaults_command_and_flags() {
+        let settings: GenerationSettings = serde_json::from_value(json!({
+            "preset": "precise",
+            "numCtx": 8192,
+            "thinking": "collapse",
+            "commands": { "chat": { "model": "mistral", "preset": "creative", "seed": 7, "thinking": "show" } }
+        }))
+        .unwrap();
+
+        let suggestion = settings
+            .resolve(Some("suggestion"), &GenerationOptions::default())
+            .unwrap();
+        assert_eq!(suggestion.temperature, Some(0.2));
+        assert_eq!(suggestion.model, None);
+        assert_eq!(suggestion.thinking, Some(ThinkingMode::Collapse));
+
+        let flags = GenerationOptions {
+            temperature: Some(0.5),
+            num_predict: Some(256),
+            ..GenerationOptions::default()
+        };
+        let chat = settings.resolve(Some("chat"), &flags).unwrap();
+        assert_eq!(chat.model.as_deref(), Some("mistral"));
+        assert_eq!(chat.temperature, Some(0.5));
+        assert_eq!(chat.top_p, Some(0.95));
+        assert_eq!(chat.num_ctx, Some(8192));
+        assert_eq!(chat.seed, Some(7));
+        assert_eq!(chat.thinking, Some(ThinkingMode::Show));
+
+        let options = chat.ollama_options();
+        assert_eq!(options["num_predict"], json!(256));
+        assert_eq!(options["seed"], json!(7));
+        assert!(!options.contains_key("stop"));
+    }
+
+    #[test]
+    fn test_resolve_rejects_bad_values() {
+        let settings = GenerationSettings::default();
+        let unknown = GenerationOptions {
+            preset: Some("wild".to_string()),
+            ..GenerationOptions::default()
+        };
+        assert!(settings.resolve(None, &unknown).is_err());
+        let hot = GenerationOptions {
+            temperature: Some(3.0),
+            ..GenerationOptions::default()
+        };
+        assert!(settings.resolve(None, &hot).is_err());
+        assert!(settings
+            .resolve(None, &GenerationOptions::default())
+            .unwrap()
+            .ollama_options()
+            .is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+use crate::context::AppContext;
+use crate::personalization::Goal;
+use crate::prompts;
+use crate::thinking;
+use serde_json::{json, Value};
+
+/// Length of a full note id (a hyphenated UUID).
+const NOTE_ID_LEN: usize = 36;
+
+/// Recent notes searched when a note is given by an id prefix.
+const NOTE_LOOKUP_WINDOW: usize = 200;
+
+/// Progress the LLM inferred from a goal's linked notes.
+#[derive(Debug, PartialEq)]
+pub struct ProgressEstimate {
+    pub percent: u32,
+    pub summary: String,
+}
+
+/// Asks the LLM how far along `goal` is, based on the notes linked to it.
+pub fn estimate_progress(ctx: &AppContext, goal: &Goal) -> Result<ProgressEstimate, String> {
+    ctx.check_generation()?;
+    if goal.linked_notes.is_empty() {
+        return Err(format!(
+            "No notes are linked to this goal yet. Link one with 'logswise-cli goal link {} <note-id>' or 'logswise-cli note \"...\" --goal {}'.",
+            goal.id, goal.id
+        ));
+    }
+    let notes = ctx.store.notes_by_ids(&goal.linked_notes)?;
+    if notes.is_empty() {
+        return Err("None of the linked notes could be found in Supabase.".to_string());
+    }
+
+    let data = json!({
+        "profile": prompts::profile_data(&ctx.config),
+        "goal": {
+            "description": goal.description,
+            "category": goal.category,
+            "timeline": goal.timeline.replace('_', " "),
+            "progress_percent": (goal.progress * 100.0).round() as u32,
+        },
+        "notes": notes
+            .iter()
+            .map(|n| json!({
+                "content": n.content,
+                "date": ctx.zone().day_label(&n.created_at),
+            }))
+            .collect::<Vec<Value>>(),
+    });
+    let reply = ctx
+        .llm
+        .generate(&ctx.prompts.render("goal_progress", &data))?;
+    parse_estimate(&reply)
+}
+
+/// Reads the `PROGRESS: n%` and `SUMMARY: ...` lines from the model's reply.
+fn parse_estimate(reply: &str) -> Result<ProgressEstimate, String> {
+    let answer = thinking::strip(reply);
+    let field = |name: &str| {
+        answer.lines().find_map(|line| {
+            let line = line.trim().trim_start_matches(['*', '-', ' ']);
+            let (key, value) = line.split_once(':')?;
+            key.trim()
+                .trim_matches('*')
+                .eq_ignore_ascii_case(name)
+                .then(|| value.trim().trim_matches('*').trim().to_string())
+        })
+    };
+
+    let percent = field("PROGRESS")
+        .and_then(|v| {
+            let digits: String = v
+                .chars()
+                .skip_while(|c| !c.is_ascii_digit())
+                .take_while(|c| c.is_ascii_digit())
+                .collect();
+            digits.parse::<u32>().ok()
+        })
+        .ok_or_else(|| {
+            format!(
+                "Could not read a progress estimate from the model's reply:\n{}",
+                answer.trim()
+            )
+        })?;
+    Ok(ProgressEstimate {
+        percent: percent.min(100),
+        summary: field("SUMMARY").unwrap_or_default(),
+    })
+}
+
+/// Resolves a full note id, or a prefix of one among recent notes.
+pub fn resolve_note_id(ctx: &AppContext, key: &str) -> Result<String, String> {
+    let key = key.trim();
+    if key.len() == NOTE_ID_LEN {
+        return Ok(key.to_string());
+    }
+    if key.is_empty() {
+        return Err("Note id cannot be empty".to_string());
+    }
+    let matches: Vec<String> = ctx
+        .store
+        .recent_notes(NOTE_LOOKUP_WINDOW, None)?
+        .into_iter()
+        .map(|n| n.id)
+        .filter(|id| id.starts_with(key))
+        .collect();
+    match matches.as_slice() {
+        [id] => Ok(id.clone()),
+        [] => Err(format!(
+            "No recent note with id '{key}'. Run 'logswise-cli recent' to see note ids."
+        )),
+        _ => Err(format!(
+            "'{key}' matches several notes; use more characters of the id."
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::context::testing::{context, fake_llm, note, FakeStore};
+    use crate::personalization::UserContext;
+
+    #[test]
+    fn test_parse_estimate() {
+        let reply = "<think>hmm</think>\n**PROGRESS:** 45%\nSUMMARY: Two of four modules migrated.";
+        assert_eq!(
+            parse_estimate(reply).unwrap(),
+            ProgressEstimate {
+                percent: 45,
+                summary: "Two of four modules migrated.".to_string()
+            }
+        );
+        assert_eq!(parse_estimate("PROGRESS: 140").unwrap().percent, 100);
+        assert!(parse_estimate("About halfway there").is_err());
+    }
+
+    #[test]
+    fn test_estimate_progress_uses_linked_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let llm = fake_llm(
+            "llama3",
+            "PROGRESS: 60%\nSUMMARY: Course done, project pending.",
+        );
+        let prompts = llm.prompts.clone();
+        let store = FakeStore::default();
+        store
+            .notes
+            .borrow_mut()
+            .extend([note("n1", "Finished the Rust book"), note("n2", "Lunch")]);
+        let ctx = context(llm, store, dir.path());
+
+        let mut user_context = UserContext::from_profile(Config::default());
+        user_context.add_goal("Learn Rust".into(), "skill".into(), "short_term".into());
+        let goal = user_context.goal_mut("1").unwrap();
+        assert!(estimate_progress(&ctx, goal).is_err());
+
+        goal.link_note("n1");
+        let estimate = estimate_progress(&ctx, goal).unwrap();
+        assert_eq!(estimate.percent, 60);
+        let prompt = &prompts.borrow()[0];
+        assert!(prompt.contains("Goal: Learn Rust"));
+        assert!(prompt.contains("Finished the Rust book"));
+        assert!(!prompt.contains("Lunch"));
+
+        assert_eq!(resolve_note_id(&ctx, "n2").unwrap(), "n2");
+        assert!(resolve_note_id(&ctx, "n").is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::chat_handler;
+use crate::context::AppContext;
 
 pub struct ChatHandler {}
 
@@ -7,7 +8,7 @@ impl ChatHandler {
         Self {}
     }
 
-    pub fn chat_with_assistant(&self, message: &str) {
-        chat_handler::chat_with_assistant(message);
+    pub fn chat_with_assistant(&self, ctx: &AppContext, message: &str, explain: bool) {
+        chat_handler::chat_with_assistant(ctx, message, explain);
     }
 }
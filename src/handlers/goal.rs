@@ -0,0 +1,321 @@
+use crate::context::AppContext;
+use crate::dates::Zone;
+use crate::goals::{estimate_progress, resolve_note_id};
+use crate::personalization::{Goal, UserContext};
+use crate::utils::spinner;
+use chrono::{DateTime, Utc};
+use colored::*;
+
+/// Fields `goal update` can change.
+pub struct GoalChanges {
+    pub progress: Option<u32>,
+    pub estimate: bool,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub timeline: Option<String>,
+}
+
+pub struct GoalHandler {}
+
+impl GoalHandler {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn add(&self, ctx: &AppContext, description: &str, category: &str, timeline: &str) {
+        if description.trim().is_empty() {
+            println!("{}", "❌ Goal description cannot be empty".red());
+            return;
+        }
+        let mut context = UserContext::load_for(ctx.config.clone());
+        let goal = context
+            .add_goal(
+                description.trim().to_string(),
+                normalize(category),
+                normalize(timeline),
+            )
+            .clone();
+        match context.save() {
+            Ok(_) => println!(
+                "{} {} {}",
+                "🎯 Goal added:".green(),
+                goal.description,
+                format!("(id {})", goal.id).bright_black()
+            ),
+            Err(e) => println!("{}", format!("❌ {e}").red()),
+        }
+    }
+
+    pub fn list(&self, ctx: &AppContext, all: bool) {
+        let context = UserContext::load_for(ctx.config.clone());
+        if context.goals.is_empty() {
+            println!(
+                "🎯 No goals yet. Add one with: {}",
+                "logswise-cli goal add \"Your goal\"".cyan()
+            );
+            return;
+        }
+
+        println!("\n{}\n", "🎯 Goals".bold().cyan());
+        let mut hidden = 0;
+        for (i, goal) in context.goals.iter().enumerate() {
+            if !all && !goal.is_active() {
+                hidden += 1;
+                continue;
+            }
+            print_goal(i + 1, goal);
+        }
+        if hidden > 0 {
+            println!(
+                "{}",
+                format!("{hidden} completed goal(s) hidden; use --all to show them.")
+                    .bright_black()
+            );
+        }
+    }
+
+    pub fn update(&self, ctx: &AppContext, key: &str, changes: GoalChanges) {
+        if changes.progress.is_none()
+            && !changes.estimate
+            && changes.description.is_none()
+            && changes.category.is_none()
+            && changes.timeline.is_none()
+        {
+            println!(
+                "{}",
+                "Nothing to update. Use --progress, --estimate, --description, --category or --timeline."
+                    .yellow()
+            );
+            return;
+        }
+
+        let mut context = UserContext::load_for(ctx.config.clone());
+        let goal = match context.goal_mut(key) {
+            Ok(goal) => goal,
+            Err(e) => {
+                println!("{}", format!("❌ {e}").red());
+                return;
+            }
+        };
+        let before = goal.progress;
+
+        if let Some(description) = changes.description {
+            goal.description = description.trim().to_string();
+        }
+        if let Some(category) = changes.category {
+            goal.category = normalize(&category);
+        }
+        if let Some(timeline) = changes.timeline {
+            goal.timeline = normalize(&timeline);
+        }
+        if let Some(percent) = changes.progress {
+            goal.set_progress(percent as f32 / 100.0);
+            goal.progress_summary = None;
+        }
+        if changes.estimate {
+            let spinner = spinner("Estimating progress from linked notes...");
+            let result = estimate_progress(ctx, goal);
+            spinner.finish_and_clear();
+            match result {
+                Ok(estimate) => {
+                    goal.set_progress(estimate.percent as f32 / 100.0);
+                    goal.progress_summary = Some(estimate.summary).filter(|s| !s.trim().is_empty());
+                }
+                Err(e) => {
+                    println!("{}", format!("❌ {e}").red());
+                    return;
+                }
+            }
+        }
+        goal.updated_at = Some(Utc::now());
+
+        let goal = goal.clone();
+        if let Err(e) = context.save() {
+            println!("{}", format!("❌ {e}").red());
+            return;
+        }
+        println!(
+            "{} {} ({:.0}% → {:.0}%)",
+            "✅ Updated goal:".green(),
+            goal.description,
+            before * 100.0,
+            goal.progress * 100.0
+        );
+        if let Some(summary) = &goal.progress_summary {
+            println!("   {}", summary.bright_black());
+        }
+    }
+
+    pub fn complete(&self, ctx: &AppContext, key: &str) {
+        let mut context = UserContext::load_for(ctx.config.clone());
+        let result = context.goal_mut(key).map(|goal| {
+            goal.complete();
+            goal.description.clone()
+        });
+        match result.and_then(|description| context.save().map(|_| description)) {
+            Ok(description) => println!("{} {}", "🏆 Goal completed:".green(), description),
+            Err(e) => println!("{}", format!("❌ {e}").red()),
+        }
+    }
+
+    pub fn remove(&self, ctx: &AppContext, key: &str) {
+        let mut context = UserContext::load_for(ctx.config.clone());
+        match context
+            .remove_goal(key)
+            .and_then(|goal| context.save().map(|_| goal))
+        {
+            Ok(goal) => println!("{} {}", "🗑️  Removed goal:".green(), goal.description),
+            Err(e) => println!("{}", format!("❌ {e}").red()),
+        }
+    }
+
+    pub fn link(&self, ctx: &AppContext, key: &str, note: &str) {
+        let mut context = UserContext::load_for(ctx.config.clone());
+        if let Err(e) = context.goal_index(key) {
+            println!("{}", format!("❌ {e}").red());
+            return;
+        }
+        let note_id = match resolve_note_id(ctx, note) {
+            Ok(id) => id,
+            Err(e) => {
+                println!("{}", format!("❌ {e}").red());
+                return;
+            }
+        };
+
+        let goal = context.goal_mut(key).expect("goal was just found");
+        if !goal.link_note(&note_id) {
+            println!("{}", "ℹ️  That note is already linked to this goal.".cyan());
+            return;
+        }
+        let description = goal.description.clone();
+        match context.save() {
+            Ok(_) => {
+                println!("{} {}", "🔗 Linked note to goal:".green(), description);
+                println!(
+                    "   Run {} to re-estimate progress.",
+                    format!("logswise-cli goal update {key} --estimate").cyan()
+                );
+            }
+            Err(e) => println!("{}", format!("❌ {e}").red()),
+        }
+    }
+
+    pub fn report(&self, ctx: &AppContext) {
+        let context = UserContext::load_for(ctx.config.clone());
+        if context.goals.is_empty() {
+            println!(
+                "🎯 No goals yet. Add one with: {}",
+                "logswise-cli goal add \"Your goal\"".cyan()
+            );
+            return;
+        }
+
+        let (active, completed): (Vec<&Goal>, Vec<&Goal>) =
+            context.goals.iter().partition(|g| g.is_active());
+        let average = if active.is_empty() {
+            0.0
+        } else {
+            active.iter().map(|g| g.progress).sum::<f32>() / active.len() as f32
+        };
+        let stale: Vec<&&Goal> = active
+            .iter()
+            .filter(|g| {
+                g.updated_at
+                    .is_none_or(|t| Utc::now() - t > chrono::Duration::days(14))
+            })
+            .collect();
+
+        println!("\n{}\n", "📈 Goal Report".bold().cyan());
+        println!(
+            "  Active: {}  Completed: {}  Average progress: {:.0}%",
+            active.len().to_string().green(),
+            completed.len().to_string().green(),
+            average * 100.0
+        );
+
+        if !active.is_empty() {
+            println!("\n{}", "In progress".yellow().bold());
+            for goal in &active {
+                println!(
+                    "  [{}] {:>3.0}%  {} {}",
+                    progress_bar(goal.progress),
+                    goal.progress * 100.0,
+                    goal.description,
+                    format!(
+                        "({}, {} linked note(s), updated {})",
+                        goal.timeline.replace('_', " "),
+                        goal.linked_notes.len(),
+                        format_date(ctx.zone(), goal.updated_at)
+                    )
+                    .bright_black()
+                );
+                if let Some(summary) = &goal.progress_summary {
+                    println!("         {}", summary.bright_black());
+                }
+            }
+        }
+        if !completed.is_empty() {
+            println!("\n{}", "Completed".yellow().bold());
+            for goal in &completed {
+                println!(
+                    "  🏆 {} {}",
+                    goal.description,
+                    format!("(completed {})", format_date(ctx.zone(), goal.completed_at))
+                        .bright_black()
+                );
+            }
+        }
+        if !stale.is_empty() {
+            println!(
+                "\n{}",
+                format!(
+                    "💡 {} goal(s) have not been updated in two weeks. Link recent notes and run 'goal update <goal> --estimate'.",
+                    stale.len()
+                )
+                .yellow()
+            );
+        }
+        println!();
+    }
+}
+
+fn print_goal(number: usize, goal: &Goal) {
+    let status = if goal.is_active() {
+        format!("{:.0}%", goal.progress * 100.0).normal()
+    } else {
+        "completed".green()
+    };
+    println!(
+        "  {}. {} {}",
+        number.to_string().green(),
+        goal.description.bold(),
+        format!("[{}]", goal.id).bright_black()
+    );
+    println!(
+        "     [{}] {}  {} | {} | {} linked note(s)",
+        progress_bar(goal.progress),
+        status,
+        goal.category.replace('_', " "),
+        goal.timeline.replace('_', " "),
+        goal.linked_notes.len()
+    );
+    if let Some(summary) = &goal.progress_summary {
+        println!("     {}", summary.bright_black());
+    }
+}
+
+fn progress_bar(progress: f32) -> String {
+    let filled = (progress.clamp(0.0, 1.0) * 10.0).round() as usize;
+    "▓".repeat(filled) + &"░".repeat(10 - filled)
+}
+
+fn format_date(zone: Zone, date: Option<DateTime<Utc>>) -> String {
+    date.map(|d| zone.local(d).format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "never".to_string())
+}
+
+/// `short-term` and `Short Term` are stored as `short_term`, matching the setup wizard.
+fn normalize(value: &str) -> String {
+    value.trim().to_lowercase().replace(['-', ' '], "_")
+}
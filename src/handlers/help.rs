@@ -24,7 +24,7 @@ impl HelpHandler {
 
     pub fn print_models(&self) {
         println!("\nEmbedding Models vs. LLMs:\n");
-        println!("- Embedding Models (e.g., nomic-embed-text, bge-base-en, all-minilm): Used for fast semantic search. Enables embedding-only mode—finds relevant notes, but does not generate new text.");
+        println!("- Embedding Models (e.g., nomic-embed-text, bge-base-en, all-minilm): Used for fast semantic search. Detected from the capabilities Ollama reports. Enables embedding-only mode—finds relevant notes, but does not generate new text.");
         println!("- LLMs (e.g., llama3, deepseek-coder, mistral, phi3): Used for generating suggestions and chat responses, always using your profile and relevant notes as context.\n");
         println!("How to choose: Use an LLM for chat/suggestions, or an embedding model for fast search only.");
         println!("Tip: The CLI will tell you which mode is active and how to switch models.\n");
@@ -64,26 +64,66 @@ impl HelpHandler {
             "suggestion".green().bold(),
             "s".green()
         );
+        println!(
+            "  {}  Rate a suggestion; 'suggestion history' lists past ones",
+            "feedback".green().bold()
+        );
         println!(
             "  {} (or {})   Chat with your AI assistant",
             "chat".green().bold(),
             "c".green()
         );
         println!(
-            "  {}  Start interactive mode for continuous use",
+            "  {}  Chat with session memory and /note, /search, /goal... commands",
             "interactive".green().bold()
         );
+        println!(
+            "  {}  Browse, search, edit and chat about notes full-screen",
+            "tui".green().bold()
+        );
         println!(
             "  {}  Show recent notes (default: 5)",
             "recent".green().bold()
         );
+        println!(
+            "  {}  Find notes containing some text",
+            "search".green().bold()
+        );
+        println!(
+            "  {}  Export notes as Markdown or JSON",
+            "export".green().bold()
+        );
+        println!(
+            "  {}    Send notes queued while offline",
+            "sync".green().bold()
+        );
+        println!(
+            "  {} Manage projects and the active project (add, list, show, edit, archive, use, challenges)",
+            "project".green().bold()
+        );
+        println!(
+            "  {}    Track goals (add, list, update, complete, remove, link, report)",
+            "goal".green().bold()
+        );
+        println!(
+            "  {}  Draft today's standup from notes since the last working day",
+            "standup".green().bold()
+        );
+        println!(
+            "  {}  Summarize a week, month or quarter of notes into a Markdown report",
+            "report".green().bold()
+        );
+        println!(
+            "  {}  Customize prompt templates (list, show, edit, reset)",
+            "prompt".green().bold()
+        );
 
         println!(
             "  {}  Generate shell completions",
             "completions".green().bold()
         );
         println!(
-            "  {}  Display your profile and configuration",
+            "  {}  Show note, model and suggestion usage statistics",
             "stats".green().bold()
         );
         println!(
@@ -92,7 +132,7 @@ impl HelpHandler {
         );
         println!("  {}    Explain how Logswise works", "how".green().bold());
         println!(
-            "  {} Show information about embedding models vs LLMs",
+            "  {} List, pull, remove and choose Ollama models",
             "models".green().bold()
         );
         println!(
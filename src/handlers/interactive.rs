@@ -1,3 +1,4 @@
+use crate::context::AppContext;
 use crate::interactive;
 
 pub struct InteractiveHandler {}
@@ -7,7 +8,11 @@ impl InteractiveHandler {
         Self {}
     }
 
-    pub fn run(&self) {
-        interactive::run_interactive();
+    pub fn run(&self, ctx: &mut AppContext) {
+        interactive::run_interactive(ctx);
+    }
+
+    pub fn run_tui(&self, ctx: &AppContext) {
+        interactive::browse_notes(ctx);
     }
 }
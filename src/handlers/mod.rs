@@ -1,8 +1,14 @@
 pub mod chat;
+pub mod goal;
 pub mod help;
 pub mod interactive;
+pub mod models;
 pub mod note;
 pub mod personalization;
+pub mod project;
+pub mod prompt;
+pub mod report;
 pub mod setup;
+pub mod standup;
 pub mod suggestion;
 pub mod system;
@@ -0,0 +1,292 @@
+use crate::context::AppContext;
+use crate::models::{self, ModelCatalog};
+use crate::services::ollama::{self, ModelInfo};
+use crate::services::supabase::NoteQuery;
+use crate::utils::spinner;
+use colored::*;
+use dialoguer::Confirm;
+
+pub struct ModelsHandler {}
+
+impl ModelsHandler {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Lists models installed on the configured Ollama server with their capabilities.
+    pub fn list_models(&self, ctx: &AppContext, refresh: bool) {
+        let config = &ctx.config;
+        let base_url = &config.ollama_base_url;
+
+        let refreshed;
+        let catalog = if refresh {
+            match ModelCatalog::refresh(&ctx.http, base_url) {
+                Ok(catalog) => {
+                    refreshed = catalog;
+                    &refreshed
+                }
+                Err(e) => {
+                    println!("{}", format!("❌ Could not query Ollama: {e}").red());
+                    println!("Make sure Ollama is running at {}", base_url.cyan());
+                    return;
+                }
+            }
+        } else {
+            ctx.models()
+        };
+
+        if catalog.models.is_empty() {
+            println!(
+                "{}",
+                format!("No models found on Ollama at {base_url}.").yellow()
+            );
+            println!("Install one with: logswise-cli models pull llama3");
+            return;
+        }
+
+        println!("\n{} ({})\n", "Installed Models".bold().cyan(), base_url);
+        for model in &catalog.models {
+            let mut markers = vec![];
+            if model.matches(&config.llm_name) {
+                markers.push("llm");
+            }
+            if model.matches(&config.embedding_model) {
+                markers.push("embeddings");
+            }
+            let active = if markers.is_empty() {
+                String::new()
+            } else {
+                format!(" ← {}", markers.join(", ")).green().to_string()
+            };
+            println!("  {}{}", model.name.bold(), active);
+            println!("     {}", describe(model).bright_black());
+        }
+
+        if let Some(fetched_at) = catalog.fetched_at {
+            println!(
+                "\n{}",
+                format!(
+                    "Discovered {} (use --refresh to query Ollama again)",
+                    ctx.zone().local(fetched_at).format("%Y-%m-%d %H:%M")
+                )
+                .bright_black()
+            );
+        }
+        println!(
+            "Models that only support embeddings run chat and suggestions in embedding-only mode (semantic search, no generation)."
+        );
+    }
+
+    /// Downloads a model with a progress bar.
+    pub fn pull(&self, ctx: &AppContext, name: &str) {
+        println!("⬇️  Pulling {}...", name.cyan());
+        match models::pull(&ctx.http, &ctx.config.ollama_base_url, name, true) {
+            Ok(()) => println!("{}", format!("✅ Model '{name}' is ready").green()),
+            Err(e) => println!("{}", format!("❌ Could not pull '{name}': {e}").red()),
+        }
+    }
+
+    /// Deletes an installed model after confirmation, refusing models still in use.
+    pub fn remove(&self, ctx: &AppContext, name: &str, yes: bool) {
+        let config = &ctx.config;
+        for (role, configured) in [
+            ("LLM", &config.llm_name),
+            ("embedding model", &config.embedding_model),
+        ] {
+            if same_model(configured, name) {
+                println!(
+                    "{}",
+                    format!("❌ '{name}' is your configured {role}.").red()
+                );
+                println!("Choose another one first with 'logswise-cli models set'.");
+                return;
+            }
+        }
+        if !yes && !confirm(&format!("Delete model '{name}' from Ollama?"), false) {
+            println!("Cancelled.");
+            return;
+        }
+        match ollama::delete_model(&ctx.http, &config.ollama_base_url, name) {
+            Ok(()) => {
+                // Keep pickers from offering the deleted model
+                let _ = ModelCatalog::refresh(&ctx.http, &config.ollama_base_url);
+                println!("{}", format!("🗑️  Deleted '{name}'").green());
+            }
+            Err(e) => println!("{}", format!("❌ {e}").red()),
+        }
+    }
+
+    /// Shows what Ollama reports about one installed model.
+    pub fn info(&self, ctx: &AppContext, name: &str) {
+        let config = &ctx.config;
+        let base_url = &config.ollama_base_url;
+        let installed = match ollama::list_models(&ctx.http, base_url) {
+            Ok(installed) => installed,
+            Err(e) => {
+                println!("{}", format!("❌ Could not query Ollama: {e}").red());
+                return;
+            }
+        };
+        let Some(model) = installed.iter().find(|m| m.matches(name)) else {
+            println!("{}", format!("❌ Model '{name}' is not installed").red());
+            println!("Install it with: logswise-cli models pull {name}");
+            return;
+        };
+        let model =
+            ollama::show_model(&ctx.http, base_url, model).unwrap_or_else(|_| model.clone());
+
+        println!("\n{}", model.name.bold().cyan());
+        println!("  {}", describe(&model));
+        if let Some(quantization) = &model.quantization {
+            println!("  Quantization: {quantization}");
+        }
+        if !model.capabilities.is_empty() {
+            println!("  Capabilities: {}", model.capabilities.join(", "));
+        }
+        let mut roles = vec![];
+        if model.matches(&config.llm_name) {
+            roles.push("LLM");
+        }
+        if model.matches(&config.embedding_model) {
+            roles.push("embedding model");
+        }
+        if !roles.is_empty() {
+            println!("  {}", format!("In use as {}", roles.join(" and ")).green());
+        }
+    }
+
+    /// Saves new generation and/or embedding models in setup.json.
+    ///
+    /// Switching the embedding model makes existing note vectors incomparable with new
+    /// ones, so the number of affected notes is shown and confirmation asked first.
+    pub fn set(&self, ctx: &AppContext, llm: Option<&str>, embedding: Option<&str>, yes: bool) {
+        let catalog = ctx.models();
+
+        for name in llm.iter().chain(embedding.iter()) {
+            if !catalog.models.is_empty() && catalog.find(name).is_none() {
+                println!(
+                    "{}",
+                    format!("⚠️  '{name}' is not installed; pull it with 'logswise-cli models pull {name}'")
+                        .yellow()
+                );
+            }
+        }
+        if let Some(llm) = llm {
+            if catalog.is_embedding_only(llm) {
+                println!(
+                    "{}",
+                    format!("⚠️  '{llm}' only produces embeddings; chat and suggestions will run in embedding-only mode.")
+                        .yellow()
+                );
+            }
+        }
+        if let Some(embedding) = embedding {
+            if catalog
+                .find(embedding)
+                .is_some_and(|m| !m.supports_embedding())
+            {
+                println!(
+                    "{}",
+                    format!("⚠️  Ollama does not report embedding support for '{embedding}'.")
+                        .yellow()
+                );
+            }
+            if !same_model(&ctx.config.embedding_model, embedding)
+                && !confirm_embedding_switch(ctx, embedding, yes)
+            {
+                println!("Cancelled.");
+                return;
+            }
+        }
+
+        // Saved from a copy: the loaded context keeps the models it was started with
+        let mut config = ctx.config.clone();
+        if let Some(llm) = llm {
+            config.llm_name = llm.to_string();
+        }
+        if let Some(embedding) = embedding {
+            config.embedding_model = embedding.to_string();
+        }
+        match config.save() {
+            Ok(()) => {
+                println!("{}", "✅ Models updated".green());
+                println!("  LLM: {}", config.llm_name.cyan());
+                println!("  Embeddings: {}", config.embedding_model.cyan());
+            }
+            Err(e) => println!("{}", format!("❌ {e}").red()),
+        }
+    }
+}
+
+/// Warns how many notes have vectors from the current embedding model and asks whether
+/// to switch anyway.
+fn confirm_embedding_switch(ctx: &AppContext, new: &str, yes: bool) -> bool {
+    let spinner = spinner("Counting notes with embeddings...");
+    let embedded = ctx.store.count_notes(&NoteQuery {
+        embedded: Some(true),
+        ..NoteQuery::default()
+    });
+    spinner.finish_and_clear();
+    match embedded {
+        Ok(0) => return true,
+        Ok(count) => println!(
+            "{}",
+            format!(
+                "⚠️  {count} note(s) have embeddings from '{}'. Vectors from '{new}' are not comparable, so those notes will stop showing up in semantic search.",
+                ctx.config.embedding_model
+            )
+            .yellow()
+        ),
+        Err(_) => println!(
+            "{}",
+            format!(
+                "⚠️  Notes embedded with '{}' will stop showing up in semantic search once you switch to '{new}'.",
+                ctx.config.embedding_model
+            )
+            .yellow()
+        ),
+    }
+    yes || confirm("Switch the embedding model anyway?", false)
+}
+
+fn confirm(prompt: &str, default: bool) -> bool {
+    Confirm::new()
+        .with_prompt(prompt)
+        .default(default)
+        .interact()
+        .unwrap_or(false)
+}
+
+/// True when two model names refer to the same model, ignoring a `:latest` tag.
+fn same_model(a: &str, b: &str) -> bool {
+    let normalize = |name: &str| {
+        let name = name.trim().to_lowercase();
+        name.strip_suffix(":latest").unwrap_or(&name).to_string()
+    };
+    !a.trim().is_empty() && normalize(a) == normalize(b)
+}
+
+/// One-line summary of a model's capabilities and size.
+fn describe(model: &ModelInfo) -> String {
+    let mut parts = vec![];
+    let role = match (model.supports_generation(), model.supports_embedding()) {
+        (true, true) => "generation + embeddings",
+        (true, false) => "generation",
+        (false, true) => "embeddings only",
+        (false, false) => "unknown capabilities",
+    };
+    parts.push(role.to_string());
+    if let Some(family) = &model.family {
+        parts.push(format!("family: {family}"));
+    }
+    if let Some(size) = &model.parameter_size {
+        parts.push(format!("params: {size}"));
+    }
+    if let Some(ctx) = model.context_length {
+        parts.push(format!("context: {ctx} tokens"));
+    }
+    if model.size > 0 {
+        parts.push(format!("{:.1} GB", model.size as f64 / 1e9));
+    }
+    parts.join(" | ")
+}
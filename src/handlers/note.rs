@@ -1,4 +1,12 @@
-use crate::note_handler;
+use crate::context::AppContext;
+use crate::export::{self, ExportFormat};
+use crate::note_handler::{self, NoteFilters};
+use crate::services::supabase::NoteQuery;
+use crate::utils::spinner;
+use chrono::{DateTime, Duration, Utc};
+use colored::*;
+use std::fs;
+use std::path::Path;
 
 pub struct NoteHandler {}
 
@@ -7,11 +15,82 @@ impl NoteHandler {
         Self {}
     }
 
-    pub fn add_note(&self, content: &str) {
-        note_handler::add_note(content);
+    pub fn add_note(&self, ctx: &AppContext, content: &str, goal: Option<&str>) {
+        note_handler::add_note(ctx, content, goal);
     }
 
-    pub fn show_recent_notes(&self, count: usize) {
-        note_handler::show_recent_notes(count);
+    pub fn show_recent_notes(&self, ctx: &AppContext, count: usize, filters: &NoteFilters) {
+        note_handler::show_recent_notes(ctx, count, filters);
+    }
+
+    pub fn search(&self, ctx: &AppContext, text: &str, count: usize, filters: &NoteFilters) {
+        note_handler::search_notes(ctx, text, count, filters);
+    }
+
+    /// Writes the notes matching `filters` to `output`, or to stdout when it is `None`.
+    pub fn export(
+        &self,
+        ctx: &AppContext,
+        format: ExportFormat,
+        filters: &NoteFilters,
+        output: Option<&Path>,
+    ) {
+        // Errors go to stderr so `export > file` never writes them into the file
+        let NoteQuery {
+            project,
+            since,
+            until,
+            ..
+        } = match filters.query(ctx) {
+            Ok(query) => query,
+            Err(e) => {
+                eprintln!("{}", format!("❌ {e}").red());
+                return;
+            }
+        };
+        let now = Utc::now();
+        let spinner = spinner("Fetching notes...");
+        let result = ctx.store.notes_between(
+            since.unwrap_or(DateTime::UNIX_EPOCH),
+            until.unwrap_or(now + Duration::days(1)),
+            project,
+        );
+        spinner.finish_and_clear();
+        let rendered = result.and_then(|notes| {
+            export::render(&notes, format, ctx.zone(), now).map(|text| (notes.len(), text))
+        });
+        let (count, text) = match rendered {
+            Ok(rendered) => rendered,
+            Err(e) => {
+                eprintln!("{}", format!("❌ {e}").red());
+                return;
+            }
+        };
+
+        let Some(path) = output else {
+            println!("{text}");
+            return;
+        };
+        match fs::write(path, text) {
+            Ok(_) => println!(
+                "{} {} note(s) to {}",
+                "📦 Exported".green(),
+                count,
+                path.display().to_string().cyan()
+            ),
+            Err(e) => println!(
+                "{}",
+                format!("❌ Failed to write {}: {e}", path.display()).red()
+            ),
+        }
+    }
+
+    pub fn sync(&self, ctx: &AppContext) {
+        note_handler::sync_outbox(ctx, true);
+    }
+
+    /// Quietly flushes queued notes before a command that talks to Supabase.
+    pub fn auto_sync(&self, ctx: &AppContext) {
+        note_handler::sync_outbox(ctx, false);
     }
 }
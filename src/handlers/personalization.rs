@@ -1,5 +1,11 @@
+use crate::activity::{sparkline, LearnedActivity, LEARN_DAYS, LEARN_NOTES};
+use crate::context::AppContext;
+use crate::dates::relative;
 use crate::enhanced_setup;
-use crate::personalization::UserContext;
+use crate::personalization::{ActivityPatterns, UserContext};
+use crate::services::supabase::NoteQuery;
+use crate::utils::spinner;
+use chrono::{Duration, Utc};
 use colored::*;
 
 pub struct PersonalizationHandler {}
@@ -50,34 +56,48 @@ impl PersonalizationHandler {
         }
     }
 
-    /// Collect feedback on the last suggestion
-    pub fn feedback(&self, category: Option<String>) {
-        let category = category.unwrap_or_else(|| "general".to_string());
-
-        println!("{}", "📝 Suggestion Feedback".cyan().bold());
-        println!("Help us improve your suggestions by providing feedback on the last suggestion you received.\n");
-
-        match crate::suggestion_handler::collect_suggestion_feedback(&category) {
-            Ok((accepted, satisfaction)) => {
-                // Update user context with feedback
-                if let Ok(mut context) = UserContext::load_or_create() {
-                    context.update_interaction_history(&category, accepted, satisfaction);
-                    if let Err(e) = context.save() {
-                        println!(
-                            "{}",
-                            format!("Warning: Could not save feedback: {e}").yellow()
-                        );
-                    } else {
-                        println!("{}", "✅ Thank you for your feedback! We'll use this to improve future suggestions.".green());
-                    }
-                } else {
-                    println!("{}", "❌ Could not save feedback. Please try again.".red());
-                }
-            }
+    /// Learns activity patterns from recent notes and the local command log and saves
+    /// them to the profile.
+    pub fn learn(&self, ctx: &AppContext) {
+        let now = Utc::now();
+        let since = now - Duration::days(LEARN_DAYS);
+        let spinner = spinner("Reading notes...");
+        let notes = ctx.store.find_notes(&NoteQuery {
+            since: Some(since),
+            limit: LEARN_NOTES,
+            with_embeddings: true,
+            ..NoteQuery::default()
+        });
+        spinner.finish_and_clear();
+        let notes = match notes {
+            Ok(notes) => notes,
             Err(e) => {
-                println!("{}", format!("❌ Feedback collection failed: {e}").red());
+                println!(
+                    "{}",
+                    format!("⚠️ Could not read notes ({e}); learning from commands only.").yellow()
+                );
+                vec![]
             }
+        };
+        let events = ctx.usage.since(since).unwrap_or_default();
+        let learned = LearnedActivity::learn(&notes, &events, ctx.zone(), now, LEARN_DAYS);
+
+        let mut context = UserContext::load_for(ctx.config.clone());
+        learned.apply(&mut context.activity_patterns, now);
+        if let Err(e) = context.save() {
+            println!("{}", format!("❌ {e}").red());
+            return;
         }
+        println!(
+            "{}",
+            format!(
+                "✅ Learned from {} note(s) and {} command(s) in the last {LEARN_DAYS} days.",
+                learned.notes, learned.commands
+            )
+            .green()
+        );
+        println!();
+        print_activity(&context.activity_patterns);
     }
 
     fn display_context(&self, context: &UserContext) {
@@ -86,20 +106,11 @@ impl PersonalizationHandler {
 
         // Basic Profile
         println!("{}", "👤 Profile:".yellow().bold());
-        println!(
-            "  Profession: {}",
-            context.basic_profile["profession"].as_str().unwrap_or("")
-        );
-        println!(
-            "  Experience: {}",
-            context.basic_profile["yearsExperience"]
-                .as_str()
-                .unwrap_or("")
-        );
+        println!("  Profession: {}", context.basic_profile.profession);
+        println!("  Experience: {}", context.basic_profile.years_experience);
         println!(
             "  Company: {} ({})",
-            context.basic_profile["companyName"].as_str().unwrap_or(""),
-            context.basic_profile["companySize"].as_str().unwrap_or("")
+            context.basic_profile.company_name, context.basic_profile.company_size
         );
         println!();
 
@@ -146,6 +157,18 @@ impl PersonalizationHandler {
             "  Collaboration: {}",
             context.activity_patterns.collaboration_frequency
         );
+        if !context.activity_patterns.common_topics.is_empty() {
+            println!(
+                "  Common Topics: {}",
+                context.activity_patterns.common_topics.join(", ")
+            );
+        }
+        match context.activity_patterns.learned_at {
+            Some(at) => println!("  Learned from your activity {}", relative(at, Utc::now())),
+            None => {
+                println!("  From setup answers; run 'logswise-cli personalize learn' to learn them")
+            }
+        }
         println!();
 
         // Current Projects
@@ -169,9 +192,10 @@ impl PersonalizationHandler {
             for (i, goal) in context.goals.iter().enumerate() {
                 println!("  {}. {} ({})", i + 1, goal.description, goal.category);
                 println!(
-                    "     Timeline: {}, Progress: {:.0}%",
+                    "     Timeline: {}, Progress: {:.0}%, Status: {}",
                     goal.timeline,
-                    goal.progress * 100.0
+                    goal.progress * 100.0,
+                    goal.status
                 );
             }
             println!();
@@ -205,3 +229,37 @@ impl PersonalizationHandler {
         println!();
     }
 }
+
+/// Prints learned activity: when the user is active, how often they write and about what.
+pub fn print_activity(patterns: &ActivityPatterns) {
+    let Some(learned_at) = patterns.learned_at else {
+        println!("  Not learned yet. Run 'logswise-cli personalize learn'.");
+        return;
+    };
+    println!(
+        "  Most active: {}",
+        patterns.most_active_times.join(", ").replace('_', " ")
+    );
+    if patterns.hour_histogram.iter().any(|&c| c > 0) {
+        println!("  By hour:     |{}|", sparkline(&patterns.hour_histogram));
+        println!("                0     6     12    18   23");
+    }
+    if patterns.weekday_histogram.iter().any(|&c| c > 0) {
+        println!(
+            "  By weekday:  |{}|",
+            sparkline(&patterns.weekday_histogram)
+        );
+        println!("                MTWTFSS");
+    }
+    println!(
+        "  Notes: {:.1} per week ({}), pace {}",
+        patterns.notes_per_week, patterns.note_taking_frequency, patterns.learning_pace
+    );
+    if !patterns.common_topics.is_empty() {
+        println!("  Topics: {}", patterns.common_topics.join(", "));
+    }
+    println!(
+        "  {}",
+        format!("Learned {}", relative(learned_at, Utc::now())).dimmed()
+    );
+}
@@ -0,0 +1,442 @@
+use crate::context::AppContext;
+use crate::personalization::{ProjectContext, UserContext};
+use crate::projects::{challenges_stale, extract_challenges, MAX_CHALLENGES};
+use crate::utils::spinner;
+use chrono::Utc;
+use colored::*;
+
+/// Project details set by `project add` and `project edit`.
+#[derive(Default)]
+pub struct ProjectChanges {
+    pub rename: Option<String>,
+    pub tech: Option<Vec<String>>,
+    pub team_size: Option<u32>,
+    pub pressure: Option<String>,
+    pub add_challenges: Vec<String>,
+    pub remove_challenges: Vec<String>,
+}
+
+impl ProjectChanges {
+    fn is_empty(&self) -> bool {
+        self.rename.is_none()
+            && self.tech.is_none()
+            && self.team_size.is_none()
+            && self.pressure.is_none()
+            && self.add_challenges.is_empty()
+            && self.remove_challenges.is_empty()
+    }
+
+    fn apply(self, project: &mut ProjectContext) {
+        if let Some(name) = self.rename {
+            project.name = name.trim().to_string();
+        }
+        if let Some(tech) = self.tech {
+            project.tech_stack = tech
+                .into_iter()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect();
+        }
+        if let Some(team_size) = self.team_size {
+            project.team_size = team_size;
+        }
+        if let Some(pressure) = self.pressure {
+            project.deadline_pressure = pressure.trim().to_lowercase();
+        }
+        project.current_challenges.retain(|c| {
+            !self
+                .remove_challenges
+                .iter()
+                .any(|r| r.eq_ignore_ascii_case(c))
+        });
+        project.merge_challenges(self.add_challenges, usize::MAX);
+    }
+}
+
+pub struct ProjectHandler {}
+
+impl ProjectHandler {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn add(&self, ctx: &AppContext, name: &str, details: ProjectChanges) {
+        let name = name.trim();
+        if name.is_empty() {
+            println!("{}", "❌ Project name cannot be empty".red());
+            return;
+        }
+        let mut context = UserContext::load_for(ctx.config.clone());
+        if context.project_index(name).is_ok() {
+            println!(
+                "{}",
+                format!("❌ A project named '{name}' already exists. Use 'logswise-cli project edit {name}' to change it.").red()
+            );
+            return;
+        }
+        let mut project = ProjectContext::new(name.to_string());
+        details.apply(&mut project);
+        context.add_project(project);
+        match context.save() {
+            Ok(_) => {
+                println!("{} {}", "🛠️  Project added:".green(), name);
+                println!(
+                    "   Make it the active project with {}",
+                    format!("logswise-cli project use \"{name}\"").cyan()
+                );
+            }
+            Err(e) => println!("{}", format!("❌ {e}").red()),
+        }
+    }
+
+    pub fn list(&self, ctx: &AppContext, all: bool) {
+        let context = UserContext::load_for(ctx.config.clone());
+        if context.current_projects.is_empty() {
+            println!(
+                "🛠️  No projects yet. Add one with: {}",
+                "logswise-cli project add \"Project name\"".cyan()
+            );
+            return;
+        }
+
+        println!("\n{}\n", "🛠️  Projects".bold().cyan());
+        let mut hidden = 0;
+        for project in &context.current_projects {
+            if project.archived && !all {
+                hidden += 1;
+                continue;
+            }
+            let marker = if is_active(ctx, project) {
+                " ← active".green().to_string()
+            } else if project.archived {
+                " (archived)".bright_black().to_string()
+            } else {
+                String::new()
+            };
+            println!("  {}{}", project.name.bold(), marker);
+            println!(
+                "     {} | team of {} | {} pressure | {} challenge(s)",
+                if project.tech_stack.is_empty() {
+                    "no tech stack".to_string()
+                } else {
+                    project.tech_stack.join(", ")
+                },
+                project.team_size,
+                project.deadline_pressure,
+                project.current_challenges.len()
+            );
+        }
+        if hidden > 0 {
+            println!(
+                "\n{}",
+                format!("{hidden} archived project(s) hidden; use --all to show them.")
+                    .bright_black()
+            );
+        }
+    }
+
+    pub fn show(&self, ctx: &AppContext, name: Option<&str>) {
+        let Some(name) = name.or(ctx.active_project()) else {
+            println!(
+                "{}",
+                "No active project. Name a project or run 'logswise-cli project use <name>'."
+                    .yellow()
+            );
+            return;
+        };
+        let mut context = UserContext::load_for(ctx.config.clone());
+        let index = match context.project_index(name) {
+            Ok(i) => i,
+            Err(e) => {
+                println!("{}", format!("❌ {e}").red());
+                return;
+            }
+        };
+        refresh_stale_challenges(ctx, &mut context, index);
+        let project = &context.current_projects[index];
+
+        let status = if project.archived {
+            " (archived)".bright_black().to_string()
+        } else if is_active(ctx, project) {
+            " (active)".green().to_string()
+        } else {
+            String::new()
+        };
+        println!("\n{}{}\n", project.name.bold().cyan(), status);
+        println!("  Tech Stack: {}", project.tech_stack.join(", "));
+        println!("  Team Size: {}", project.team_size);
+        println!("  Deadline Pressure: {}", project.deadline_pressure);
+        println!("  Challenges:");
+        if project.current_challenges.is_empty() {
+            println!("    {}", "none recorded".bright_black());
+        }
+        for challenge in &project.current_challenges {
+            println!("    • {challenge}");
+        }
+        if let Some(updated) = project.challenges_updated_at {
+            println!(
+                "    {}",
+                format!(
+                    "extracted from notes on {}",
+                    ctx.zone().local(updated).format("%Y-%m-%d")
+                )
+                .bright_black()
+            );
+        }
+
+        match ctx.store.recent_notes(5, Some(&project.name)) {
+            Ok(notes) if !notes.is_empty() => {
+                println!("\n  Recent Notes:");
+                for note in notes {
+                    let when = ctx.zone().describe(&note.created_at, Utc::now());
+                    println!("    • {} {}", note.content, format!("({when})").bright_black());
+                }
+            }
+            Ok(_) => println!("\n  {}", "No notes tagged with this project yet.".bright_black()),
+            Err(e) => println!(
+                "\n  {}",
+                format!("Could not load project notes: {e}. Run 'logswise-cli init' if your notes table has no 'project' column.").yellow()
+            ),
+        }
+        println!();
+    }
+
+    pub fn edit(&self, ctx: &AppContext, name: &str, changes: ProjectChanges) {
+        if changes.is_empty() {
+            println!(
+                "{}",
+                "Nothing to change. Use --rename, --tech, --team-size, --pressure, --add-challenge or --remove-challenge."
+                    .yellow()
+            );
+            return;
+        }
+        let mut context = UserContext::load_for(ctx.config.clone());
+        if let Some(new_name) = &changes.rename {
+            if let Ok(i) = context.project_index(new_name) {
+                if !context.current_projects[i].name.eq_ignore_ascii_case(name) {
+                    println!(
+                        "{}",
+                        format!("❌ A project named '{new_name}' already exists.").red()
+                    );
+                    return;
+                }
+            }
+        }
+        let renamed = changes.rename.clone();
+        let was_active = ctx
+            .active_project()
+            .is_some_and(|p| p.eq_ignore_ascii_case(name));
+        match context.project_mut(name) {
+            Ok(project) => changes.apply(project),
+            Err(e) => {
+                println!("{}", format!("❌ {e}").red());
+                return;
+            }
+        }
+        if let Err(e) = context.save() {
+            println!("{}", format!("❌ {e}").red());
+            return;
+        }
+        println!("{} {}", "✅ Updated project:".green(), name);
+
+        if let Some(new_name) = renamed {
+            if was_active {
+                set_active_project(ctx, new_name.trim());
+            }
+            println!(
+                "{}",
+                "ℹ️  Notes already tagged with the old name keep it.".cyan()
+            );
+        }
+    }
+
+    pub fn archive(&self, ctx: &AppContext, name: &str, undo: bool) {
+        let mut context = UserContext::load_for(ctx.config.clone());
+        let project_name = match context.project_mut(name) {
+            Ok(project) => {
+                project.archived = !undo;
+                project.name.clone()
+            }
+            Err(e) => {
+                println!("{}", format!("❌ {e}").red());
+                return;
+            }
+        };
+        if let Err(e) = context.save() {
+            println!("{}", format!("❌ {e}").red());
+            return;
+        }
+        if undo {
+            println!("{} {}", "📂 Restored project:".green(), project_name);
+            return;
+        }
+        println!("{} {}", "📦 Archived project:".green(), project_name);
+        if ctx
+            .active_project()
+            .is_some_and(|p| p.eq_ignore_ascii_case(&project_name))
+        {
+            set_active_project(ctx, "");
+            println!("{}", "ℹ️  It is no longer the active project.".cyan());
+        }
+    }
+
+    /// Sets, clears (`clear`) or shows the active project.
+    pub fn use_project(&self, ctx: &AppContext, name: Option<&str>, clear: bool) {
+        if clear {
+            if set_active_project(ctx, "") {
+                println!(
+                    "{}",
+                    "✅ No active project; notes are no longer scoped.".green()
+                );
+            }
+            return;
+        }
+        let Some(name) = name else {
+            match ctx.active_project() {
+                Some(project) => println!("🎯 Active project: {}", project.cyan()),
+                None => {
+                    println!("No active project. Set one with 'logswise-cli project use <name>'.")
+                }
+            }
+            return;
+        };
+
+        let mut context = UserContext::load_for(ctx.config.clone());
+        let index = match context.project_index(name) {
+            Ok(i) => i,
+            Err(e) => {
+                println!("{}", format!("❌ {e}").red());
+                return;
+            }
+        };
+        let project = &context.current_projects[index];
+        if project.archived {
+            println!(
+                "{}",
+                format!("❌ '{}' is archived. Restore it with 'logswise-cli project archive \"{}\" --undo'.", project.name, project.name).red()
+            );
+            return;
+        }
+        if set_active_project(ctx, &project.name) {
+            println!("{} {}", "🎯 Active project:".green(), project.name.cyan());
+            println!(
+                "   New notes are tagged with it, and chat, suggestions and 'recent' use its notes."
+            );
+            println!(
+                "   {}",
+                "Tagging needs a 'project' column on your notes table; run 'logswise-cli init' for the SQL."
+                    .bright_black()
+            );
+            refresh_stale_challenges(ctx, &mut context, index);
+        }
+    }
+
+    /// Extracts challenges from the project's recent notes and records new ones.
+    pub fn challenges(&self, ctx: &AppContext, name: Option<&str>) {
+        let Some(name) = name.or(ctx.active_project()) else {
+            println!(
+                "{}",
+                "No active project. Name a project or run 'logswise-cli project use <name>'."
+                    .yellow()
+            );
+            return;
+        };
+        let mut context = UserContext::load_for(ctx.config.clone());
+        let project = match context.project_mut(name) {
+            Ok(project) => project,
+            Err(e) => {
+                println!("{}", format!("❌ {e}").red());
+                return;
+            }
+        };
+
+        let spinner = spinner("Reading project notes for challenges...");
+        let result = extract_challenges(ctx, project);
+        spinner.finish_and_clear();
+        let found = match result {
+            Ok(found) => found,
+            Err(e) => {
+                println!("{}", format!("❌ {e}").red());
+                return;
+            }
+        };
+
+        if found.is_empty() {
+            println!("{}", "✅ No open challenges found in recent notes.".green());
+        } else {
+            println!("{}", "🔍 Challenges found in recent notes:".cyan());
+            for challenge in &found {
+                println!("  • {challenge}");
+            }
+        }
+        let added = project.merge_challenges(found, MAX_CHALLENGES);
+        project.challenges_updated_at = Some(Utc::now());
+        match context.save() {
+            Ok(_) => println!("{}", format!("Recorded {added} new challenge(s).").green()),
+            Err(e) => println!("{}", format!("❌ {e}").red()),
+        }
+    }
+}
+
+/// Extracts challenges again when notes tagged with the project are newer than the last
+/// extraction. Problems are reported without stopping the command that asked.
+fn refresh_stale_challenges(ctx: &AppContext, context: &mut UserContext, index: usize) {
+    let project = &context.current_projects[index];
+    if project.archived || ctx.is_embedding_only() {
+        return;
+    }
+    match challenges_stale(ctx, project) {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            log::debug!("Could not check '{}' for new notes: {e}", project.name);
+            return;
+        }
+    }
+
+    let spinner = spinner("Updating challenges from new project notes...");
+    let result = extract_challenges(ctx, project);
+    spinner.finish_and_clear();
+    let found = match result {
+        Ok(found) => found,
+        Err(e) => {
+            println!(
+                "{}",
+                format!("⚠️  Could not update challenges from notes: {e}").yellow()
+            );
+            return;
+        }
+    };
+    let project = &mut context.current_projects[index];
+    let added = project.merge_challenges(found, MAX_CHALLENGES);
+    project.challenges_updated_at = Some(Utc::now());
+    match context.save() {
+        Ok(_) if added > 0 => println!(
+            "{}",
+            format!("🔍 Recorded {added} new challenge(s) from recent notes.").cyan()
+        ),
+        Ok(_) => {}
+        Err(e) => println!(
+            "{}",
+            format!("⚠️  Could not save the updated challenges: {e}").yellow()
+        ),
+    }
+}
+
+fn is_active(ctx: &AppContext, project: &ProjectContext) -> bool {
+    ctx.active_project()
+        .is_some_and(|p| p.eq_ignore_ascii_case(&project.name))
+}
+
+/// Saves `name` (empty for none) as the active project in setup.json.
+fn set_active_project(ctx: &AppContext, name: &str) -> bool {
+    let mut config = ctx.config.clone();
+    config.active_project = name.to_string();
+    match config.save() {
+        Ok(_) => true,
+        Err(e) => {
+            println!("{}", format!("❌ {e}").red());
+            false
+        }
+    }
+}
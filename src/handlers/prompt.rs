@@ -0,0 +1,128 @@
+use crate::prompts::{builtin, Prompts, Template, BUILTIN_PROMPTS};
+use colored::*;
+use std::process::Command;
+
+pub struct PromptHandler {}
+
+impl PromptHandler {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn prompts(&self) -> Option<Prompts> {
+        match Prompts::open_default() {
+            Ok(prompts) => Some(prompts),
+            Err(e) => {
+                println!("{}", format!("❌ {e}").red());
+                None
+            }
+        }
+    }
+
+    pub fn list(&self) {
+        let Some(prompts) = self.prompts() else {
+            return;
+        };
+        println!("\n{}\n", "Prompt Templates".bold().cyan());
+        for prompt in &BUILTIN_PROMPTS {
+            let status = if prompts.is_customized(prompt.name) {
+                format!("customized: {}", prompts.path(prompt.name).display()).yellow()
+            } else {
+                "built-in".bright_black()
+            };
+            println!(
+                "  {:<12} {} ({})",
+                prompt.name.green().bold(),
+                prompt.description,
+                status
+            );
+        }
+        println!("\nEdit with 'logswise-cli prompt edit <name>', undo with 'logswise-cli prompt reset <name>'.");
+    }
+
+    pub fn show(&self, name: &str, default: bool) {
+        let Some(prompts) = self.prompts() else {
+            return;
+        };
+        let source = if default {
+            builtin(name)
+                .map(|p| p.source.to_string())
+                .ok_or_else(|| format!("Unknown prompt '{name}'"))
+        } else {
+            prompts.source(name)
+        };
+        match source {
+            Ok(source) => print!("{source}"),
+            Err(e) => println!("{}", format!("❌ {e}").red()),
+        }
+    }
+
+    pub fn edit(&self, name: &str) {
+        let Some(prompts) = self.prompts() else {
+            return;
+        };
+        let path = match prompts.ensure_file(name) {
+            Ok(path) => path,
+            Err(e) => {
+                println!("{}", format!("❌ {e}").red());
+                return;
+            }
+        };
+
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| {
+                if cfg!(windows) {
+                    "notepad".to_string()
+                } else {
+                    "vi".to_string()
+                }
+            });
+        // Editors are often configured with arguments, e.g. `code --wait`
+        let Some((program, args)) = shell_words::split(&editor)
+            .ok()
+            .and_then(|words| words.split_first().map(|(p, a)| (p.clone(), a.to_vec())))
+        else {
+            println!("{}", format!("❌ Invalid editor command '{editor}'").red());
+            println!("Edit the file directly: {}", path.display());
+            return;
+        };
+        match Command::new(&program).args(&args).arg(&path).status() {
+            Ok(status) if status.success() => {}
+            Ok(status) => {
+                println!("{}", format!("⚠️  {editor} exited with {status}").yellow());
+            }
+            Err(e) => {
+                println!(
+                    "{}",
+                    format!("❌ Could not start editor '{editor}': {e}").red()
+                );
+                println!("Edit the file directly: {}", path.display());
+                return;
+            }
+        }
+
+        match prompts.source(name).and_then(|s| Template::parse(&s)) {
+            Ok(_) => println!("{} Prompt '{}' saved.", "✅".green(), name),
+            Err(e) => {
+                println!("{} Prompt '{}' has errors: {}", "⚠️".yellow(), name, e);
+                println!("The built-in prompt will be used until this is fixed.");
+            }
+        }
+    }
+
+    pub fn reset(&self, name: &str) {
+        let Some(prompts) = self.prompts() else {
+            return;
+        };
+        match prompts.reset(name) {
+            Ok(true) => println!(
+                "{} Prompt '{}' reset to the built-in template.",
+                "✅".green(),
+                name
+            ),
+            Ok(false) => println!("Prompt '{name}' is already using the built-in template."),
+            Err(e) => println!("{}", format!("❌ {e}").red()),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::context::AppContext;
+use crate::dates::{self, DateRange, Period, Zone};
+use crate::report::generate_report;
+use crate::utils::spinner;
+use colored::*;
+use std::fs;
+use std::path::PathBuf;
+
+/// Options for `report`, as given on the command line.
+pub struct ReportOptions {
+    pub period: Option<Period>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub project: Option<String>,
+    pub output: Option<PathBuf>,
+}
+
+pub struct ReportHandler {}
+
+impl ReportHandler {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn report(&self, ctx: &AppContext, options: ReportOptions) {
+        if ctx.is_embedding_only() {
+            println!(
+                "{}",
+                "❌ Reports need a text generation model. Choose one with 'logswise-cli setup'."
+                    .red()
+            );
+            return;
+        }
+        let range = match resolve_range(&options, ctx.zone()) {
+            Ok(range) => range,
+            Err(e) => {
+                println!("{}", format!("❌ {e}").red());
+                return;
+            }
+        };
+
+        let spinner = spinner("Fetching notes...");
+        let result = generate_report(ctx, &range, options.project.as_deref(), &|status| {
+            spinner.set_message(status.to_string())
+        });
+        spinner.finish_and_clear();
+        let report = match result {
+            Ok(report) => report,
+            Err(e) => {
+                println!("{}", format!("❌ {e}").red());
+                return;
+            }
+        };
+
+        let scope = options
+            .project
+            .as_deref()
+            .map(|p| format!(" for {p}"))
+            .unwrap_or_default();
+        let document = format!(
+            "# Work Summary{scope}: {}\n\n_Generated by logswise-cli on {} from {} note(s)._\n\n{}\n",
+            range.label(),
+            ctx.zone().today().format("%Y-%m-%d"),
+            report.note_count,
+            report.markdown
+        );
+        let path = options.output.unwrap_or_else(|| {
+            PathBuf::from(format!("logswise-report-{}_{}.md", range.from, range.to))
+        });
+        if let Err(e) = fs::write(&path, &document) {
+            println!(
+                "{}",
+                format!("❌ Failed to write {}: {e}", path.display()).red()
+            );
+            return;
+        }
+
+        println!("\n{}\n", document.trim_end());
+        println!(
+            "{} {}",
+            "📄 Report saved to".green(),
+            path.display().to_string().cyan()
+        );
+        if report.passes > 0 {
+            println!(
+                "{}",
+                format!(
+                    "   {} notes in {} group(s) were summarised in {} pass(es) to fit the model's context.",
+                    report.note_count, report.group_count, report.passes
+                )
+                .bright_black()
+            );
+        }
+    }
+}
+
+/// `--from` alone implies a custom period; otherwise the default is the last week.
+fn resolve_range(options: &ReportOptions, zone: Zone) -> Result<DateRange, String> {
+    let from = options.from.as_deref().map(dates::parse_date).transpose()?;
+    let to = options.to.as_deref().map(dates::parse_date).transpose()?;
+    let period = options.period.unwrap_or(if from.is_some() {
+        Period::Custom
+    } else {
+        Period::Week
+    });
+    if period != Period::Custom && (from.is_some() || to.is_some()) {
+        return Err("--from and --to only apply to --period custom".to_string());
+    }
+    DateRange::for_period(period, zone.today(), from, to)
+}
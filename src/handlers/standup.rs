@@ -0,0 +1,73 @@
+use crate::context::AppContext;
+use crate::standup::{collect, draft, format_raw};
+use crate::utils::spinner;
+use colored::*;
+
+pub struct StandupHandler {}
+
+impl StandupHandler {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Prints a standup draft from the notes since the last working day.
+    ///
+    /// Scoped to the active project unless `all` is set. With `no_llm`, or when the model
+    /// cannot generate text, the notes are formatted as they are.
+    pub fn standup(&self, ctx: &AppContext, no_llm: bool, all: bool) {
+        let zone = ctx.zone();
+        let project = if all { None } else { ctx.active_project() };
+
+        let spinner = spinner("Fetching notes since the last working day...");
+        let notes = match collect(ctx, zone, project) {
+            Ok(notes) => notes,
+            Err(e) => {
+                spinner.finish_and_clear();
+                println!("{}", format!("❌ {e}").red());
+                return;
+            }
+        };
+        if notes.is_empty() {
+            spinner.finish_and_clear();
+            println!(
+                "{}",
+                format!("📭 No notes since {}.", notes.since_label()).yellow()
+            );
+            return;
+        }
+
+        let use_llm = !no_llm && !ctx.is_embedding_only();
+        let text = if use_llm {
+            spinner.set_message("Drafting your standup...");
+            match draft(ctx, &notes) {
+                Ok(text) => text,
+                Err(e) => {
+                    spinner.finish_and_clear();
+                    println!(
+                        "{}",
+                        format!(
+                            "⚠️  Could not draft with the LLM ({e}); showing your notes instead."
+                        )
+                        .yellow()
+                    );
+                    format_raw(&notes)
+                }
+            }
+        } else {
+            format_raw(&notes)
+        };
+        spinner.finish_and_clear();
+
+        let scope = project.map(|p| format!(" · {p}")).unwrap_or_default();
+        println!(
+            "\n{}\n",
+            format!(
+                "🗣️  Standup for {}{scope}",
+                notes.today.format("%A, %b %-d")
+            )
+            .bold()
+            .cyan()
+        );
+        println!("{text}\n");
+    }
+}
@@ -1,4 +1,5 @@
-use crate::suggestion_handler;
+use crate::context::AppContext;
+use crate::suggestion_handler::{self, FeedbackInput};
 
 pub struct SuggestionHandler {}
 
@@ -7,7 +8,23 @@ impl SuggestionHandler {
         Self {}
     }
 
-    pub fn get_suggestions(&self, query: &str) {
-        suggestion_handler::get_suggestions(query);
+    pub fn get_suggestions(&self, ctx: &AppContext, query: &str, explain: bool) {
+        suggestion_handler::get_suggestions(ctx, query, explain);
+    }
+
+    pub fn feedback(&self, ctx: &AppContext, id: Option<&str>, input: FeedbackInput) {
+        suggestion_handler::give_feedback(ctx, id, input);
+    }
+
+    pub fn explain(&self, ctx: &AppContext, query: Option<&str>) {
+        suggestion_handler::explain_adaptation(ctx, query);
+    }
+
+    pub fn history(&self, ctx: &AppContext, count: usize, unrated: bool) {
+        suggestion_handler::show_history(ctx, count, unrated);
+    }
+
+    pub fn show(&self, ctx: &AppContext, id: Option<&str>) {
+        suggestion_handler::show_suggestion(ctx, id);
     }
 }
@@ -1,7 +1,21 @@
+use crate::activity::sparkline;
+use crate::config::Config;
+use crate::context::AppContext;
+use crate::dates::{self, Bound};
+use crate::doctor::{self, Check, DoctorOptions, Status};
+use crate::handlers::personalization::print_activity;
+use crate::logging;
+use crate::personalization::UserContext;
+use crate::services::http::HttpClient;
+use crate::services::supabase::NoteQuery;
+use crate::stats::{Count, ModelStats, StatsInput, UsageStats};
+use crate::utils::spinner;
+use chrono::{DateTime, Utc};
 use clap::CommandFactory;
 use clap_complete::{generate, Shell};
 use colored::*;
 use std::io;
+use std::path::PathBuf;
 
 pub struct SystemHandler {}
 
@@ -10,45 +24,120 @@ impl SystemHandler {
         Self {}
     }
 
-    pub fn print_stats(&self) {
-        match crate::utils::load_profile() {
-            Ok(profile_json) => {
-                println!("Profile loaded from ~/.logswise/setup.json");
-                println!(
-                    "Profession: {}",
-                    profile_json["profession"].as_str().unwrap_or("-")
-                );
-                println!(
-                    "Job Title: {}",
-                    profile_json["jobTitle"].as_str().unwrap_or("-")
-                );
-                println!(
-                    "Company: {} ({} employees)",
-                    profile_json["companyName"].as_str().unwrap_or("-"),
-                    profile_json["companySize"].as_str().unwrap_or("-")
-                );
-                println!("LLM: {}", profile_json["llmName"].as_str().unwrap_or("-"));
-                println!(
-                    "Ollama Base URL: {}",
-                    profile_json["ollamaBaseUrl"].as_str().unwrap_or("-")
-                );
-                println!(
-                    "Embedding Model: {}",
-                    profile_json["embeddingModel"].as_str().unwrap_or("-")
-                );
-                println!(
-                    "Supabase URL: {}",
-                    profile_json["supabaseUrl"].as_str().unwrap_or("-")
-                );
+    /// Prints note, model and suggestion usage, optionally only since `since`.
+    pub fn print_stats(&self, ctx: &AppContext, since: Option<&str>, json: bool) {
+        // With --json, stdout carries only the document
+        let fail = |message: String| {
+            if json {
+                eprintln!("{}", message.red());
+            } else {
+                println!("{}", message.red());
             }
+        };
+        let now = Utc::now();
+        let since = match since
+            .map(|s| dates::parse_when(s, ctx.zone(), now, Bound::Start))
+            .transpose()
+        {
+            Ok(since) => since,
             Err(e) => {
-                println!("{}", format!("Error loading profile: {e}").red());
-                println!("No profile found. Run 'logswise-cli setup' first.");
+                fail(format!("❌ {e}"));
+                return;
+            }
+        };
+
+        let spinner = spinner("Counting notes...");
+        let notes = ctx
+            .store
+            .notes_between(since.unwrap_or(DateTime::UNIX_EPOCH), now, None);
+        let embedded = ctx
+            .store
+            .count_notes(&NoteQuery {
+                since,
+                embedded: Some(true),
+                ..NoteQuery::default()
+            })
+            .ok();
+        spinner.finish_and_clear();
+        let notes = match notes {
+            Ok(notes) => notes,
+            Err(e) => {
+                fail(format!("❌ Could not read notes: {e}"));
+                return;
             }
+        };
+        let context = UserContext::load_for(ctx.config.clone());
+        let stats = UsageStats::compute(
+            StatsInput {
+                notes: &notes,
+                embedded,
+                topics: context.activity_patterns.common_topics.clone(),
+                metrics: &ctx.metrics.all().unwrap_or_default(),
+                suggestions: &ctx.suggestions.all().unwrap_or_default(),
+            },
+            since,
+            ctx.zone(),
+            now,
+        );
+
+        if json {
+            match serde_json::to_string_pretty(&stats) {
+                Ok(text) => println!("{text}"),
+                Err(e) => fail(format!("❌ Failed to encode stats: {e}")),
+            }
+            return;
+        }
+
+        let period = match since {
+            Some(since) => format!("since {}", ctx.zone().local(since).format("%Y-%m-%d %H:%M")),
+            None => "all time".to_string(),
+        };
+        println!("{}", format!("📊 Logswise Stats ({period})").cyan().bold());
+        println!(
+            "{} · LLM {} · embeddings {}",
+            ctx.config.profession, ctx.config.llm_name, ctx.config.embedding_model
+        );
+
+        println!("\n{}", "📝 Notes".yellow().bold());
+        let coverage = match stats.notes.embedding_coverage_percent {
+            Some(percent) => format!(" ({percent:.0}% with embeddings)"),
+            None => String::new(),
+        };
+        println!("  Total: {}{coverage}", stats.notes.total);
+        let weekly: Vec<usize> = stats.notes.per_week.iter().map(|w| w.notes).collect();
+        println!(
+            "  Per week: |{}| {} week(s), {} this week",
+            sparkline(&weekly),
+            weekly.len(),
+            weekly.last().copied().unwrap_or(0)
+        );
+        if !stats.notes.by_project.is_empty() {
+            println!("  Projects: {}", counts(&stats.notes.by_project));
         }
+        if !stats.notes.top_tags.is_empty() {
+            println!("  Tags: {}", counts(&stats.notes.top_tags));
+        }
+        if !stats.topics.is_empty() {
+            println!("  Topics: {}", stats.topics.join(", "));
+        }
+
+        println!("\n{}", "🤖 Models".yellow().bold());
+        println!("  Generation: {}", model_line(&stats.generation, true));
+        println!("  Embeddings: {}", model_line(&stats.embeddings, false));
+
+        println!("\n{}", "💡 Suggestions".yellow().bold());
+        let acceptance = match stats.suggestions.acceptance_rate_percent {
+            Some(percent) => format!(", {percent:.0}% helpful"),
+            None => String::new(),
+        };
         println!(
-            "\nNote: For full stats (note count, etc.), future versions will fetch from Supabase.\n"
+            "  {} suggestion(s), {} rated{acceptance}",
+            stats.suggestions.total, stats.suggestions.rated
         );
+
+        println!("\n{}", "⏰ Activity".yellow().bold());
+        print_activity(&context.activity_patterns);
+        println!();
     }
 
     pub fn generate_completions(&self, shell: &str) {
@@ -70,260 +159,116 @@ impl SystemHandler {
         generate(shell_type, &mut app, "logswise-cli", &mut io::stdout());
     }
 
-    pub fn run_doctor(&self) {
-        println!("\n{}\n", "🔍 Logswise CLI Health Check".bold().cyan());
-
-        let mut issues_found = 0;
-
-        // Check configuration file exists
-        println!("{}", "Checking configuration...".bold());
-        let config_result = crate::utils::load_profile();
-        let supabase_config_result = crate::utils::load_supabase_config();
-
-        match (&config_result, &supabase_config_result) {
-            (Ok(profile), Ok(supabase_config)) => {
-                println!("  ✅ Configuration file found and valid");
-
-                // Check required fields
-                let required_fields = [
-                    ("profession", "Profession"),
-                    ("jobTitle", "Job Title"),
-                    ("llmName", "LLM Name"),
-                    ("ollamaBaseUrl", "Ollama Base URL"),
-                    ("embeddingModel", "Embedding Model"),
-                ];
-
-                for (field, display_name) in &required_fields {
-                    if let Some(value) = profile[field].as_str() {
-                        if !value.trim().is_empty() {
-                            println!("  ✅ {display_name} configured: {value}");
-                        } else {
-                            println!("  ⚠️  {} is empty", display_name.yellow());
-                            issues_found += 1;
-                        }
-                    } else {
-                        println!("  ❌ {} missing", display_name.red());
-                        issues_found += 1;
-                    }
-                }
-
-                // Check URL formats
-                if let Some(ollama_url) = profile["ollamaBaseUrl"].as_str() {
-                    if crate::validation::validate_url(ollama_url) {
-                        println!("  ✅ Ollama URL format valid");
-                    } else {
-                        println!("  ❌ Invalid Ollama URL format: {}", ollama_url.red());
-                        issues_found += 1;
-                    }
-                }
-
-                if crate::validation::validate_url(&supabase_config.project_url) {
-                    println!("  ✅ Supabase URL format valid");
-                } else {
-                    println!("  ❌ Invalid Supabase URL format");
-                    issues_found += 1;
-                }
+    /// Runs the health checks, printing each result as it comes or, with `json`, a
+    /// report at the end. Returns false when a check failed.
+    pub fn run_doctor(&self, fix: bool, json: bool) -> bool {
+        if !json {
+            let title = if fix {
+                "🔧 Logswise CLI Health Check (fixing what is safe to fix)"
+            } else {
+                "🔍 Logswise CLI Health Check"
+            };
+            println!("\n{}", title.bold().cyan());
+        }
 
-                if crate::validation::validate_api_key(&supabase_config.api_key) {
-                    println!("  ✅ Supabase API key format valid");
-                } else {
-                    println!("  ❌ Invalid Supabase API key format");
-                    issues_found += 1;
-                }
+        let mut section = "";
+        let options = DoctorOptions {
+            fix,
+            progress: !json,
+        };
+        let report = doctor::run(options, |check| {
+            if json {
+                return;
             }
-            _ => {
-                println!("  ❌ Configuration file missing or invalid");
-                println!("     Run 'logswise-cli setup' to create configuration");
-                issues_found += 1;
+            let group = check.id.split('.').next().unwrap_or_default();
+            if group != section {
+                section = group;
+                let heading = match group {
+                    "config" => "Configuration",
+                    "ollama" => "Ollama",
+                    "supabase" => "Supabase",
+                    "outbox" => "Local outbox",
+                    other => other,
+                };
+                println!("\n{}", heading.bold());
             }
-        }
+            print_check(check);
+        });
 
-        // Test Ollama connectivity (if config exists)
-        if let Ok(profile) = config_result {
-            println!("\n{}", "Testing Ollama connectivity...".bold());
-            let ollama_base_url = profile["ollamaBaseUrl"]
-                .as_str()
-                .unwrap_or("http://localhost:11434");
-            let test_url = format!("{ollama_base_url}/api/tags");
-
-            match reqwest::blocking::get(&test_url) {
-                Ok(response) if response.status().is_success() => {
-                    println!("  ✅ Ollama server is reachable");
-
-                    // Try to test embedding model
-                    let embedding_model = profile["embeddingModel"]
-                        .as_str()
-                        .unwrap_or("nomic-embed-text");
-                    println!("  🔍 Testing embedding model: {}", embedding_model.cyan());
-
-                    let client = reqwest::blocking::Client::new();
-                    let embedding_url = format!("{ollama_base_url}/api/embeddings");
-                    match crate::services::ollama::generate_embedding(
-                        &client,
-                        &embedding_url,
-                        embedding_model,
-                        "test",
-                    ) {
-                        Ok(_) => println!("  ✅ Embedding model '{embedding_model}' is working"),
-                        Err(e) => {
-                            println!(
-                                "  ⚠️  Embedding model '{}' failed: {}",
-                                embedding_model.yellow(),
-                                e
-                            );
-                            issues_found += 1;
-                        }
-                    }
-
-                    // Try to test LLM
-                    let llm_name = profile["llmName"].as_str().unwrap_or("");
-                    if !llm_name.is_empty() {
-                        println!("  🔍 Testing LLM: {}", llm_name.cyan());
-                        let generate_url = format!("{ollama_base_url}/api/generate");
-                        match crate::services::ollama::generate_suggestion(
-                            &client,
-                            &generate_url,
-                            llm_name,
-                            "test",
-                        ) {
-                            Ok(_) => println!("  ✅ LLM '{llm_name}' is working"),
-                            Err(e) => {
-                                println!("  ⚠️  LLM '{}' failed: {}", llm_name.yellow(), e);
-                                issues_found += 1;
-                            }
-                        }
-                    }
-                }
-                _ => {
+        if json {
+            match serde_json::to_string_pretty(&report) {
+                Ok(text) => println!("{text}"),
+                Err(e) => println!("{}", format!("❌ Failed to encode report: {e}").red()),
+            }
+        } else {
+            println!("\n{}", "Summary:".bold());
+            let fixed = report.checks.iter().filter(|c| c.fixed.is_some()).count();
+            if fixed > 0 {
+                println!("  🔧 Fixed {fixed} issue(s)");
+            }
+            let (warnings, failures) = (report.summary.warn, report.summary.fail);
+            if warnings + failures == 0 {
+                println!("  🎉 All systems are working correctly!");
+                println!("     Your Logswise CLI is ready to use.");
+            } else {
+                println!(
+                    "  ⚠️  {} failed, {} warning(s)",
+                    failures.to_string().red(),
+                    warnings.to_string().yellow()
+                );
+                if !fix {
                     println!(
-                        "  ❌ Cannot reach Ollama server at {}",
-                        ollama_base_url.red()
+                        "     Run 'logswise-cli doctor --fix' to fix what can be fixed safely"
                     );
-                    println!("     Make sure Ollama is running: ollama serve");
-                    issues_found += 1;
                 }
+                println!("     Run 'logswise-cli setup' to fix configuration issues");
             }
         }
 
-        // Test Supabase connectivity (if config exists)
-        if let Ok(supabase_config) = supabase_config_result {
-            println!("\n{}", "Testing Supabase connectivity...".bold());
-
-            let client = reqwest::blocking::Client::new();
-
-            // Test basic connection
-            match crate::services::supabase::test_connection(&client, &supabase_config) {
-                Ok(_) => {
-                    println!("  ✅ Supabase connection successful");
-
-                    // Test database schema
-                    println!("  🔍 Checking database schema...");
-                    match crate::services::supabase::check_notes_table_exists(
-                        &client,
-                        &supabase_config,
-                    ) {
-                        Ok(true) => {
-                            println!("  ✅ Notes table exists and is accessible");
-
-                            // Test if we can write to the table
-                            println!("  🔍 Testing database write access...");
-                            let test_url = format!("{}/rest/v1/notes", supabase_config.project_url);
-
-                            match client
-                                .post(&test_url)
-                                .header("apikey", &supabase_config.api_key)
-                                .header(
-                                    "Authorization",
-                                    format!("Bearer {}", &supabase_config.api_key),
-                                )
-                                .header("Content-Type", "application/json")
-                                .header("Prefer", "return=minimal")
-                                .json(&serde_json::json!({
-                                    "content": "Doctor health check test note",
-                                    "embedding": serde_json::Value::Null
-                                }))
-                                .send()
-                            {
-                                Ok(resp) if resp.status().is_success() => {
-                                    println!("  ✅ Database write access confirmed");
-                                }
-                                Ok(resp) => {
-                                    let status = resp.status();
-                                    println!(
-                                        "  ⚠️  Database write test failed: HTTP {}",
-                                        status.to_string().yellow()
-                                    );
-                                    if status.as_u16() == 403 {
-                                        println!(
-                                            "      Check your RLS (Row Level Security) policies"
-                                        );
-                                    }
-                                    issues_found += 1;
-                                }
-                                Err(e) => {
-                                    println!(
-                                        "  ⚠️  Database write test failed: {}",
-                                        e.to_string().yellow()
-                                    );
-                                    issues_found += 1;
-                                }
-                            }
-                        }
-                        Ok(false) => {
-                            println!("  ❌ Notes table does not exist");
-                            println!("      You need to set up your database schema");
-
-                            // Offer to set up database automatically
-                            println!("\n{}", "🔧 Database Setup Available".bold().cyan());
-                            println!("  The notes table is missing from your Supabase database.");
-                            println!("  You can set up the database schema by running:");
-                            println!("    {}", "logswise-cli setup".green());
-                            println!("  Or set up manually using SUPABASE_SETUP.md");
+        report.healthy()
+    }
 
-                            issues_found += 1;
-                        }
-                        Err(e) => {
-                            println!("  ❌ Failed to check database schema: {}", e.red());
-                            println!("      This might indicate permission or connection issues");
-                            issues_found += 1;
-                        }
-                    }
-                }
-                Err(e) => {
-                    println!("  ❌ Supabase connection failed: {}", e.red());
-                    println!("      Check your Supabase URL and API key");
-                    issues_found += 1;
+    /// Writes `logswise-bundle-<time>.txt` to the current directory for bug reports.
+    /// With `json` its messages go to stderr, so stdout holds only the doctor report.
+    pub fn write_bundle(&self, json: bool) {
+        let say = |message: String| {
+            if json {
+                eprintln!("{message}");
+            } else {
+                println!("{message}");
+            }
+        };
+        let Some(data_dir) = dirs::home_dir().map(|home| home.join(".logswise")) else {
+            say("❌ Could not determine home directory".red().to_string());
+            return;
+        };
+        let out = PathBuf::from(format!(
+            "logswise-bundle-{}.txt",
+            Utc::now().format("%Y%m%d-%H%M%S")
+        ));
+        let config = Config::load().ok();
+        match logging::write_bundle(&data_dir, config.as_ref(), &out) {
+            Ok(()) => {
+                say(format!(
+                    "\n{}",
+                    format!("📦 Bug report bundle written to {}", out.display()).green()
+                ));
+                say("   API keys are redacted; review the file before sharing it.".to_string());
+                if !config.is_some_and(|c| c.logging.redact_notes) {
+                    say("   Debug logs may quote note text; set logging.redactNotes in setup.json to leave it out.".to_string());
                 }
             }
+            Err(e) => say(format!("❌ {e}").red().to_string()),
         }
-
-        // Summary
-        println!("\n{}", "Summary:".bold());
-        if issues_found == 0 {
-            println!("  🎉 All systems are working correctly!");
-            println!("     Your Logswise CLI is ready to use.");
-        } else {
-            println!(
-                "  ⚠️  Found {} issue(s) that need attention",
-                issues_found.to_string().yellow()
-            );
-            println!("     Run 'logswise-cli setup' to fix configuration issues");
-            println!("     Check Ollama and Supabase documentation for connectivity issues");
-        }
-
-        println!("\n{}", "Useful commands:".bold());
-        println!("  logswise-cli setup     # Fix configuration");
-        println!("  ollama serve           # Start Ollama server");
-        println!("  ollama pull <model>    # Download a model");
-        println!("  logswise-cli guide     # Show detailed help");
     }
 
     pub fn run_init(&self) {
         println!("\n{}\n", "🔧 Database Initialization".bold().cyan());
 
         // Check if we have Supabase configuration
-        let supabase_config = match crate::utils::load_supabase_config() {
-            Ok(config) => config,
+        let config = Config::load().unwrap_or_default();
+        let supabase_config = match config.supabase() {
+            Ok(supabase_config) => supabase_config,
             Err(_) => {
                 println!("{}", "❌ No Supabase configuration found.".red());
                 println!(
@@ -342,7 +287,7 @@ impl SystemHandler {
         );
         println!();
 
-        let client = reqwest::blocking::Client::new();
+        let client = HttpClient::from_config(&config);
 
         // Test connection
         println!("🔍 Testing Supabase connection...");
@@ -404,3 +349,51 @@ impl SystemHandler {
         println!("  • Start interactive mode: 'logswise-cli interactive'");
     }
 }
+
+fn print_check(check: &Check) {
+    let line = match check.status {
+        Status::Pass => format!("  ✅ {}", check.message),
+        Status::Warn => format!("  ⚠️  {}", check.message.yellow()),
+        Status::Fail => format!("  ❌ {}", check.message.red()),
+        Status::Skip => format!("  ⏭️  {}", check.message.bright_black()),
+    };
+    println!("{line}");
+    if let Some(fixed) = &check.fixed {
+        println!("     {}", format!("🔧 {fixed}").green());
+    }
+    if let Some(hint) = &check.hint {
+        for line in hint.lines() {
+            println!("     {line}");
+        }
+    }
+}
+
+/// `Atlas 12, (none) 3`
+fn counts(counts: &[Count]) -> String {
+    counts
+        .iter()
+        .map(|c| format!("{} {}", c.name, c.count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `12 calls (1 failed), avg 3.2s, 4200 prompt + 900 output tokens`
+fn model_line(stats: &ModelStats, tokens: bool) -> String {
+    if stats.calls == 0 {
+        return "no calls recorded".to_string();
+    }
+    let mut line = format!("{} call(s)", stats.calls);
+    if stats.failed > 0 {
+        line.push_str(&format!(" ({} failed)", stats.failed));
+    }
+    if let Some(ms) = stats.average_latency_ms {
+        line.push_str(&format!(", avg {:.2}s", ms as f64 / 1000.0));
+    }
+    if tokens {
+        line.push_str(&format!(
+            ", {} prompt + {} output tokens",
+            stats.prompt_tokens, stats.output_tokens
+        ));
+    }
+    line
+}
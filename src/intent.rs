@@ -0,0 +1,459 @@
+use crate::services::ollama::LlmProvider;
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Neighbours that vote on a query's label.
+const K: usize = 5;
+
+/// Labelled example queries shipped with the CLI. Users can add more in
+/// `~/.logswise/intents.json`.
+const BUILTIN_INTENTS: &[(&str, &[&str])] = &[
+    (
+        "meeting_preparation",
+        &[
+            "Help me prepare for my one-on-one with my manager tomorrow",
+            "What should I bring up in the sprint planning meeting?",
+            "Agenda for a discussion with the product team",
+            "How do I prepare for my performance review conversation?",
+        ],
+    ),
+    (
+        "progress_reporting",
+        &[
+            "Summarize what I accomplished this week",
+            "Write a status update for my team",
+            "What did I complete on the migration project?",
+            "Draft a progress report for stakeholders",
+        ],
+    ),
+    (
+        "problem_solving",
+        &[
+            "I'm stuck on a bug in the payment service",
+            "How do I fix the flaky integration tests?",
+            "Our deployment keeps failing, what could be the issue?",
+            "The API is slow under load, how do I troubleshoot it?",
+        ],
+    ),
+    (
+        "skill_development",
+        &[
+            "How can I improve my system design skills?",
+            "What should I learn to get better at Rust?",
+            "Resources to develop my public speaking",
+            "How do I get better at writing tests?",
+        ],
+    ),
+    (
+        "career_growth",
+        &[
+            "How do I get promoted to senior engineer?",
+            "What should I focus on to become a tech lead?",
+            "Should I switch teams to advance my career?",
+            "How do I prepare for a staff engineer role?",
+        ],
+    ),
+    (
+        "general_advice",
+        &[
+            "Any tips for staying productive?",
+            "How should I organize my week?",
+            "Give me some advice for today",
+            "What are good habits for a developer?",
+        ],
+    ),
+];
+
+const BUILTIN_CONTEXTS: &[(&str, &[&str])] = &[
+    (
+        "team_collaboration",
+        &[
+            "How do I give feedback to a colleague?",
+            "My team disagrees on the code review process",
+            "How can we improve communication between teams?",
+            "Onboarding a new teammate",
+        ],
+    ),
+    (
+        "project_specific",
+        &[
+            "What are the next steps for our project?",
+            "Plan the rollout of the new billing system",
+            "Risks in the current project timeline",
+            "Scope of the next release",
+        ],
+    ),
+    (
+        "technical_focus",
+        &[
+            "How should I structure this code?",
+            "Best way to handle errors in a Rust library",
+            "Database indexing strategy for large tables",
+            "Refactoring a legacy module",
+        ],
+    ),
+    (
+        "general_professional",
+        &[
+            "How do I manage my time better?",
+            "Tips for avoiding burnout",
+            "How do I set good goals for the quarter?",
+            "Ways to stay motivated at work",
+        ],
+    ),
+];
+
+/// (label, example query) pairs.
+type Examples = Vec<(String, String)>;
+
+/// How a query was classified.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntentMethod {
+    /// Nearest neighbours over example embeddings.
+    Embeddings,
+    /// Keyword matching, used when embeddings are unavailable.
+    Keywords,
+}
+
+/// A labelled example close to the query, shown by `--explain`.
+#[derive(Debug, Clone)]
+pub struct Neighbour {
+    pub label: String,
+    pub example: String,
+    pub similarity: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct QueryIntent {
+    pub intent_type: String,
+    /// Share of the neighbours' similarity that voted for `intent_type` (0.0 to 1.0).
+    pub intent_confidence: f32,
+    pub context_type: String,
+    pub context_confidence: f32,
+    pub urgency_level: String,
+    pub method: IntentMethod,
+    pub neighbours: Vec<Neighbour>,
+}
+
+/// User-supplied examples in `~/.logswise/intents.json`, added to the built-in ones.
+///
+/// ```json
+/// { "intents": { "incident_review": ["Write a postmortem for last night's outage"] },
+///   "contexts": { "project_specific": ["Next steps for Atlas"] } }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct IntentExamples {
+    #[serde(default)]
+    pub intents: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub contexts: BTreeMap<String, Vec<String>>,
+}
+
+/// Embeddings of example queries for one model, cached so each example is embedded once.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct EmbeddingCache {
+    model: String,
+    embeddings: HashMap<String, Vec<f32>>,
+}
+
+/// Classifies queries by intent and context type.
+pub struct IntentClassifier {
+    examples_path: PathBuf,
+    cache_path: PathBuf,
+}
+
+impl IntentClassifier {
+    pub fn open_default() -> Result<Self, String> {
+        let mut dir = home_dir().ok_or("Could not determine home directory")?;
+        dir.push(".logswise");
+        Ok(Self::at(&dir))
+    }
+
+    /// Uses `dir/intents.json` for user examples and `dir/cache/` for embeddings.
+    pub fn at(dir: &Path) -> Self {
+        Self {
+            examples_path: dir.join("intents.json"),
+            cache_path: dir.join("cache/intent_embeddings.json"),
+        }
+    }
+
+    /// Built-in examples merged with the user's, as (label, example) pairs.
+    fn examples(&self) -> (Examples, Examples) {
+        let user: IntentExamples = match fs::read_to_string(&self.examples_path) {
+            Ok(data) => serde_json::from_str(&data).unwrap_or_else(|e| {
+                log::warn!("Ignoring {}: {e}", self.examples_path.display());
+                IntentExamples::default()
+            }),
+            Err(_) => IntentExamples::default(),
+        };
+        (
+            merge(BUILTIN_INTENTS, &user.intents),
+            merge(BUILTIN_CONTEXTS, &user.contexts),
+        )
+    }
+
+    /// Classifies `query` using the LLM's embedding model, falling back to keywords when
+    /// embeddings cannot be produced. `query_embedding` avoids embedding the query twice.
+    pub fn classify(
+        &self,
+        llm: &dyn LlmProvider,
+        query: &str,
+        query_embedding: Option<&[f32]>,
+        project_names: &[String],
+    ) -> QueryIntent {
+        let mut cache = self.read_cache(llm.embedding_model());
+        let result = self.classify_with(
+            |text| {
+                if text == query {
+                    if let Some(embedding) = query_embedding {
+                        return Ok(embedding.to_vec());
+                    }
+                }
+                if let Some(embedding) = cache.embeddings.get(text) {
+                    return Ok(embedding.clone());
+                }
+                let embedding = llm.embed(text)?;
+                if text != query {
+                    cache.embeddings.insert(text.to_string(), embedding.clone());
+                }
+                Ok(embedding)
+            },
+            query,
+        );
+        // A failed cache write only means examples are embedded again next time
+        let _ = self.write_cache(&cache);
+
+        result.unwrap_or_else(|_| keyword_intent(query, project_names))
+    }
+
+    fn classify_with<F>(&self, mut embed: F, query: &str) -> Result<QueryIntent, String>
+    where
+        F: FnMut(&str) -> Result<Vec<f32>, String>,
+    {
+        let query_embedding = embed(query)?;
+        let (intents, contexts) = self.examples();
+
+        let mut score = |examples: &[(String, String)]| -> Result<Vec<Neighbour>, String> {
+            let mut neighbours = vec![];
+            for (label, example) in examples {
+                neighbours.push(Neighbour {
+                    label: label.clone(),
+                    example: example.clone(),
+                    similarity: cosine_similarity(&query_embedding, &embed(example)?),
+                });
+            }
+            neighbours.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+            neighbours.truncate(K);
+            Ok(neighbours)
+        };
+        let intent_neighbours = score(&intents)?;
+        let context_neighbours = score(&contexts)?;
+
+        let (intent_type, intent_confidence) = vote(&intent_neighbours);
+        let (context_type, context_confidence) = vote(&context_neighbours);
+        Ok(QueryIntent {
+            intent_type,
+            intent_confidence,
+            context_type,
+            context_confidence,
+            urgency_level: urgency_level(query).to_string(),
+            method: IntentMethod::Embeddings,
+            neighbours: intent_neighbours,
+        })
+    }
+
+    fn read_cache(&self, model: &str) -> EmbeddingCache {
+        fs::read_to_string(&self.cache_path)
+            .ok()
+            .and_then(|data| serde_json::from_str::<EmbeddingCache>(&data).ok())
+            .filter(|cache| cache.model == model)
+            .unwrap_or_else(|| EmbeddingCache {
+                model: model.to_string(),
+                embeddings: HashMap::new(),
+            })
+    }
+
+    fn write_cache(&self, cache: &EmbeddingCache) -> Result<(), String> {
+        if let Some(parent) = self.cache_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+        }
+        let data = serde_json::to_string(cache)
+            .map_err(|e| format!("Failed to encode embedding cache: {e}"))?;
+        fs::write(&self.cache_path, data)
+            .map_err(|e| format!("Failed to write embedding cache: {e}"))
+    }
+}
+
+fn merge(builtin: &[(&str, &[&str])], user: &BTreeMap<String, Vec<String>>) -> Examples {
+    let builtin = builtin.iter().flat_map(|(label, examples)| {
+        examples
+            .iter()
+            .map(move |e| (label.to_string(), e.to_string()))
+    });
+    let user = user
+        .iter()
+        .flat_map(|(label, examples)| examples.iter().map(move |e| (label.clone(), e.clone())));
+    builtin.chain(user).collect()
+}
+
+/// Similarity-weighted vote among the nearest neighbours.
+fn vote(neighbours: &[Neighbour]) -> (String, f32) {
+    let mut weights: BTreeMap<&str, f32> = BTreeMap::new();
+    for n in neighbours {
+        *weights.entry(&n.label).or_default() += n.similarity.max(0.0);
+    }
+    let total: f32 = weights.values().sum();
+    weights
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(label, weight)| {
+            let confidence = if total > 0.0 { weight / total } else { 0.0 };
+            (label.to_string(), confidence)
+        })
+        .unwrap_or_else(|| ("general_advice".to_string(), 0.0))
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Keyword classification used when embeddings are unavailable. A query naming one of
+/// the user's projects counts as project-specific.
+pub fn keyword_intent(query: &str, project_names: &[String]) -> QueryIntent {
+    let query_lower = query.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| query_lower.contains(w));
+
+    let intent_type = if has(&["meeting", "one-on-one", "discuss", "prepare"]) {
+        "meeting_preparation"
+    } else if has(&["progress", "update", "accomplished", "completed"]) {
+        "progress_reporting"
+    } else if has(&["problem", "issue", "challenge", "stuck"]) {
+        "problem_solving"
+    } else if has(&["learn", "skill", "improve", "develop"]) {
+        "skill_development"
+    } else if has(&["career", "promotion", "senior", "advancement"]) {
+        "career_growth"
+    } else {
+        "general_advice"
+    };
+
+    let names_project = project_names
+        .iter()
+        .any(|name| !name.trim().is_empty() && query_lower.contains(&name.to_lowercase()));
+    let context_type = if has(&["team", "colleague"]) {
+        "team_collaboration"
+    } else if names_project || has(&["project"]) {
+        "project_specific"
+    } else if has(&["technical", "code", "development"]) {
+        "technical_focus"
+    } else {
+        "general_professional"
+    };
+
+    QueryIntent {
+        intent_type: intent_type.to_string(),
+        intent_confidence: 0.0,
+        context_type: context_type.to_string(),
+        context_confidence: 0.0,
+        urgency_level: urgency_level(query).to_string(),
+        method: IntentMethod::Keywords,
+        neighbours: vec![],
+    }
+}
+
+fn urgency_level(query: &str) -> &'static str {
+    let query_lower = query.to_lowercase();
+    if ["tomorrow", "urgent", "asap", "immediately"]
+        .iter()
+        .any(|w| query_lower.contains(w))
+    {
+        "high"
+    } else if query_lower.contains("soon") || query_lower.contains("this week") {
+        "medium"
+    } else {
+        "normal"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Toy embedding: one dimension per topic word.
+    fn embed(text: &str) -> Result<Vec<f32>, String> {
+        let text = text.to_lowercase();
+        let topics = [
+            ["meeting", "agenda", "one-on-one"],
+            ["bug", "fix", "failing"],
+            ["postmortem", "outage", "incident"],
+        ];
+        let mut v: Vec<f32> = topics
+            .iter()
+            .map(|words| words.iter().filter(|w| text.contains(*w)).count() as f32)
+            .collect();
+        v.push(0.1);
+        Ok(v)
+    }
+
+    #[test]
+    fn test_nearest_examples_decide_intent_and_user_examples_extend_labels() {
+        let dir = tempfile::tempdir().unwrap();
+        let classifier = IntentClassifier::at(dir.path());
+
+        let intent = classifier
+            .classify_with(embed, "Can you fix this bug in the failing job?")
+            .unwrap();
+        assert_eq!(intent.intent_type, "problem_solving");
+        assert_eq!(intent.method, IntentMethod::Embeddings);
+        assert!(intent.intent_confidence > 0.5);
+        assert_eq!(intent.neighbours[0].label, "problem_solving");
+
+        fs::write(
+            dir.path().join("intents.json"),
+            r#"{ "intents": { "incident_review": [
+                "Write a postmortem for the outage",
+                "Summarize the incident timeline",
+                "Outage postmortem action items"
+            ] } }"#,
+        )
+        .unwrap();
+        let intent = classifier
+            .classify_with(embed, "Draft the postmortem for yesterday's outage")
+            .unwrap();
+        assert_eq!(intent.intent_type, "incident_review");
+    }
+
+    #[test]
+    fn test_keyword_fallback_uses_project_names() {
+        let intent = keyword_intent("Next steps for Atlas ASAP", &["Atlas".to_string()]);
+        assert_eq!(intent.method, IntentMethod::Keywords);
+        assert_eq!(intent.context_type, "project_specific");
+        assert_eq!(intent.urgency_level, "high");
+
+        let intent = keyword_intent("I'm stuck", &[]);
+        assert_eq!(intent.intent_type, "problem_solving");
+        assert_eq!(intent.context_type, "general_professional");
+    }
+
+    #[test]
+    fn test_vote_confidence() {
+        let n = |label: &str, similarity| Neighbour {
+            label: label.to_string(),
+            example: String::new(),
+            similarity,
+        };
+        let (label, confidence) = vote(&[n("a", 0.9), n("a", 0.6), n("b", 0.5)]);
+        assert_eq!(label, "a");
+        assert!((confidence - 0.75).abs() < 1e-6);
+    }
+}
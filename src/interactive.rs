@@ -1,125 +1,647 @@
+use chrono::{DateTime, Utc};
 use colored::*;
-use dialoguer::{Input, Select};
+use dirs::home_dir;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Editor, Helper};
+use std::fs;
+use std::path::PathBuf;
 
-use crate::chat_handler;
-use crate::note_handler;
+use crate::chat_handler::{self, ChatReply, ChatTurn};
+use crate::context::AppContext;
+use crate::dates::Zone;
+use crate::handlers::goal::GoalHandler;
+use crate::handlers::system::SystemHandler;
+use crate::note_handler::{self, NoteFilters};
 use crate::suggestion_handler;
+use crate::utils::spinner;
+
+/// Slash commands and what they do, in the order `/help` lists them.
+const COMMANDS: [(&str, &str); 12] = [
+    ("/note", "<text>  Save a note"),
+    ("/search", "<text>  Find notes containing the text"),
+    ("/recent", "[count]  Show the latest notes"),
+    (
+        "/suggest",
+        "<question>  Get suggestions based on your notes",
+    ),
+    (
+        "/goal",
+        "[list|add <text>|done <goal>|link <goal> <note>|report]  Manage goals",
+    ),
+    (
+        "/model",
+        "[name]  Show models or switch the model for this session",
+    ),
+    (
+        "/session",
+        "[clear|save [file]]  Show, reset or save this conversation",
+    ),
+    ("/browse", "Open the full-screen note browser"),
+    ("/stats", "Show note, model and suggestion usage"),
+    ("/help", "Show this help"),
+    ("/exit", "Leave interactive mode"),
+    ("/quit", "Leave interactive mode"),
+];
+const GOAL_ACTIONS: [&str; 5] = ["list", "add", "done", "link", "report"];
+const SESSION_ACTIONS: [&str; 2] = ["clear", "save"];
+/// Recent notes whose ids are offered by tab completion.
+const COMPLETION_NOTES: usize = 50;
+const DEFAULT_RECENT: usize = 5;
+const SEARCH_RESULTS: usize = 10;
+
+/// A line typed at the interactive prompt.
+#[derive(Debug, PartialEq)]
+enum Command {
+    Chat(String),
+    Note(String),
+    Search(String),
+    Recent(usize),
+    Suggest(String),
+    Goal(GoalCommand),
+    Model(Option<String>),
+    Session(SessionCommand),
+    Browse,
+    Stats,
+    Help,
+    Exit,
+    /// A known command used wrongly; holds the usage line to show.
+    Usage(String),
+    Unknown(String),
+}
+
+#[derive(Debug, PartialEq)]
+enum GoalCommand {
+    List,
+    Add(String),
+    Done(String),
+    Link { goal: String, note: String },
+    Report,
+}
+
+#[derive(Debug, PartialEq)]
+enum SessionCommand {
+    Show,
+    Clear,
+    Save(Option<PathBuf>),
+}
+
+/// Parses one prompt line. Plain text is a chat message; blank lines are ignored.
+fn parse_line(line: &str) -> Option<Command> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    let Some(rest) = line.strip_prefix('/') else {
+        return Some(Command::Chat(line.to_string()));
+    };
+    let (name, args) = match rest.split_once(char::is_whitespace) {
+        Some((name, args)) => (name, args.trim()),
+        None => (rest, ""),
+    };
+    let name = format!("/{}", name.to_lowercase());
+    let show_usage = || Command::Usage(usage(&name));
+    let text = |make: fn(String) -> Command| {
+        if args.is_empty() {
+            show_usage()
+        } else {
+            make(args.to_string())
+        }
+    };
+
+    let command = match name.as_str() {
+        "/note" => text(Command::Note),
+        "/search" => text(Command::Search),
+        "/suggest" => text(Command::Suggest),
+        "/recent" if args.is_empty() => Command::Recent(DEFAULT_RECENT),
+        "/recent" => match args.parse() {
+            Ok(count) if count > 0 => Command::Recent(count),
+            _ => show_usage(),
+        },
+        "/goal" => parse_goal(args).map_or_else(show_usage, Command::Goal),
+        "/model" => Command::Model(Some(args.to_string()).filter(|a| !a.is_empty())),
+        "/session" => match args.split_once(char::is_whitespace).unwrap_or((args, "")) {
+            ("", _) => Command::Session(SessionCommand::Show),
+            ("clear", "") => Command::Session(SessionCommand::Clear),
+            ("save", path) => Command::Session(SessionCommand::Save(
+                Some(path.trim())
+                    .filter(|p| !p.is_empty())
+                    .map(PathBuf::from),
+            )),
+            _ => show_usage(),
+        },
+        "/browse" => Command::Browse,
+        "/stats" => Command::Stats,
+        "/help" => Command::Help,
+        "/exit" | "/quit" => Command::Exit,
+        _ => Command::Unknown(name),
+    };
+    Some(command)
+}
+
+fn parse_goal(args: &str) -> Option<GoalCommand> {
+    let mut words = args.split_whitespace();
+    let action = words.next().unwrap_or("list");
+    let rest: Vec<&str> = words.collect();
+    match (action, rest.as_slice()) {
+        ("list", []) => Some(GoalCommand::List),
+        ("report", []) => Some(GoalCommand::Report),
+        ("add", [_, ..]) => Some(GoalCommand::Add(rest.join(" "))),
+        ("done", [goal]) => Some(GoalCommand::Done(goal.to_string())),
+        ("link", [goal, note]) => Some(GoalCommand::Link {
+            goal: goal.to_string(),
+            note: note.to_string(),
+        }),
+        _ => None,
+    }
+}
+
+fn usage(name: &str) -> String {
+    COMMANDS
+        .iter()
+        .find(|(command, _)| *command == name)
+        .map(|(command, help)| {
+            let args = help.split("  ").next().unwrap_or_default();
+            format!("{command} {args}")
+        })
+        .unwrap_or_default()
+}
+
+/// Completion for the word before `pos`: command names, their sub-actions, and note ids
+/// where a command takes one. Returns where the completed word starts and the candidates.
+fn complete(line: &str, pos: usize, note_ids: &[String]) -> (usize, Vec<String>) {
+    let before = &line[..pos];
+    let start = before.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let word = &before[start..];
+    let previous: Vec<&str> = before[..start].split_whitespace().collect();
+    let matching = |options: &mut dyn Iterator<Item = &str>| -> Vec<String> {
+        options
+            .filter(|o| o.starts_with(word))
+            .map(str::to_string)
+            .collect()
+    };
+
+    let candidates = match previous.as_slice() {
+        [] if word.starts_with('/') => matching(&mut COMMANDS.iter().map(|(c, _)| *c)),
+        ["/goal"] => matching(&mut GOAL_ACTIONS.into_iter()),
+        ["/session"] => matching(&mut SESSION_ACTIONS.into_iter()),
+        ["/goal", "link", _] => matching(&mut note_ids.iter().map(String::as_str)),
+        _ => Vec::new(),
+    };
+    (start, candidates)
+}
+
+/// Line editor helper providing tab completion.
+struct ReplHelper {
+    /// Short ids of recent notes, refreshed when notes are added or listed.
+    note_ids: Vec<String>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(complete(line, pos, &self.note_ids))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// State kept across turns of one interactive session.
+struct Session {
+    started: DateTime<Utc>,
+    turns: Vec<ChatTurn>,
+    notes_added: usize,
+}
+
+impl Session {
+    /// Markdown of the conversation; with `thinking`, each answer's reasoning is kept
+    /// in a collapsed block before it.
+    fn transcript(&self, zone: Zone, model: &str, thinking: bool) -> String {
+        let mut text = format!(
+            "# Logswise session {}\n\nModel: {model}\n",
+            zone.local(self.started).format("%Y-%m-%d %H:%M")
+        );
+        for turn in &self.turns {
+            text.push_str(&format!("\n**You:** {}\n", turn.user));
+            if thinking && !turn.thinking.is_empty() {
+                text.push_str(&format!(
+                    "\n<details><summary>Thinking</summary>\n\n{}\n\n</details>\n",
+                    turn.thinking.join("\n\n")
+                ));
+            }
+            text.push_str(&format!("\n**Assistant:** {}\n", turn.assistant));
+        }
+        text
+    }
+}
+
+fn history_path() -> Option<PathBuf> {
+    let mut path = home_dir()?;
+    path.push(".logswise/history.txt");
+    Some(path)
+}
+
+/// Runs an interactive session: plain text chats with the assistant, keeping earlier
+/// turns as context, and `/` commands capture, find and manage notes.
+pub fn run_interactive(ctx: &mut AppContext) {
+    let mut editor: Editor<ReplHelper, DefaultHistory> = match Editor::new() {
+        Ok(editor) => editor,
+        Err(e) => {
+            println!(
+                "{}",
+                format!("❌ Could not start the line editor: {e}").red()
+            );
+            return;
+        }
+    };
+    editor.set_helper(Some(ReplHelper {
+        note_ids: recent_note_ids(ctx),
+    }));
+    let history = history_path();
+    if let Some(path) = &history {
+        let _ = editor.load_history(path);
+    }
 
-/// Runs an interactive session for continuous use
-pub fn run_interactive() {
     println!(
         "{}",
         "🚀 Welcome to Logswise Interactive Mode!".cyan().bold()
     );
     println!(
         "{}",
-        "Type 'help' for commands, 'exit' to quit".bright_black()
+        "Type a message to chat, /help for commands, /exit to quit. Tab completes commands."
+            .bright_black()
     );
     println!();
 
+    let mut session = Session {
+        started: Utc::now(),
+        turns: Vec::new(),
+        notes_added: 0,
+    };
     loop {
-        let action_options = vec![
-            "💬 Chat",
-            "📝 Add Note",
-            "💡 Get Suggestions",
-            "📊 View Stats",
-            "❓ Help",
-            "🚪 Exit",
-        ];
-
-        let selection = Select::new()
-            .with_prompt("What would you like to do?")
-            .items(&action_options)
-            .default(0)
-            .interact();
-
-        match selection {
-            Ok(0) => {
-                // Chat
-                let message: String = Input::new()
-                    .with_prompt("💬 Ask me anything")
-                    .interact_text()
-                    .unwrap_or_default();
-
-                if !message.trim().is_empty() {
-                    chat_handler::chat_with_assistant(&message);
-                }
-            }
-            Ok(1) => {
-                // Add Note
-                let note: String = Input::new()
-                    .with_prompt("📝 What's on your mind?")
-                    .interact_text()
-                    .unwrap_or_default();
-
-                if !note.trim().is_empty() {
-                    note_handler::add_note(&note);
-                }
+        let line = match editor.readline("logswise> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                println!("{}", format!("❌ {e}").red());
+                break;
             }
-            Ok(2) => {
-                // Get Suggestions
-                let query: String = Input::new()
-                    .with_prompt("💡 What do you need suggestions for?")
-                    .interact_text()
-                    .unwrap_or_default();
-
-                if !query.trim().is_empty() {
-                    suggestion_handler::get_suggestions(&query);
-                }
+        };
+        let Some(command) = parse_line(&line) else {
+            continue;
+        };
+        let _ = editor.add_history_entry(line.trim());
+
+        let refresh_ids = matches!(command, Command::Note(_) | Command::Recent(_));
+        match command {
+            Command::Exit => break,
+            command => run_command(ctx, &mut session, command),
+        }
+        if refresh_ids {
+            if let Some(helper) = editor.helper_mut() {
+                helper.note_ids = recent_note_ids(ctx);
             }
-            Ok(3) => {
-                // View Stats
-                use crate::handlers::system::SystemHandler;
-                let system_handler = SystemHandler::new();
-                system_handler.print_stats();
+        }
+        println!();
+    }
+
+    if let Some(path) = &history {
+        let _ = editor.save_history(path);
+    }
+    println!(
+        "{}",
+        "👋 Thanks for using Logswise! See you next time.".green()
+    );
+}
+
+fn run_command(ctx: &mut AppContext, session: &mut Session, command: Command) {
+    match command {
+        Command::Chat(message) => chat_turn(ctx, session, &message),
+        Command::Note(text) => {
+            if note_handler::add_note(ctx, &text, None) {
+                session.notes_added += 1;
             }
-            Ok(4) => {
-                // Help
-                print_interactive_help();
+        }
+        Command::Search(text) => {
+            note_handler::search_notes(ctx, &text, SEARCH_RESULTS, &NoteFilters::default())
+        }
+        Command::Recent(count) => {
+            note_handler::show_recent_notes(ctx, count, &NoteFilters::default())
+        }
+        Command::Suggest(query) => suggestion_handler::get_suggestions(ctx, &query, false),
+        Command::Goal(goal) => {
+            let goals = GoalHandler::new();
+            match goal {
+                GoalCommand::List => goals.list(ctx, false),
+                GoalCommand::Add(text) => goals.add(ctx, &text, "personal", "medium_term"),
+                GoalCommand::Done(key) => goals.complete(ctx, &key),
+                GoalCommand::Link { goal, note } => goals.link(ctx, &goal, &note),
+                GoalCommand::Report => goals.report(ctx),
             }
-            Ok(5) => {
-                // Exit
+        }
+        Command::Model(None) => print_models(ctx),
+        Command::Model(Some(name)) => switch_model(ctx, &name),
+        Command::Session(SessionCommand::Show) => print_session(ctx, session),
+        Command::Session(SessionCommand::Clear) => {
+            session.turns.clear();
+            println!("{}", "🧹 Conversation cleared.".green());
+        }
+        Command::Session(SessionCommand::Save(path)) => save_session(ctx, session, path),
+        Command::Browse => browse_notes(ctx),
+        Command::Stats => SystemHandler::new().print_stats(ctx, None, false),
+        Command::Help => print_interactive_help(),
+        Command::Usage(usage) => println!("{} {}", "Usage:".yellow(), usage),
+        Command::Unknown(name) => println!(
+            "{}",
+            format!("❓ Unknown command '{name}'. Type /help for the list.").yellow()
+        ),
+        Command::Exit => {}
+    }
+}
+
+/// Sends a chat message with the conversation so far and records the answer.
+fn chat_turn(ctx: &AppContext, session: &mut Session, message: &str) {
+    if ctx.llm_name().is_empty() {
+        println!(
+            "{}",
+            "😅 No LLM configured. Please set up your LLM in setup.json.".yellow()
+        );
+        return;
+    }
+    let spinner = spinner("Thinking...");
+    let reply = chat_handler::chat(ctx, message, &session.turns, |msg| {
+        spinner.set_message(msg.to_string())
+    });
+    spinner.finish_and_clear();
+    match reply {
+        Ok(ChatReply::Answer { reply, budget }) => {
+            if budget.trimmed() {
                 println!(
                     "{}",
-                    "👋 Thanks for using Logswise! See you next time.".green()
+                    "ℹ️  Some notes or earlier turns were left out to fit the model's context window."
+                        .bright_black()
                 );
-                break;
-            }
-            Err(_) => {
-                println!("{}", "❌ Invalid selection. Try again.".red());
             }
-            _ => {
-                println!("{}", "❌ Invalid selection. Try again.".red());
+            reply.print_thinking(ctx.generation.thinking.unwrap_or_default());
+            println!("{}", reply.answer.cyan());
+            session.turns.push(ChatTurn {
+                user: message.to_string(),
+                assistant: reply.answer,
+                thinking: reply.thinking,
+            });
+        }
+        Ok(ChatReply::Notes(notes)) if notes.is_empty() => {
+            println!("No relevant notes found.");
+        }
+        Ok(ChatReply::Notes(notes)) => {
+            println!(
+                "{}",
+                "⚡ Embedding-only model; showing related notes instead of an answer."
+                    .bright_black()
+            );
+            for (i, note) in notes.iter().enumerate() {
+                println!("{}. {}", (i + 1).to_string().green(), note.content);
             }
         }
-        println!(); // Add spacing between actions
+        Err(msg) => println!("{}", msg.red()),
     }
 }
 
-fn print_interactive_help() {
-    println!("{}", "Interactive Mode Help:".bold().cyan());
+fn print_models(ctx: &AppContext) {
+    println!("🧠 Current model: {}", ctx.llm_name().cyan());
+    let models = ctx.models().generation_models();
+    if models.is_empty() {
+        println!(
+            "{}",
+            "No other models found on the Ollama server.".bright_black()
+        );
+        return;
+    }
+    println!("Installed models:");
+    for model in models {
+        let marker = if model.matches(ctx.llm_name()) {
+            "*"
+        } else {
+            " "
+        };
+        println!(" {} {}", marker.green(), model.name);
+    }
     println!(
-        "• {} - Have a conversation with your AI assistant",
-        "Chat".green()
+        "{}",
+        "Use /model <name> to switch for this session.".bright_black()
     );
+}
+
+fn switch_model(ctx: &mut AppContext, name: &str) {
+    let catalog = ctx.models();
+    if !catalog.models.is_empty() && catalog.find(name).is_none() {
+        println!(
+            "{}",
+            format!("❌ Model '{name}' is not installed. Run /model to see installed models.")
+                .red()
+        );
+        return;
+    }
+    ctx.use_model(name);
     println!(
-        "• {} - Capture thoughts, ideas, or code snippets",
-        "Add Note".green()
+        "🔁 Using {} for the rest of this session.",
+        ctx.llm_name().cyan()
     );
+    if ctx.is_embedding_only() {
+        println!(
+            "{}",
+            "⚡ This is an embedding model: chat will show related notes instead of answers."
+                .yellow()
+        );
+    }
+}
+
+fn print_session(ctx: &AppContext, session: &Session) {
+    let now = Utc::now();
+    println!("{}", "🗒️  Session".bold().cyan());
     println!(
-        "• {} - Get context-aware advice and recommendations",
-        "Get Suggestions".green()
+        "Started: {}",
+        ctx.zone().describe(&session.started.to_rfc3339(), now)
     );
+    println!("Model: {}", ctx.llm_name().cyan());
+    if let Some(project) = ctx.active_project() {
+        println!("Project: {}", project.cyan());
+    }
+    println!("Chat turns remembered: {}", session.turns.len());
+    println!("Notes added: {}", session.notes_added);
+}
+
+fn save_session(ctx: &AppContext, session: &Session, path: Option<PathBuf>) {
+    if session.turns.is_empty() {
+        println!(
+            "{}",
+            "Nothing to save yet: the conversation is empty.".yellow()
+        );
+        return;
+    }
+    let path = path.unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "logswise-session-{}.md",
+            ctx.zone().local(session.started).format("%Y-%m-%d_%H%M")
+        ))
+    });
+    let thinking = ctx.generation.save_thinking.unwrap_or(false);
+    match fs::write(
+        &path,
+        session.transcript(ctx.zone(), ctx.llm_name(), thinking),
+    ) {
+        Ok(_) => println!(
+            "{} {}",
+            "💾 Conversation saved to".green(),
+            path.display().to_string().cyan()
+        ),
+        Err(e) => println!(
+            "{}",
+            format!("❌ Failed to write {}: {e}", path.display()).red()
+        ),
+    }
+}
+
+/// Short ids of the latest notes, for tab completion. Empty when Supabase is unreachable.
+fn recent_note_ids(ctx: &AppContext) -> Vec<String> {
+    ctx.store
+        .recent_notes(COMPLETION_NOTES, None)
+        .map(|notes| {
+            notes
+                .iter()
+                .map(|n| note_handler::short_id(&n.id).to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Opens the full-screen note browser.
+pub fn browse_notes(ctx: &AppContext) {
+    if let Err(e) = crate::tui::run(ctx) {
+        println!("{}", format!("❌ {e}").red());
+    }
+}
+
+fn print_interactive_help() {
+    println!("{}", "Interactive Mode Help:".bold().cyan());
     println!(
-        "• {} - View your profile and configuration",
-        "View Stats".green()
+        "Type anything without a leading {} to chat; earlier answers stay in context.",
+        "/".green()
     );
-    println!("• {} - Show this help message", "Help".green());
-    println!("• {} - Exit interactive mode", "Exit".green());
+    for (command, help) in COMMANDS.iter().filter(|(c, _)| *c != "/quit") {
+        match help.split_once("  ") {
+            Some((args, what)) => println!("• {} {} - {}", command.green(), args, what),
+            None => println!("• {} - {}", command.green(), help),
+        }
+    }
     println!();
     println!(
         "💡 {}",
-        "Tip: All your notes and conversations are automatically saved and used as context!"
+        "Tip: Tab completes commands and note ids; ↑/↓ recall earlier lines, even from past sessions."
             .yellow()
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line_commands_and_chat() {
+        assert_eq!(parse_line("   "), None);
+        assert_eq!(
+            parse_line("what did I ship?"),
+            Some(Command::Chat("what did I ship?".to_string()))
+        );
+        assert_eq!(
+            parse_line("/note  fixed the flaky test "),
+            Some(Command::Note("fixed the flaky test".to_string()))
+        );
+        assert_eq!(parse_line("/recent"), Some(Command::Recent(5)));
+        assert_eq!(parse_line("/recent 12"), Some(Command::Recent(12)));
+        assert_eq!(
+            parse_line("/recent lots"),
+            Some(Command::Usage("/recent [count]".to_string()))
+        );
+        assert_eq!(
+            parse_line("/search"),
+            Some(Command::Usage("/search <text>".to_string()))
+        );
+        assert_eq!(
+            parse_line("/goal link 2 3f2a9c1d"),
+            Some(Command::Goal(GoalCommand::Link {
+                goal: "2".to_string(),
+                note: "3f2a9c1d".to_string()
+            }))
+        );
+        assert_eq!(parse_line("/goal"), Some(Command::Goal(GoalCommand::List)));
+        assert_eq!(parse_line("/model"), Some(Command::Model(None)));
+        assert_eq!(
+            parse_line("/session save notes/today.md"),
+            Some(Command::Session(SessionCommand::Save(Some(PathBuf::from(
+                "notes/today.md"
+            )))))
+        );
+        assert_eq!(parse_line("/QUIT"), Some(Command::Exit));
+        assert_eq!(
+            parse_line("/deploy now"),
+            Some(Command::Unknown("/deploy".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_complete_commands_actions_and_note_ids() {
+        let ids = vec!["3f2a9c1d".to_string(), "3e11aa02".to_string()];
+        assert_eq!(
+            complete("/se", 3, &ids),
+            (0, vec!["/search".to_string(), "/session".to_string()])
+        );
+        assert_eq!(
+            complete("/goal l", 7, &ids),
+            (6, vec!["list".to_string(), "link".to_string()])
+        );
+        assert_eq!(
+            complete("/goal link 2 3f", 15, &ids),
+            (13, vec!["3f2a9c1d".to_string()])
+        );
+        assert_eq!(complete("/note 3f", 8, &ids), (6, Vec::<String>::new()));
+        assert_eq!(complete("hello /n", 8, &ids), (6, Vec::<String>::new()));
+    }
+
+    #[test]
+    fn test_transcript_keeps_thinking_only_when_asked() {
+        let session = Session {
+            started: Utc::now(),
+            turns: vec![ChatTurn {
+                user: "Any blockers?".to_string(),
+                assistant: "The CI runner quota.".to_string(),
+                thinking: vec!["Notes mention CI twice.".to_string()],
+            }],
+            notes_added: 0,
+        };
+        let plain = session.transcript(Zone::Named(chrono_tz::UTC), "qwen3", false);
+        assert!(plain.contains("**Assistant:** The CI runner quota."));
+        assert!(!plain.contains("Notes mention CI"));
+        let full = session.transcript(Zone::Named(chrono_tz::UTC), "qwen3", true);
+        assert!(full.contains("<summary>Thinking</summary>\n\nNotes mention CI twice."));
+    }
+}
@@ -4,33 +4,63 @@
 //!
 //! All configuration is stored in `~/.logswise/setup.json` after running the setup command.
 
+mod activity;
+mod adaptation;
+mod budget;
 mod chat_handler;
 mod cli;
+mod config;
+mod context;
+mod dates;
+mod doctor;
 mod enhanced_setup;
 mod errors;
+mod export;
+mod generation;
+mod goals;
 mod handlers;
+mod intent;
 mod interactive;
+mod logging;
+mod metrics;
+mod models;
 mod note_handler;
+mod outbox;
 mod performance;
 mod personalization;
+mod projects;
+mod prompts;
+mod report;
 mod router;
 mod setup;
+mod standup;
+mod stats;
 mod suggestion_handler;
+mod suggestion_log;
+mod thinking;
+mod tokens;
+mod tui;
 mod types;
+mod usage_log;
 mod utils;
 mod validation;
 mod services {
+    pub mod http;
     pub mod ollama;
     pub mod supabase;
 }
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use cli::Cli;
+use logging::Verbosity;
 use router::CommandRouter;
+use std::time::Instant;
+use usage_log::UsageLog;
 
+#[allow(clippy::collapsible_match)] // One block of checks per command reads better than guards
 fn validate_input(cli: &Cli) {
     match &cli.command {
-        cli::Commands::Note { content } | cli::Commands::N { content } => {
+        cli::Commands::Note { content, .. } | cli::Commands::N { content, .. } => {
             if content.trim().is_empty() {
                 eprintln!("❌ Note content cannot be empty");
                 std::process::exit(1);
@@ -40,13 +70,13 @@ fn validate_input(cli: &Cli) {
                 std::process::exit(1);
             }
         }
-        cli::Commands::Suggestion { query } | cli::Commands::S { query } => {
-            if query.trim().is_empty() {
+        cli::Commands::Suggestion(args) | cli::Commands::S(args) => {
+            if args.query.as_deref().is_some_and(|q| q.trim().is_empty()) {
                 eprintln!("❌ Query cannot be empty");
                 std::process::exit(1);
             }
         }
-        cli::Commands::Chat { message } | cli::Commands::C { message } => {
+        cli::Commands::Chat { message, .. } | cli::Commands::C { message, .. } => {
             if message.trim().is_empty() {
                 eprintln!("❌ Message cannot be empty");
                 std::process::exit(1);
@@ -63,12 +93,32 @@ fn validate_input(cli: &Cli) {
 }
 
 fn main() {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    logging::init(if cli.quiet {
+        Verbosity::Quiet
+    } else {
+        Verbosity::Verbose(cli.verbose)
+    });
 
     // Input validation
     validate_input(&cli);
 
+    // Remember when commands run so activity patterns can be learned; never fatal
+    if let (Some(command), Ok(log)) = (matches.subcommand_name(), UsageLog::open_default()) {
+        let _ = log.record(command);
+    }
+
     // Create router and handle command
+    let started = Instant::now();
     let router = CommandRouter::new();
+    log::debug!(
+        "Running {}",
+        matches.subcommand_name().unwrap_or("logswise-cli")
+    );
     router.route(cli.command);
+    log::debug!("Finished in {}ms", started.elapsed().as_millis());
+    if cli.timings {
+        performance::print_timings(started.elapsed());
+    }
 }
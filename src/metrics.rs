@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Measurements kept in the log; the oldest are dropped once it grows past this.
+const MAX_METRICS: usize = 5000;
+
+/// One timed call to a model or to note storage.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Metric {
+    pub at: DateTime<Utc>,
+    /// `generate`, `embed`, or `store.<call>` for note storage.
+    pub operation: String,
+    /// Model used; empty for storage calls.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub model: String,
+    pub latency_ms: u64,
+    /// Text sent and received, where known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+    /// Tokens in the prompt, as counted by Ollama (`prompt_eval_count`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens: Option<u64>,
+    /// Tokens generated, as counted by Ollama (`eval_count`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<u64>,
+    pub success: bool,
+}
+
+impl Metric {
+    /// A measurement taken just now, without sizes or token counts.
+    pub fn new(operation: &str, model: &str, latency: Duration, success: bool) -> Self {
+        Self {
+            at: Utc::now(),
+            operation: operation.to_string(),
+            model: model.to_string(),
+            latency_ms: latency.as_millis() as u64,
+            bytes: None,
+            prompt_tokens: None,
+            output_tokens: None,
+            success,
+        }
+    }
+}
+
+/// Rolling local log of model and storage calls in `~/.logswise/metrics.jsonl`, one per line.
+#[derive(Clone)]
+pub struct MetricsLog {
+    path: PathBuf,
+}
+
+impl MetricsLog {
+    /// Opens the log in the default location (`~/.logswise/metrics.jsonl`).
+    pub fn open_default() -> Result<Self, String> {
+        let mut path = home_dir().ok_or("Could not determine home directory")?;
+        path.push(".logswise/metrics.jsonl");
+        Ok(Self::at(path))
+    }
+
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Appends a measurement, dropping the oldest ones beyond the retention limit.
+    pub fn record(&self, metric: &Metric) -> Result<(), String> {
+        let existing = self.all()?;
+        if existing.len() >= MAX_METRICS {
+            let mut kept = existing[existing.len() + 1 - MAX_METRICS..].to_vec();
+            kept.push(metric.clone());
+            return self.rewrite(&kept);
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+        }
+        let line =
+            serde_json::to_string(metric).map_err(|e| format!("Failed to encode metric: {e}"))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open metrics log: {e}"))?;
+        writeln!(file, "{line}").map_err(|e| format!("Failed to write metrics log: {e}"))
+    }
+
+    /// All recorded measurements, oldest first.
+    pub fn all(&self) -> Result<Vec<Metric>, String> {
+        let data = match fs::read_to_string(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(format!("Failed to read metrics log: {e}")),
+        };
+        Ok(data
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .collect())
+    }
+
+    fn rewrite(&self, metrics: &[Metric]) -> Result<(), String> {
+        let mut data = String::new();
+        for metric in metrics {
+            let line = serde_json::to_string(metric)
+                .map_err(|e| format!("Failed to encode metric: {e}"))?;
+            data.push_str(&line);
+            data.push('\n');
+        }
+        let tmp = self.path.with_extension("jsonl.tmp");
+        fs::write(&tmp, data).map_err(|e| format!("Failed to write metrics log: {e}"))?;
+        fs::rename(&tmp, &self.path).map_err(|e| format!("Failed to write metrics log: {e}"))
+    }
+}
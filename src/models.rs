@@ -0,0 +1,202 @@
+use crate::services::http::HttpClient;
+use crate::services::ollama::{self, looks_like_embedding_model, ModelInfo};
+use chrono::{DateTime, Duration, Utc};
+use dirs::home_dir;
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How long discovered model metadata is trusted before Ollama is asked again.
+const CACHE_TTL_HOURS: i64 = 24;
+
+/// Models installed on an Ollama server, cached in `~/.logswise/cache/models.json`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelCatalog {
+    pub base_url: String,
+    pub fetched_at: Option<DateTime<Utc>>,
+    pub models: Vec<ModelInfo>,
+}
+
+impl ModelCatalog {
+    fn cache_path() -> Result<PathBuf, String> {
+        let mut path = home_dir().ok_or("Could not determine home directory")?;
+        path.push(".logswise/cache/models.json");
+        Ok(path)
+    }
+
+    /// Returns the cached catalog for `base_url`, refreshing it when missing or stale.
+    ///
+    /// Falls back to a stale cache, then to an empty catalog, when Ollama is unreachable.
+    pub fn load(client: &HttpClient, base_url: &str) -> Self {
+        let cached = Self::cache_path()
+            .ok()
+            .and_then(|p| Self::read(&p))
+            .filter(|c| c.base_url == base_url);
+        if let Some(cached) = &cached {
+            if cached.is_fresh(Utc::now()) {
+                return cached.clone();
+            }
+        }
+        match Self::refresh(client, base_url) {
+            Ok(catalog) => catalog,
+            Err(_) => cached.unwrap_or_else(|| Self {
+                base_url: base_url.to_string(),
+                ..Self::default()
+            }),
+        }
+    }
+
+    /// Queries Ollama for installed models and their capabilities and updates the cache.
+    pub fn refresh(client: &HttpClient, base_url: &str) -> Result<Self, String> {
+        let models = ollama::list_models(client, base_url)?
+            .into_iter()
+            .map(|m| ollama::show_model(client, base_url, &m).unwrap_or(m))
+            .collect();
+        let catalog = Self {
+            base_url: base_url.to_string(),
+            fetched_at: Some(Utc::now()),
+            models,
+        };
+        if let Ok(path) = Self::cache_path() {
+            // A failed cache write only costs a refresh next time
+            let _ = catalog.write(&path);
+        }
+        Ok(catalog)
+    }
+
+    fn read(path: &Path) -> Option<Self> {
+        let data = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&data).ok()
+    }
+
+    fn write(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+        }
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to encode model cache: {e}"))?;
+        fs::write(path, data).map_err(|e| format!("Failed to write model cache: {e}"))
+    }
+
+    fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        self.fetched_at
+            .is_some_and(|t| now - t < Duration::hours(CACHE_TTL_HOURS))
+    }
+
+    pub fn find(&self, name: &str) -> Option<&ModelInfo> {
+        self.models.iter().find(|m| m.matches(name))
+    }
+
+    /// Models that can generate text, for chat and suggestions.
+    pub fn generation_models(&self) -> Vec<&ModelInfo> {
+        self.models
+            .iter()
+            .filter(|m| m.supports_generation())
+            .collect()
+    }
+
+    /// Models that can produce embeddings, for semantic search.
+    pub fn embedding_models(&self) -> Vec<&ModelInfo> {
+        self.models
+            .iter()
+            .filter(|m| m.supports_embedding())
+            .collect()
+    }
+
+    /// True when `name` can only produce embeddings, so chat and suggestions fall back
+    /// to semantic search. Unknown models are judged by name.
+    pub fn is_embedding_only(&self, name: &str) -> bool {
+        match self.find(name) {
+            Some(info) => info.supports_embedding() && !info.supports_generation(),
+            None => looks_like_embedding_model(name),
+        }
+    }
+}
+
+/// Downloads `model` from the Ollama library, drawing a progress bar on stderr when
+/// `show_progress` is set, and refreshes the cached catalog afterwards.
+pub fn pull(
+    client: &HttpClient,
+    base_url: &str,
+    model: &str,
+    show_progress: bool,
+) -> Result<(), String> {
+    let bar = if show_progress {
+        ProgressBar::new(0)
+    } else {
+        ProgressBar::hidden()
+    };
+    bar.set_style(
+        ProgressStyle::default_bar()
+            .template(
+                "{msg:24} [{bar:30.cyan/blue}] {bytes}/{total_bytes} {bytes_per_sec} eta {eta}",
+            )
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    let result = ollama::pull_model(client, base_url, model, |progress| {
+        bar.set_message(progress.status.clone());
+        match (progress.total, progress.completed) {
+            (Some(total), completed) => {
+                bar.set_length(total);
+                bar.set_position(completed.unwrap_or(0));
+            }
+            (None, _) => bar.tick(),
+        }
+    });
+    bar.finish_and_clear();
+    result?;
+    // The new model should show up in pickers right away
+    let _ = ModelCatalog::refresh(client, base_url);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(name: &str, capabilities: &[&str]) -> ModelInfo {
+        ModelInfo {
+            name: name.to_string(),
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            ..ModelInfo::default()
+        }
+    }
+
+    #[test]
+    fn test_embedding_only_uses_capabilities() {
+        let catalog = ModelCatalog {
+            models: vec![
+                model("llama3:latest", &["completion"]),
+                model("mxbai-large:latest", &["embedding"]),
+            ],
+            ..ModelCatalog::default()
+        };
+        assert!(!catalog.is_embedding_only("llama3"));
+        assert!(catalog.is_embedding_only("mxbai-large"));
+        // Not installed: fall back to the name
+        assert!(catalog.is_embedding_only("nomic-embed-text"));
+        assert!(!catalog.is_embedding_only("mistral"));
+        assert_eq!(catalog.generation_models().len(), 1);
+    }
+
+    #[test]
+    fn test_cache_round_trip_and_freshness() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache/models.json");
+        let now = Utc::now();
+        let catalog = ModelCatalog {
+            base_url: "http://localhost:11434".to_string(),
+            fetched_at: Some(now),
+            models: vec![model("llama3:latest", &["completion"])],
+        };
+        catalog.write(&path).unwrap();
+
+        let cached = ModelCatalog::read(&path).unwrap();
+        assert_eq!(cached.models, catalog.models);
+        assert!(cached.is_fresh(now + Duration::hours(1)));
+        assert!(!cached.is_fresh(now + Duration::hours(CACHE_TTL_HOURS + 1)));
+    }
+}
@@ -1,17 +1,33 @@
-use crate::services::ollama::generate_embedding;
-use crate::utils::load_supabase_config;
+use crate::context::AppContext;
+use crate::dates::{self, Bound};
+use crate::personalization::UserContext;
+use crate::services::supabase::{self, NoteQuery};
+use crate::types::Note;
+use crate::utils::spinner;
+use chrono::Utc;
 use colored::*;
-use indicatif::{ProgressBar, ProgressStyle};
-use reqwest::blocking::Client;
-use serde_json::json;
-use std::time::Duration;
+use uuid::Uuid;
 
-/// Adds a note to the Supabase database.
-pub fn add_note(content: &str) {
+/// Where a newly captured note ended up.
+#[derive(Debug)]
+pub enum SaveOutcome {
+    /// Written to Supabase.
+    Saved,
+    /// Written to Supabase without its project tag, because the notes table has no
+    /// `project` column yet.
+    SavedWithoutProject,
+    /// Supabase was unreachable; the note waits in the local outbox.
+    Queued { pending: usize, error: String },
+}
+
+/// Adds a note to the Supabase database, queueing it locally if Supabase is unreachable.
+/// With `goal`, the note is also linked to that goal. Returns whether the note was saved
+/// or queued.
+pub fn add_note(ctx: &AppContext, content: &str, goal: Option<&str>) -> bool {
     // Input validation
     if content.trim().is_empty() {
         println!("{}", "❌ Note content cannot be empty".red());
-        return;
+        return false;
     }
 
     if content.len() > 10000 {
@@ -19,49 +35,23 @@ pub fn add_note(content: &str) {
             "{}",
             "❌ Note content too long (max 10,000 characters)".red()
         );
-        return;
+        return false;
     }
 
-    let config = match load_supabase_config() {
-        Ok(cfg) => cfg,
-        Err(e) => {
-            println!("{}", format!("Error loading Supabase config: {e}").red());
-            println!("Please run 'logswise-cli setup' first.");
-            return;
-        }
-    };
-
-    // Load profile for Ollama configuration
-    let profile = match crate::utils::load_profile() {
-        Ok(p) => p,
-        Err(e) => {
-            println!("{}", format!("Error loading profile: {e}").red());
-            println!("Please run 'logswise-cli setup' first.");
-            return;
+    // Resolve the goal first so a typo doesn't leave an unlinked note behind
+    let mut user_context = None;
+    if let Some(key) = goal {
+        let context = UserContext::load_for(ctx.config.clone());
+        if let Err(e) = context.goal_index(key) {
+            println!("{}", format!("❌ {e}").red());
+            return false;
         }
-    };
+        user_context = Some((context, key));
+    }
 
-    let spinner = ProgressBar::new_spinner();
-    spinner.set_style(
-        ProgressStyle::default_spinner()
-            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    spinner.enable_steady_tick(Duration::from_millis(100));
-
-    let client = Client::new();
-
-    // 1. Generate embedding for the note content using shared Ollama service
-    spinner.set_message("Generating embedding for note...");
-    let ollama_base_url = profile["ollamaBaseUrl"]
-        .as_str()
-        .unwrap_or("http://localhost:11434");
-    let ollama_url = format!("{ollama_base_url}/api/embeddings");
-    let ollama_model = profile["embeddingModel"]
-        .as_str()
-        .unwrap_or("nomic-embed-text");
-    let embedding_vec = match generate_embedding(&client, &ollama_url, ollama_model, content) {
+    // 1. Generate embedding for the note content
+    let spinner = spinner("Generating embedding for note...");
+    let embedding_vec = match ctx.llm.embed(content) {
         Ok(embedding) => Some(embedding),
         Err(msg) => {
             spinner.finish_and_clear();
@@ -74,133 +64,310 @@ pub fn add_note(content: &str) {
         }
     };
 
-    // 2. Store note and embedding in Supabase
+    // 2. Store note and embedding in Supabase, falling back to the local outbox
     spinner.set_message("Saving note to Supabase...");
-    let url = format!("{}/rest/v1/notes", config.project_url);
-    let body = if let Some(embedding) = embedding_vec {
-        json!({ "content": content, "embedding": embedding })
-    } else {
-        json!({ "content": content })
-    };
-    let res = client
-        .post(&url)
-        .header("apikey", &config.api_key)
-        .header("Authorization", format!("Bearer {}", &config.api_key))
-        .header("Content-Type", "application/json")
-        .json(&body)
-        .send();
-    match res {
-        Ok(resp) if resp.status().is_success() => {
-            spinner.finish_and_clear();
+    let mut note = new_note(content, embedding_vec);
+    note.project = ctx.active_project().map(str::to_string);
+    let result = store_note(ctx, &note);
+    spinner.finish_and_clear();
+    match result {
+        Ok(SaveOutcome::Saved) => {
             println!("{}", "📝 Note added successfully!".green());
         }
-        Ok(resp) => {
-            spinner.finish_and_clear();
-            let error_text = resp.text().unwrap_or_default();
-            println!("{} {}", "❌ Failed to add note:".red(), error_text);
+        Ok(SaveOutcome::SavedWithoutProject) => {
+            println!("{}", "📝 Note added successfully!".green());
             println!(
                 "{}",
-                "Check your Supabase configuration and try again.".yellow()
+                "⚠️  Your notes table has no project column, so the note was saved without its project tag."
+                    .yellow()
+            );
+            println!(
+                "Run this in the Supabase SQL Editor to enable project tags: {}",
+                supabase::PROJECT_COLUMN_SQL.cyan()
             );
         }
-        Err(e) => {
-            spinner.finish_and_clear();
-            println!("{} {}", "❌ Failed to add note:".red(), e);
+        Ok(SaveOutcome::Queued { pending, error }) => {
+            println!("{} {}", "⚠️  Could not reach Supabase:".yellow(), error);
             println!(
                 "{}",
-                "Check your internet connection and Supabase configuration.".yellow()
+                format!(
+                    "📥 Note saved to the local outbox ({pending} pending). It will sync automatically, or run 'logswise-cli sync'."
+                )
+                .cyan()
             );
         }
+        Err(e) => {
+            println!("{} {}", "❌ Failed to add note:".red(), e);
+            return false;
+        }
+    }
+
+    if let Some((mut context, key)) = user_context {
+        let result = context.goal_mut(key).map(|goal| {
+            goal.link_note(&note.id);
+            goal.description.clone()
+        });
+        match result.and_then(|description| context.save().map(|_| description)) {
+            Ok(description) => println!("{}", format!("🎯 Linked to goal: {description}").green()),
+            Err(e) => println!(
+                "{}",
+                format!("⚠️  Could not link note to goal: {e}").yellow()
+            ),
+        }
+    }
+    true
+}
+
+/// A new note with a fresh client-generated id.
+pub fn new_note(content: &str, embedding: Option<Vec<f32>>) -> Note {
+    Note {
+        id: Uuid::new_v4().to_string(),
+        content: content.to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        embedding,
+        project: None,
+    }
+}
+
+/// Stores `note`, falling back to the outbox when Supabase cannot be reached. Errors
+/// Supabase returns for the note itself are passed on, since retrying would not help.
+pub fn store_note(ctx: &AppContext, note: &Note) -> Result<SaveOutcome, String> {
+    match supabase::insert_note_untagged_if_needed(ctx.store.as_ref(), note) {
+        Ok(false) => Ok(SaveOutcome::Saved),
+        Ok(true) => Ok(SaveOutcome::SavedWithoutProject),
+        Err(error) if error.is_rejection() => Err(error.message),
+        Err(error) => {
+            ctx.outbox.push(note)?;
+            Ok(SaveOutcome::Queued {
+                pending: ctx.outbox.len(),
+                error: error.message,
+            })
+        }
+    }
+}
+
+/// Sends notes queued in the local outbox to Supabase.
+///
+/// With `verbose` unset nothing is printed unless notes were actually synced, and that
+/// goes to stderr, so this can run before any command that talks to Supabase without
+/// mixing into output meant for other programs (`--json`, `export`).
+pub fn sync_outbox(ctx: &AppContext, verbose: bool) {
+    let notice = |message: ColoredString| {
+        if verbose {
+            println!("{message}");
+        } else {
+            eprintln!("{message}");
+        }
+    };
+    if ctx.outbox.len() == 0 {
+        if verbose {
+            println!("{}", "✅ Outbox is empty, nothing to sync.".green());
+        }
+        return;
+    }
+
+    match ctx.outbox.flush(ctx.store.as_ref()) {
+        Ok(report) => {
+            if report.sent > 0 {
+                notice(format!("☁️  Synced {} queued note(s) to Supabase.", report.sent).green());
+            }
+            if report.rejected > 0 {
+                notice(
+                    format!(
+                        "⚠️  Supabase refused {} queued note(s); they were moved to {}",
+                        report.rejected,
+                        ctx.outbox.rejected_path().display()
+                    )
+                    .yellow(),
+                );
+            }
+            if verbose && report.remaining > 0 {
+                if let Some(e) = report.last_error {
+                    println!(
+                        "{}",
+                        format!("⚠️  {} note(s) still pending: {e}", report.remaining).yellow()
+                    );
+                }
+            }
+        }
+        Err(e) => {
+            if verbose {
+                println!("{}", format!("❌ Sync failed: {e}").red());
+            }
+        }
+    }
+}
+
+/// Project and date filters shared by `recent`, `search` and `export`.
+#[derive(Default)]
+pub struct NoteFilters {
+    /// Include notes from every project, not just the active one.
+    pub all: bool,
+    /// Natural date expressions, see [`dates::parse_when`].
+    pub since: Option<String>,
+    pub until: Option<String>,
+}
+
+impl NoteFilters {
+    /// Resolves the filters into a store query with no limit set.
+    pub fn query<'a>(&self, ctx: &'a AppContext) -> Result<NoteQuery<'a>, String> {
+        let zone = ctx.zone();
+        let now = Utc::now();
+        let parse = |expr: &Option<String>, bound| {
+            expr.as_deref()
+                .map(|e| dates::parse_when(e, zone, now, bound))
+                .transpose()
+        };
+        let since = parse(&self.since, Bound::Start)?;
+        let until = parse(&self.until, Bound::End)?;
+        if let (Some(since), Some(until)) = (since, until) {
+            if since >= until {
+                return Err("--since must be before --until".to_string());
+            }
+        }
+        Ok(NoteQuery {
+            project: ctx.active_project().filter(|_| !self.all),
+            since,
+            until,
+            ..NoteQuery::default()
+        })
+    }
+
+    /// ` since yesterday until 2026-09-01`, as given on the command line.
+    fn describe(&self) -> String {
+        let mut text = String::new();
+        if let Some(since) = &self.since {
+            text.push_str(&format!(" since {since}"));
+        }
+        if let Some(until) = &self.until {
+            text.push_str(&format!(" until {until}"));
+        }
+        text
     }
 }
 
-/// Shows recent notes from Supabase
-pub fn show_recent_notes(count: usize) {
-    let config = match load_supabase_config() {
-        Ok(cfg) => cfg,
+/// Shows the newest notes, optionally within a date range.
+pub fn show_recent_notes(ctx: &AppContext, count: usize, filters: &NoteFilters) {
+    let query = match filters.query(ctx) {
+        Ok(query) => NoteQuery {
+            limit: count,
+            ..query
+        },
         Err(e) => {
-            println!("{}", format!("Error loading Supabase config: {e}").red());
-            println!("Please run 'logswise-cli setup' first.");
+            println!("{}", format!("❌ {e}").red());
             return;
         }
     };
+    let spinner = spinner("Fetching recent notes...");
+    let result = ctx.store.find_notes(&query);
+    spinner.finish_and_clear();
 
-    let client = Client::new();
-    let url = format!("{}/rest/v1/notes", config.project_url);
-
-    let spinner = ProgressBar::new_spinner();
-    spinner.set_style(
-        ProgressStyle::default_spinner()
-            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    spinner.enable_steady_tick(Duration::from_millis(100));
-    spinner.set_message("Fetching recent notes...");
-
-    let response = client
-        .get(&url)
-        .header("apikey", &config.api_key)
-        .header("Authorization", format!("Bearer {}", config.api_key))
-        .query(&[
-            ("select", "content,created_at"),
-            ("order", "created_at.desc"),
-            ("limit", &count.to_string()),
-        ])
-        .send();
-
-    match response {
-        Ok(resp) => {
-            spinner.finish_and_clear();
-            if resp.status().is_success() {
-                match resp.json::<Vec<serde_json::Value>>() {
-                    Ok(notes) => {
-                        if notes.is_empty() {
-                            println!(
-                                "📝 No notes found. Add your first note with: {}",
-                                "logswise-cli note 'Your note here'".cyan()
-                            );
-                        } else {
-                            println!("📝 {} most recent notes:\n", notes.len().to_string().cyan());
-                            for (i, note) in notes.iter().enumerate() {
-                                let content = note["content"].as_str().unwrap_or("(empty)");
-                                let created_at =
-                                    note["created_at"].as_str().unwrap_or("unknown time");
-
-                                // Format the timestamp (simplified)
-                                let formatted_time =
-                                    created_at.split('T').next().unwrap_or(created_at);
-
-                                println!(
-                                    "{}. {} {}",
-                                    (i + 1).to_string().green(),
-                                    content,
-                                    format!("({formatted_time})").bright_black()
-                                );
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        println!("{}", format!("Error parsing notes: {e}").red());
-                    }
+    match result {
+        Ok(notes) => {
+            if notes.is_empty() {
+                if filters.since.is_some() || filters.until.is_some() {
+                    println!("📝 No notes{}.", filters.describe());
+                } else {
+                    println!(
+                        "📝 No notes found. Add your first note with: {}",
+                        "logswise-cli note 'Your note here'".cyan()
+                    );
                 }
             } else {
-                println!(
-                    "{}",
-                    format!("Error fetching notes: HTTP {}", resp.status()).red()
-                );
+                match query.project {
+                    Some(project) => println!(
+                        "📝 {} most recent notes{} in project {} (use --all for every project):\n",
+                        notes.len().to_string().cyan(),
+                        filters.describe(),
+                        project.cyan()
+                    ),
+                    None => println!(
+                        "📝 {} most recent notes{}:\n",
+                        notes.len().to_string().cyan(),
+                        filters.describe()
+                    ),
+                }
+                print_notes(ctx, &notes);
             }
         }
         Err(e) => {
-            spinner.finish_and_clear();
-            println!("{}", format!("Network error: {e}").red());
+            println!("{}", e.red());
         }
     }
 }
 
+/// Lists notes whose content contains `text`, newest first.
+pub fn search_notes(ctx: &AppContext, text: &str, count: usize, filters: &NoteFilters) {
+    if text.trim().is_empty() {
+        println!("{}", "❌ Search text cannot be empty".red());
+        return;
+    }
+    let query = match filters.query(ctx) {
+        Ok(query) => NoteQuery {
+            text: Some(text.trim()),
+            limit: count,
+            ..query
+        },
+        Err(e) => {
+            println!("{}", format!("❌ {e}").red());
+            return;
+        }
+    };
+    let spinner = spinner("Searching notes...");
+    let result = ctx.store.find_notes(&query);
+    spinner.finish_and_clear();
+
+    match result {
+        Ok(notes) if notes.is_empty() => {
+            println!(
+                "🔍 No notes containing '{}'{}.",
+                text.trim(),
+                filters.describe()
+            );
+            if query.project.is_some() {
+                println!("   Use --all to search every project.");
+            }
+        }
+        Ok(notes) => {
+            println!(
+                "🔍 {} note(s) containing '{}'{}:\n",
+                notes.len().to_string().cyan(),
+                text.trim(),
+                filters.describe()
+            );
+            print_notes(ctx, &notes);
+        }
+        Err(e) => println!("{}", e.red()),
+    }
+}
+
+fn print_notes(ctx: &AppContext, notes: &[Note]) {
+    let zone = ctx.zone();
+    let now = Utc::now();
+    for (i, note) in notes.iter().enumerate() {
+        println!(
+            "{}. {} {}",
+            (i + 1).to_string().green(),
+            note.content,
+            format!(
+                "({}, id {})",
+                zone.describe(&note.created_at, now),
+                short_id(&note.id)
+            )
+            .bright_black()
+        );
+    }
+}
+
+/// First block of a note's UUID, enough to refer to it in commands like `goal link`.
+pub fn short_id(id: &str) -> &str {
+    id.split('-').next().unwrap_or(id)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::types::Note;
+    use super::*;
+    use crate::context::testing::{context, fake_llm, FakeStore};
 
     #[test]
     fn test_add_note_and_retrieve() {
@@ -210,6 +377,7 @@ mod tests {
             content: "Integration test note".to_string(),
             created_at: "2025-06-05T12:00:00Z".to_string(),
             embedding: None,
+            project: None,
         };
         assert_eq!(note.content, "Integration test note");
     }
@@ -222,4 +390,117 @@ mod tests {
         let prompt = format!("{user_info}\n\nUser wants suggestions for: {query}\nSuggestions:");
         assert!(prompt.contains("User wants suggestions for: How to improve logging?"));
     }
+
+    #[test]
+    fn test_save_note_queues_when_store_is_offline() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FakeStore {
+            offline: true,
+            ..Default::default()
+        };
+        let ctx = context(fake_llm("llama3", ""), store, dir.path());
+
+        let outcome = store_note(&ctx, &new_note("Deployed v2", Some(vec![0.1]))).unwrap();
+        assert!(matches!(outcome, SaveOutcome::Queued { pending: 1, .. }));
+        let queued = ctx.outbox.pending().unwrap();
+        assert_eq!(queued[0].content, "Deployed v2");
+        assert_eq!(queued[0].embedding, Some(vec![0.1]));
+    }
+
+    #[test]
+    fn test_save_note_reports_rejections_without_queueing() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FakeStore {
+            rejecting: true,
+            ..Default::default()
+        };
+        let ctx = context(fake_llm("llama3", ""), store, dir.path());
+
+        let error = store_note(&ctx, &new_note("Deployed v2", None)).unwrap_err();
+        assert!(error.starts_with("HTTP 400"));
+        assert_eq!(ctx.outbox.len(), 0);
+    }
+
+    #[test]
+    fn test_save_note_drops_project_on_old_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FakeStore {
+            without_project_column: true,
+            ..Default::default()
+        };
+        let notes = store.notes.clone();
+        let ctx = context(fake_llm("llama3", ""), store, dir.path());
+
+        let mut note = new_note("Planned the Atlas launch", None);
+        note.project = Some("Atlas".to_string());
+        let outcome = store_note(&ctx, &note).unwrap();
+        assert!(matches!(outcome, SaveOutcome::SavedWithoutProject));
+        assert_eq!(notes.borrow()[0].project, None);
+        assert_eq!(ctx.outbox.len(), 0);
+    }
+
+    #[test]
+    fn test_save_note_writes_to_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FakeStore::default();
+        let notes = store.notes.clone();
+        let ctx = context(fake_llm("llama3", ""), store, dir.path());
+
+        let outcome = store_note(&ctx, &new_note("Fixed flaky test", None)).unwrap();
+        assert!(matches!(outcome, SaveOutcome::Saved));
+        assert_eq!(notes.borrow().len(), 1);
+        assert_eq!(ctx.outbox.len(), 0);
+    }
+
+    #[test]
+    fn test_note_filters_build_store_query() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FakeStore::default();
+        let mut old = new_note("Old deploy", None);
+        old.created_at = "2020-01-01T09:00:00Z".to_string();
+        let mut recent = new_note("Deploy #42 went fine", None);
+        recent.project = Some("Atlas".to_string());
+        store.notes.borrow_mut().extend([old, recent]);
+        let mut ctx = context(fake_llm("llama3", ""), store, dir.path());
+        ctx.config.active_project = "Atlas".to_string();
+
+        let filters = NoteFilters {
+            since: Some("2 weeks ago".to_string()),
+            ..NoteFilters::default()
+        };
+        let query = filters.query(&ctx).unwrap();
+        assert_eq!(query.project, Some("Atlas"));
+        assert!(query.since.is_some() && query.until.is_none());
+        let found = ctx
+            .store
+            .find_notes(&NoteQuery {
+                text: Some("DEPLOY"),
+                limit: 10,
+                ..query
+            })
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].content, "Deploy #42 went fine");
+
+        let all = NoteFilters {
+            all: true,
+            until: Some("2020-01-01".to_string()),
+            ..NoteFilters::default()
+        };
+        let query = all.query(&ctx).unwrap();
+        assert_eq!(query.project, None);
+        let found = ctx
+            .store
+            .find_notes(&NoteQuery { limit: 10, ..query })
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].content, "Old deploy");
+
+        let backwards = NoteFilters {
+            since: Some("today".to_string()),
+            until: Some("yesterday".to_string()),
+            ..NoteFilters::default()
+        };
+        assert!(backwards.query(&ctx).is_err());
+    }
 }
@@ -0,0 +1,239 @@
+use crate::services::supabase::{self, NoteStore, StoreError};
+use crate::types::Note;
+use dirs::home_dir;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Durable local queue of notes that could not be written to Supabase.
+///
+/// Each line of `~/.logswise/outbox.jsonl` holds one serialized [`Note`]. Notes keep the
+/// id generated at capture time, so replaying the outbox never creates duplicates.
+pub struct Outbox {
+    path: PathBuf,
+}
+
+/// Outcome of an attempt to flush the outbox.
+#[derive(Debug, Default)]
+pub struct FlushReport {
+    pub sent: usize,
+    pub remaining: usize,
+    /// Notes Supabase refused, moved to [`Outbox::rejected_path`].
+    pub rejected: usize,
+    pub last_error: Option<String>,
+}
+
+impl Outbox {
+    /// Opens the outbox in the default location (`~/.logswise/outbox.jsonl`).
+    pub fn open_default() -> Result<Self, String> {
+        let mut path = home_dir().ok_or("Could not determine home directory")?;
+        path.push(".logswise/outbox.jsonl");
+        Ok(Self::at(path))
+    }
+
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Appends a note to the outbox unless a note with the same id is already queued.
+    pub fn push(&self, note: &Note) -> Result<(), String> {
+        if self.pending()?.iter().any(|n| n.id == note.id) {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+        }
+        let line =
+            serde_json::to_string(note).map_err(|e| format!("Failed to encode note: {e}"))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open outbox: {e}"))?;
+        writeln!(file, "{line}").map_err(|e| format!("Failed to write outbox: {e}"))?;
+        file.sync_all()
+            .map_err(|e| format!("Failed to write outbox: {e}"))
+    }
+
+    /// Returns all queued notes in the order they were captured.
+    pub fn pending(&self) -> Result<Vec<Note>, String> {
+        let data = match fs::read_to_string(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(format!("Failed to read outbox: {e}")),
+        };
+        // Skip lines that fail to parse (e.g. a torn write) instead of blocking the queue
+        Ok(data
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .collect())
+    }
+
+    /// Where notes Supabase refused are set aside, so they stop blocking the queue but
+    /// are not lost.
+    pub fn rejected_path(&self) -> PathBuf {
+        self.path.with_file_name("outbox-rejected.jsonl")
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending().map(|p| p.len()).unwrap_or(0)
+    }
+
+    /// Replaces the outbox contents, removing the file when nothing is left.
+    fn rewrite(&self, notes: &[Note]) -> Result<(), String> {
+        if notes.is_empty() {
+            return match fs::remove_file(&self.path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(format!("Failed to clear outbox: {e}"))
+                }
+                _ => Ok(()),
+            };
+        }
+        let mut data = String::new();
+        for note in notes {
+            let line =
+                serde_json::to_string(note).map_err(|e| format!("Failed to encode note: {e}"))?;
+            data.push_str(&line);
+            data.push('\n');
+        }
+        let tmp = self.path.with_extension("jsonl.tmp");
+        fs::write(&tmp, data).map_err(|e| format!("Failed to write outbox: {e}"))?;
+        fs::rename(&tmp, &self.path).map_err(|e| format!("Failed to write outbox: {e}"))
+    }
+
+    /// Sends queued notes in order using `send`, stopping at the first failure that may
+    /// go away (network, rate limit, server error, authentication or permissions) so
+    /// that ordering is preserved. Notes Supabase refuses as invalid are set aside
+    /// instead of blocking the rest. Sent and set-aside notes are removed from the outbox.
+    pub fn flush_with<F>(&self, mut send: F) -> Result<FlushReport, String>
+    where
+        F: FnMut(&Note) -> Result<(), StoreError>,
+    {
+        let pending = self.pending()?;
+        let mut report = FlushReport::default();
+        for (i, note) in pending.iter().enumerate() {
+            match send(note) {
+                Ok(()) => report.sent += 1,
+                Err(e) if e.is_rejection() => {
+                    self.set_aside(note)?;
+                    report.rejected += 1;
+                    report.last_error = Some(e.message);
+                }
+                Err(e) => {
+                    report.last_error = Some(e.message);
+                    report.remaining = pending.len() - i;
+                    self.rewrite(&pending[i..])?;
+                    return Ok(report);
+                }
+            }
+        }
+        self.rewrite(&[])?;
+        Ok(report)
+    }
+
+    fn set_aside(&self, note: &Note) -> Result<(), String> {
+        let line =
+            serde_json::to_string(note).map_err(|e| format!("Failed to encode note: {e}"))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.rejected_path())
+            .map_err(|e| format!("Failed to set aside rejected note: {e}"))?;
+        writeln!(file, "{line}").map_err(|e| format!("Failed to set aside rejected note: {e}"))
+    }
+
+    /// Flushes queued notes to the note store.
+    pub fn flush(&self, store: &dyn NoteStore) -> Result<FlushReport, String> {
+        self.flush_with(|note| supabase::insert_note_untagged_if_needed(store, note).map(|_| ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(id: &str) -> Note {
+        Note {
+            id: id.to_string(),
+            content: format!("note {id}"),
+            created_at: "2026-10-01T09:00:00Z".to_string(),
+            embedding: Some(vec![0.1, 0.2]),
+            project: None,
+        }
+    }
+
+    #[test]
+    fn test_push_deduplicates_by_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let outbox = Outbox::at(dir.path().join("outbox.jsonl"));
+        outbox.push(&note("a")).unwrap();
+        outbox.push(&note("a")).unwrap();
+        outbox.push(&note("b")).unwrap();
+        let pending = outbox.pending().unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].embedding, Some(vec![0.1, 0.2]));
+    }
+
+    #[test]
+    fn test_flush_stops_at_first_failure_and_keeps_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let outbox = Outbox::at(dir.path().join("outbox.jsonl"));
+        for id in ["a", "b", "c"] {
+            outbox.push(&note(id)).unwrap();
+        }
+
+        let report = outbox
+            .flush_with(|n| {
+                if n.id == "b" {
+                    Err(StoreError {
+                        status: None,
+                        message: "offline".to_string(),
+                    })
+                } else {
+                    Ok(())
+                }
+            })
+            .unwrap();
+        assert_eq!(report.sent, 1);
+        assert_eq!(report.remaining, 2);
+        let ids: Vec<String> = outbox
+            .pending()
+            .unwrap()
+            .into_iter()
+            .map(|n| n.id)
+            .collect();
+        assert_eq!(ids, vec!["b", "c"]);
+
+        let report = outbox.flush_with(|_| Ok(())).unwrap();
+        assert_eq!(report.sent, 2);
+        assert_eq!(outbox.len(), 0);
+        assert!(!dir.path().join("outbox.jsonl").exists());
+    }
+
+    #[test]
+    fn test_flush_sets_aside_rejected_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let outbox = Outbox::at(dir.path().join("outbox.jsonl"));
+        for id in ["a", "b", "c"] {
+            outbox.push(&note(id)).unwrap();
+        }
+
+        let error = |status: u16| StoreError {
+            status: Some(status),
+            message: format!("HTTP {status}"),
+        };
+        let report = outbox
+            .flush_with(|n| match n.id.as_str() {
+                "a" => Err(error(400)),
+                "c" => Err(error(401)),
+                _ => Ok(()),
+            })
+            .unwrap();
+        assert_eq!((report.sent, report.rejected, report.remaining), (1, 1, 1));
+        let rejected = fs::read_to_string(outbox.rejected_path()).unwrap();
+        assert!(rejected.contains("\"id\":\"a\""));
+        // An expired key affects every note, so the rest stay queued
+        assert_eq!(outbox.pending().unwrap()[0].id, "c");
+    }
+}
@@ -1,13 +1,27 @@
+use colored::*;
+use std::cell::RefCell;
+use std::cmp::Reverse;
 use std::time::{Duration, Instant};
 
-/// Simple performance timer for operations
-#[allow(dead_code)] // Will be used in future performance improvements
+thread_local! {
+    /// Operations finished in this process, for `--timings`.
+    static SPANS: RefCell<Vec<Span>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A finished, timed operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub operation: String,
+    pub elapsed: Duration,
+    pub success: bool,
+}
+
+/// Times one operation. Finished timers are remembered for the `--timings` breakdown.
 pub struct Timer {
     start: Instant,
     operation: String,
 }
 
-#[allow(dead_code)] // Will be used in future performance improvements
 impl Timer {
     pub fn new(operation: &str) -> Self {
         Self {
@@ -20,23 +34,94 @@ impl Timer {
         self.start.elapsed()
     }
 
-    pub fn finish(self) -> Duration {
+    /// Stops the timer and remembers how long the operation took and whether it worked.
+    pub fn finish(self, success: bool) -> Duration {
         let elapsed = self.elapsed();
-        if elapsed > Duration::from_secs(5) {
-            println!("⏱️  {} took {:.2}s", self.operation, elapsed.as_secs_f32());
-        }
+        SPANS.with(|spans| {
+            spans.borrow_mut().push(Span {
+                operation: self.operation,
+                elapsed,
+                success,
+            })
+        });
         elapsed
     }
 }
 
-/// Context for better error reporting
-#[allow(dead_code)] // Will be used in future error handling improvements
+/// Time spent on one kind of operation.
+#[derive(Debug, PartialEq)]
+pub struct TimingRow {
+    pub operation: String,
+    pub count: usize,
+    pub failed: usize,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+/// Groups `spans` by operation, slowest total first.
+pub fn breakdown(spans: &[Span]) -> Vec<TimingRow> {
+    let mut rows: Vec<TimingRow> = vec![];
+    for span in spans {
+        let row = match rows.iter_mut().position(|r| r.operation == span.operation) {
+            Some(i) => &mut rows[i],
+            None => {
+                rows.push(TimingRow {
+                    operation: span.operation.clone(),
+                    count: 0,
+                    failed: 0,
+                    total: Duration::ZERO,
+                    max: Duration::ZERO,
+                });
+                rows.last_mut().expect("row was just pushed")
+            }
+        };
+        row.count += 1;
+        row.failed += usize::from(!span.success);
+        row.total += span.elapsed;
+        row.max = row.max.max(span.elapsed);
+    }
+    rows.sort_by_key(|r| Reverse(r.total));
+    rows
+}
+
+/// Prints where the `total` run time of this command went (`--timings`).
+pub fn print_timings(total: Duration) {
+    let spans = SPANS.with(|spans| spans.borrow().clone());
+    let rows = breakdown(&spans);
+    println!("\n{}", "⏱️  Timings".cyan().bold());
+    for row in &rows {
+        let failed = if row.failed > 0 {
+            format!(" ({} failed)", row.failed).red().to_string()
+        } else {
+            String::new()
+        };
+        println!(
+            "  {:<16} {:>3} × {:>7}  max {:>7}{failed}",
+            row.operation,
+            row.count,
+            seconds(row.total / row.count as u32),
+            seconds(row.max)
+        );
+    }
+    let measured: Duration = rows.iter().map(|r| r.total).sum();
+    println!(
+        "  {:<16} {:>13}",
+        "other",
+        seconds(total.saturating_sub(measured))
+    );
+    println!("  {:<16} {:>13}", "total".bold(), seconds(total));
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.2}s", duration.as_secs_f64())
+}
+
+/// What was being attempted when an error happened, to make the message actionable.
 pub struct OperationContext {
     pub operation: String,
     pub details: Vec<String>,
 }
 
-#[allow(dead_code)] // Will be used in future error handling improvements
 impl OperationContext {
     pub fn new(operation: &str) -> Self {
         Self {
@@ -49,8 +134,9 @@ impl OperationContext {
         self.details.push(detail.to_string());
     }
 
+    /// `Failed to <operation>: <error>`, followed by the details on their own lines.
     pub fn format_error(&self, error: &str) -> String {
-        let mut result = format!("❌ Failed to {}: {}", self.operation, error);
+        let mut result = format!("Failed to {}: {}", self.operation, error);
         if !self.details.is_empty() {
             result.push_str("\n   Context:");
             for detail in &self.details {
@@ -60,3 +146,49 @@ impl OperationContext {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(operation: &str, ms: u64, success: bool) -> Span {
+        Span {
+            operation: operation.to_string(),
+            elapsed: Duration::from_millis(ms),
+            success,
+        }
+    }
+
+    #[test]
+    fn test_breakdown_groups_by_operation() {
+        let rows = breakdown(&[
+            span("embed", 40, true),
+            span("generate", 3000, true),
+            span("embed", 60, false),
+        ]);
+        assert_eq!(rows[0].operation, "generate");
+        assert_eq!(rows[1].count, 2);
+        assert_eq!(rows[1].failed, 1);
+        assert_eq!(rows[1].total, Duration::from_millis(100));
+        assert_eq!(rows[1].max, Duration::from_millis(60));
+    }
+
+    #[test]
+    fn test_timer_records_span() {
+        Timer::new("store.insert").finish(false);
+        let spans = SPANS.with(|spans| spans.borrow().clone());
+        assert!(spans
+            .iter()
+            .any(|s| s.operation == "store.insert" && !s.success));
+    }
+
+    #[test]
+    fn test_operation_context_format() {
+        let mut context = OperationContext::new("generate a response");
+        context.add_detail("model: llama3");
+        assert_eq!(
+            context.format_error("timed out"),
+            "Failed to generate a response: timed out\n   Context:\n   • model: llama3"
+        );
+    }
+}
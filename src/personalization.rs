@@ -1,22 +1,48 @@
-use crate::utils::load_profile;
+use crate::config::Config;
+use crate::intent::QueryIntent;
+use crate::prompts;
+use crate::utils::backup_file;
+use chrono::{DateTime, Utc};
+use colored::*;
 use dirs::home_dir;
-use serde_json::{json, Value};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
 use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Version of the `enhanced_context.json` layout written by this build.
+pub const CONTEXT_SCHEMA_VERSION: u32 = 2;
 
 /// Enhanced user context for more personalized suggestions
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserContext {
-    pub basic_profile: Value,
+    #[serde(rename = "schemaVersion", default)]
+    pub schema_version: u32,
+    /// Basic profile from setup.json; not part of the enhanced context file.
+    #[serde(skip)]
+    pub basic_profile: Config,
+    #[serde(default)]
     pub preferences: UserPreferences,
+    #[serde(default)]
     pub activity_patterns: ActivityPatterns,
+    #[serde(default)]
     pub learning_style: LearningStyle,
+    #[serde(default)]
     pub current_projects: Vec<ProjectContext>,
+    #[serde(default)]
     pub goals: Vec<Goal>,
+    #[serde(default)]
     pub interaction_history: InteractionHistory,
+    /// Why an existing `enhanced_context.json` could not be loaded; a context built in
+    /// its place is never saved over it.
+    #[serde(skip)]
+    load_error: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct UserPreferences {
     pub suggestion_types: Vec<String>, // learning, productivity, collaboration, wellness
     pub communication_style: String,   // concise, detailed, casual, formal
@@ -25,40 +51,85 @@ pub struct UserPreferences {
     pub focus_areas: Vec<String>,      // technical skills, soft skills, career growth
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ActivityPatterns {
     pub most_active_times: Vec<String>,
     pub note_taking_frequency: String,
     pub common_topics: Vec<String>,
     pub collaboration_frequency: String,
     pub learning_pace: String, // fast, moderate, slow
+    /// Notes and commands per local hour of day, from `personalize learn`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hour_histogram: Vec<usize>,
+    /// Notes and commands per weekday, Monday first, from `personalize learn`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub weekday_histogram: Vec<usize>,
+    pub notes_per_week: f32,
+    /// When the fields above were last learned; `None` if they only come from setup.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub learned_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LearningStyle {
-    pub preferred_format: String, // hands-on, reading, videos, peer-learning
+    pub preferred_format: String, // hands_on, reading, videos, peer_learning
     pub complexity_preference: String, // beginner, intermediate, advanced
     pub feedback_preference: String, // immediate, periodic, milestone-based
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectContext {
     pub name: String,
+    #[serde(default)]
     pub tech_stack: Vec<String>,
+    #[serde(default)]
     pub current_challenges: Vec<String>,
+    #[serde(default = "default_team_size")]
     pub team_size: u32,
+    #[serde(default = "default_deadline_pressure")]
     pub deadline_pressure: String, // low, medium, high
+    /// Archived projects are kept for reference but left out of suggestions.
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When challenges were last extracted from the project's notes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub challenges_updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Goal {
+    /// Short identifier used by the `goal` commands.
+    #[serde(default)]
+    pub id: String,
     pub description: String,
+    #[serde(default = "default_goal_category")]
     pub category: String, // career, skill, project, personal
-    pub timeline: String, // short-term, medium-term, long-term
-    pub progress: f32,    // 0.0 to 1.0
+    #[serde(default = "default_goal_timeline")]
+    pub timeline: String, // short_term, medium_term, long_term
+    #[serde(default)]
+    pub progress: f32, // 0.0 to 1.0
+    #[serde(default = "default_goal_status")]
+    pub status: String, // active, completed
+    /// Ids of notes that document work towards the goal.
+    #[serde(default)]
+    pub linked_notes: Vec<String>,
+    /// Explanation from the last LLM progress estimate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress_summary: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct InteractionHistory {
     pub suggestion_acceptance_rate: f32,
     pub most_engaged_categories: Vec<String>,
@@ -66,313 +137,303 @@ pub struct InteractionHistory {
     pub feedback_patterns: HashMap<String, f32>, // topic -> satisfaction score
 }
 
-#[derive(Debug, Clone)]
-pub struct QueryIntent {
-    pub intent_type: String,
-    pub context_type: String,
-    pub urgency_level: String,
+fn default_team_size() -> u32 {
+    1
+}
+
+fn default_deadline_pressure() -> String {
+    "medium".to_string()
+}
+
+fn default_goal_category() -> String {
+    "personal".to_string()
+}
+
+fn default_goal_timeline() -> String {
+    "medium_term".to_string()
+}
+
+fn default_goal_status() -> String {
+    "active".to_string()
+}
+
+fn new_goal_id() -> String {
+    Uuid::new_v4().simple().to_string()[..8].to_string()
 }
 
 impl UserContext {
     /// Load existing user context or create a basic one from profile
     pub fn load_or_create() -> Result<Self, String> {
-        let profile = load_profile()?;
+        let profile = Config::load()?;
+        let path = Self::path()?;
+        if path.exists() {
+            Self::load_from(&path, profile)
+        } else {
+            Ok(Self::from_profile(profile))
+        }
+    }
+
+    /// Load existing user context for an already loaded profile, or create a basic one
+    pub fn load_for(profile: Config) -> Self {
+        match Self::path() {
+            Ok(path) => Self::load_or_default(&path, profile),
+            Err(_) => Self::from_profile(profile),
+        }
+    }
 
-        // Try to load enhanced context
-        if let Ok(enhanced_context) = Self::load_enhanced_context() {
-            return Ok(enhanced_context);
+    /// The context at `path`, or a basic one when there is none. A file that exists but
+    /// cannot be loaded (newer schema, invalid values) is reported and left untouched.
+    fn load_or_default(path: &Path, profile: Config) -> Self {
+        if !path.exists() {
+            return Self::from_profile(profile);
+        }
+        match Self::load_from(path, profile.clone()) {
+            Ok(context) => context,
+            Err(e) => {
+                log::warn!("{e}; using defaults from your profile until it is fixed");
+                Self {
+                    load_error: Some(e),
+                    ..Self::from_profile(profile)
+                }
+            }
         }
+    }
 
-        // Create basic context from existing profile
-        Ok(Self {
-            basic_profile: profile.clone(),
+    /// Create a basic context inferred from the profile alone
+    pub fn from_profile(profile: Config) -> Self {
+        Self {
+            schema_version: CONTEXT_SCHEMA_VERSION,
             preferences: UserPreferences::from_profile(&profile),
             activity_patterns: ActivityPatterns::default(),
             learning_style: LearningStyle::from_profile(&profile),
+            basic_profile: profile,
             current_projects: vec![],
             goals: vec![],
             interaction_history: InteractionHistory::default(),
-        })
+            load_error: None,
+        }
     }
 
-    /// Load enhanced context from file
-    fn load_enhanced_context() -> Result<Self, String> {
+    fn path() -> Result<PathBuf, String> {
         let mut path = home_dir().ok_or("Could not determine home directory")?;
         path.push(".logswise/enhanced_context.json");
+        Ok(path)
+    }
 
-        let data = fs::read_to_string(&path).map_err(|_| "Enhanced context not found")?;
-
-        let context_data: Value =
-            serde_json::from_str(&data).map_err(|_| "Failed to parse enhanced context")?;
-
-        // Parse the JSON back into UserContext
-        // This is simplified - in a real implementation you'd want proper deserialization
-        let basic_profile = load_profile()?;
-
-        Ok(Self {
-            basic_profile,
-            preferences: UserPreferences::from_json(&context_data["preferences"]),
-            activity_patterns: ActivityPatterns::from_json(&context_data["activity_patterns"]),
-            learning_style: LearningStyle::from_json(&context_data["learning_style"]),
-            current_projects: ProjectContext::vec_from_json(&context_data["current_projects"]),
-            goals: Goal::vec_from_json(&context_data["goals"]),
-            interaction_history: InteractionHistory::from_json(
-                &context_data["interaction_history"],
-            ),
-        })
+    /// Loads the enhanced context at `path`, upgrading older files after backing them up.
+    fn load_from(path: &Path, basic_profile: Config) -> Result<Self, String> {
+        let data = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read enhanced_context.json: {e}"))?;
+        let value: Value = serde_json::from_str(&data)
+            .map_err(|e| format!("Failed to parse enhanced_context.json: {e}"))?;
+
+        let (value, migrated_from) = migrate(value)?;
+        let mut context: Self =
+            serde_json::from_value(value).map_err(|e| format!("Invalid enhanced context: {e}"))?;
+        context.basic_profile = basic_profile;
+
+        if let Some(version) = migrated_from {
+            let backup = backup_file(path, version)?;
+            context.save_to(path)?;
+            // stderr, so the notice never ends up in redirected command output
+            eprintln!(
+                "{}",
+                format!(
+                    "ℹ️  Upgraded enhanced_context.json to schema v{CONTEXT_SCHEMA_VERSION} (backup: {})",
+                    backup.display()
+                )
+                .cyan()
+            );
+        }
+        Ok(context)
     }
 
     /// Save enhanced context to file
     pub fn save(&self) -> Result<(), String> {
-        let mut path = home_dir().ok_or("Could not determine home directory")?;
-        path.push(".logswise");
-        fs::create_dir_all(&path).map_err(|e| format!("Failed to create directory: {e}"))?;
-        path.push("enhanced_context.json");
-
-        let context_data = json!({
-            "preferences": self.preferences.to_json(),
-            "activity_patterns": self.activity_patterns.to_json(),
-            "learning_style": self.learning_style.to_json(),
-            "current_projects": self.current_projects.iter().map(|p| p.to_json()).collect::<Vec<_>>(),
-            "goals": self.goals.iter().map(|g| g.to_json()).collect::<Vec<_>>(),
-            "interaction_history": self.interaction_history.to_json(),
-        });
-
-        fs::write(&path, serde_json::to_string_pretty(&context_data).unwrap())
-            .map_err(|e| format!("Failed to save enhanced context: {e}"))?;
-
-        Ok(())
+        self.save_to(&Self::path()?)
     }
 
-    /// Generate rich prompt context for LLM using advanced contextualization
-    pub fn generate_llm_context(&self, query: &str, relevant_notes: &[String]) -> String {
-        let mut context = String::new();
-
-        // Advanced system prompt with chain-of-thought reasoning
-        context.push_str("=== SYSTEM INSTRUCTIONS ===\n");
-        context.push_str(
-            "You are an AI assistant specializing in personalized professional development.\n",
-        );
-        context.push_str(
-            "Your role: Provide contextual, actionable advice based on verified user data.\n\n",
-        );
-
-        context.push_str("REASONING FRAMEWORK:\n");
-        context.push_str("1. ANALYZE: Understand the intent behind the query\n");
-        context.push_str("2. CONTEXTUALIZE: Map query to relevant user projects/goals\n");
-        context.push_str("3. SYNTHESIZE: Generate specific, actionable suggestions\n");
-        context.push_str("4. VALIDATE: Ensure all advice is grounded in verified data\n\n");
-
-        context.push_str("CRITICAL CONSTRAINTS:\n");
-        context.push_str("- NEVER invent relationships, conversations, or commitments\n");
-        context.push_str("- Focus on SITUATION and TOPIC, not on people mentioned\n");
-        context.push_str(
-            "- Use conversation context as INPUT TYPE (meeting prep, progress update, etc.)\n",
-        );
-        context
-            .push_str("- Ground ALL suggestions in documented projects, challenges, and goals\n");
-        context.push_str("- Provide MEASURABLE and TIME-BOUND recommendations when possible\n\n");
-
-        // Enhanced user profile with contextual signals
-        context.push_str(&format!(
-            "=== USER CONTEXT PROFILE ===\n\
-            🎯 PROFESSIONAL IDENTITY:\n\
-            Role: {} | Experience Level: {} | Domain: {}\n\
-            Organization: {} ({} employees) | Work Style: {}\n\
-            Tech Stack: {} | Career Stage: Aiming to {}\n\n\
-            💡 COGNITIVE PREFERENCES:\n\
-            Communication: {} | Learning: {} (complexity: {})\n\
-            Decision Making: {} feedback loops | Active Times: {}\n\
-            Learning Velocity: {} | Focus Areas: {}\n\n",
-            self.basic_profile["profession"]
-                .as_str()
-                .unwrap_or("Professional"),
-            self.basic_profile["yearsExperience"]
-                .as_str()
-                .unwrap_or("Not specified"),
-            self.basic_profile["preferredLanguage"]
-                .as_str()
-                .unwrap_or("Multi-tech"),
-            self.basic_profile["companyName"]
-                .as_str()
-                .unwrap_or("Current Organization"),
-            self.basic_profile["companySize"]
-                .as_str()
-                .unwrap_or("Unknown size"),
-            self.basic_profile["workMode"]
-                .as_str()
-                .unwrap_or("Flexible"),
-            self.basic_profile["preferredLanguage"]
-                .as_str()
-                .unwrap_or("Various"),
-            self.goals
-                .first()
-                .map(|g| g.description.as_str())
-                .unwrap_or("grow professionally"),
-            self.preferences.communication_style,
-            self.learning_style.preferred_format,
-            self.learning_style.complexity_preference,
-            self.learning_style.feedback_preference,
-            self.activity_patterns.most_active_times.join(", "),
-            self.activity_patterns.learning_pace,
-            self.preferences.focus_areas.join(" + ")
-        ));
+    fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(e) = &self.load_error {
+            return Err(format!(
+                "Not overwriting {}, which could not be loaded ({e}). Fix or move the file first",
+                path.display()
+            ));
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+        }
+        let mut context = self.clone();
+        context.schema_version = CONTEXT_SCHEMA_VERSION;
+        let data = serde_json::to_string_pretty(&context)
+            .map_err(|e| format!("Failed to encode enhanced context: {e}"))?;
+        fs::write(path, data).map_err(|e| format!("Failed to save enhanced context: {e}"))
+    }
 
-        // Strategic project portfolio analysis
-        if !self.current_projects.is_empty() {
-            context.push_str("=== ACTIVE PROJECT PORTFOLIO ===\n");
-            for (i, project) in self.current_projects.iter().enumerate() {
+    /// Variables for the `suggestion` prompt template: profile, preferences, projects,
+    /// goals, interaction history, relevant notes, and the query with its classified intent.
+    pub fn prompt_data(
+        &self,
+        query: &str,
+        intent: &QueryIntent,
+        relevant_notes: &[String],
+    ) -> Value {
+        let notes: Vec<Value> = relevant_notes
+            .iter()
+            .enumerate()
+            .map(|(i, note)| {
+                let relevance = if i < 2 {
+                    "🎯 HIGH"
+                } else if i < 4 {
+                    "📋 MEDIUM"
+                } else {
+                    "📝 LOW"
+                };
+                json!({ "content": note, "relevance": relevance })
+            })
+            .collect();
+
+        let active_project = self.basic_profile.active_project.trim();
+        let projects: Vec<Value> = self
+            .current_projects
+            .iter()
+            .filter(|project| !project.archived)
+            .map(|project| {
                 let priority = match project.deadline_pressure.as_str() {
                     "high" => "🔥 HIGH PRIORITY",
                     "medium" => "⚡ MODERATE PRIORITY",
                     _ => "📋 STANDARD PRIORITY",
                 };
-
                 let team_context = match project.team_size {
                     1 => "Solo Project",
                     2..=5 => "Small Team",
                     6..=15 => "Medium Team",
                     _ => "Large Team",
                 };
-
-                context.push_str(&format!(
-                    "🚀 PROJECT {}: {} [{}]\n\
-                    ├─ Tech Stack: {}\n\
-                    ├─ Team Context: {} ({} people)\n\
-                    ├─ Key Challenges: {}\n\
-                    └─ Strategic Focus: {}\n\n",
-                    i + 1,
-                    project.name,
-                    priority,
-                    project.tech_stack.join(" + "),
-                    team_context,
-                    project.team_size,
-                    project.current_challenges.join(" | "),
-                    if project.deadline_pressure == "high" {
-                        "Delivery & Quality"
-                    } else {
-                        "Innovation & Growth"
-                    }
-                ));
-            }
-        } else {
-            context.push_str("=== PROJECT PORTFOLIO ===\n");
-            context.push_str("📝 No specific projects documented. Opportunity to capture current work context.\n\n");
-        }
-
-        // Goal-oriented development roadmap
-        if !self.goals.is_empty() {
-            context.push_str("=== DEVELOPMENT ROADMAP ===\n");
-            for goal in &self.goals {
-                let progress_bar = "▓".repeat((goal.progress * 10.0) as usize)
-                    + &"░".repeat(10 - (goal.progress * 10.0) as usize);
+                let strategic_focus = if project.deadline_pressure == "high" {
+                    "Delivery & Quality"
+                } else {
+                    "Innovation & Growth"
+                };
+                json!({
+                    "name": project.name,
+                    "priority": priority,
+                    "tech_stack": project.tech_stack,
+                    "team_context": team_context,
+                    "team_size": project.team_size,
+                    "challenges": project.current_challenges,
+                    "strategic_focus": strategic_focus,
+                    "deadline_pressure": project.deadline_pressure,
+                    "is_active": project.name.eq_ignore_ascii_case(active_project),
+                })
+            })
+            .collect();
+
+        let goals: Vec<Value> = self
+            .goals
+            .iter()
+            .filter(|goal| goal.is_active())
+            .map(|goal| {
+                let filled = (goal.progress.clamp(0.0, 1.0) * 10.0) as usize;
                 let timeline_icon = match goal.timeline.as_str() {
-                    "short-term" => "⚡",
-                    "medium-term" => "🎯",
+                    "short_term" => "⚡",
+                    "medium_term" => "🎯",
                     _ => "🌟",
                 };
+                json!({
+                    "description": goal.description.trim(),
+                    "category": goal.category,
+                    "category_label": goal.category.replace('_', " ").to_uppercase(),
+                    "timeline": goal.timeline,
+                    "timeline_label": goal.timeline.replace('_', " ").to_uppercase(),
+                    "timeline_icon": timeline_icon,
+                    "progress_bar": "▓".repeat(filled) + &"░".repeat(10 - filled),
+                    "progress_percent": (goal.progress.clamp(0.0, 1.0) * 100.0) as u32,
+                    "linked_notes": goal.linked_notes.len(),
+                    "progress_summary": goal.progress_summary.as_deref().unwrap_or(""),
+                })
+            })
+            .collect();
+        let completed_goals: Vec<&str> = self
+            .goals
+            .iter()
+            .filter(|goal| !goal.is_active())
+            .map(|goal| goal.description.trim())
+            .collect();
+
+        let challenges: Vec<&String> = self
+            .current_projects
+            .iter()
+            .filter(|p| !p.archived)
+            .flat_map(|p| &p.current_challenges)
+            .collect();
+
+        let mut preferences = serde_json::to_value(&self.preferences).unwrap_or_default();
+        preferences["style"] = one_of(
+            &self.preferences.communication_style,
+            &["concise", "detailed", "casual", "professional"],
+            "balanced",
+        );
+        let mut learning = serde_json::to_value(&self.learning_style).unwrap_or_default();
+        learning["format"] = one_of(
+            &self.learning_style.preferred_format,
+            &["hands_on", "reading", "videos", "peer_learning"],
+            "mixed",
+        );
+        learning["complexity"] = one_of(
+            &self.learning_style.complexity_preference,
+            &["beginner", "advanced", "adaptive"],
+            "intermediate",
+        );
+        learning["feedback"] = one_of(
+            &self.learning_style.feedback_preference,
+            &["immediate", "milestone"],
+            "periodic",
+        );
 
-                context.push_str(&format!(
-                    "{} {} | {} | [{}] {}%\n\
-                    └─ Domain: {} | Target: {}\n",
-                    timeline_icon,
-                    goal.description.trim(),
-                    goal.timeline.replace('-', " ").to_uppercase(),
-                    progress_bar,
-                    (goal.progress * 100.0) as u32,
-                    goal.category.replace('_', " ").to_uppercase(),
-                    goal.timeline
-                ));
-            }
-            context.push('\n');
-        }
-
-        // Interaction patterns (excluding recent topics to prevent hallucination)
-        context.push_str(&format!(
-            "=== INTERACTION PATTERNS ===\n\
-            Suggestion Acceptance Rate: {:.1}%\n\
-            Most Engaged Categories: {}\n\
-            Activity Level: {} suggestion interactions\n\n",
-            self.interaction_history.suggestion_acceptance_rate * 100.0,
-            self.interaction_history.most_engaged_categories.join(", "),
-            self.interaction_history.recent_topics.len()
-        ));
-
-        // Relevant notes
-        if !relevant_notes.is_empty() {
-            context.push_str("=== RELEVANT NOTES ===\n");
-            for (i, note) in relevant_notes.iter().enumerate() {
-                context.push_str(&format!("{}. {}\n", i + 1, note));
-            }
-            context.push('\n');
-        }
-
-        // Advanced query contextualization
-        let query_intent = self.analyze_query_intent(query);
-        context.push_str("=== QUERY ANALYSIS ===\n");
-        context.push_str(&format!("📋 Request: \"{query}\"\n"));
-        context.push_str(&format!("🎯 Intent: {}\n", query_intent.intent_type));
-        context.push_str(&format!("📊 Context: {}\n", query_intent.context_type));
-        context.push_str(&format!("⚡ Priority: {}\n\n", query_intent.urgency_level));
-
-        // Relevant notes with contextual ranking
-        if !relevant_notes.is_empty() {
-            context.push_str("=== CONTEXTUAL KNOWLEDGE BASE ===\n");
-            for (i, note) in relevant_notes.iter().enumerate() {
-                let relevance = if i < 2 {
-                    "🎯 HIGH"
-                } else if i < 4 {
-                    "📋 MEDIUM"
-                } else {
-                    "📝 LOW"
-                };
-                context.push_str(&format!("{}. [{}] {}\n", i + 1, relevance, note));
-            }
-            context.push('\n');
-        }
-
-        context.push_str("=== RESPONSE FRAMEWORK ===\n");
-        context.push_str("Structure your response using this advanced framework:\n\n");
-
-        match query_intent.intent_type.as_str() {
-            "meeting_preparation" => {
-                context.push_str("📋 MEETING PREP FRAMEWORK:\n");
-                context.push_str("1. 🎯 AGENDA PREPARATION: Key topics to address\n");
-                context.push_str("2. 📊 PROGRESS SUMMARY: Quantified achievements\n");
-                context.push_str("3. 🚧 CHALLENGE ANALYSIS: Issues and proposed solutions\n");
-                context.push_str("4. 🎪 NEXT STEPS: Specific, measurable actions\n");
-            }
-            "progress_reporting" => {
-                context.push_str("📈 PROGRESS REPORT FRAMEWORK:\n");
-                context.push_str("1. ✅ ACCOMPLISHMENTS: What was delivered\n");
-                context.push_str("2. 📊 METRICS: Quantifiable progress indicators\n");
-                context.push_str("3. 🔄 PROCESS IMPROVEMENTS: How efficiency was enhanced\n");
-                context.push_str("4. 🎯 UPCOMING MILESTONES: What's next with timelines\n");
-            }
-            "problem_solving" => {
-                context.push_str("🔧 PROBLEM-SOLVING FRAMEWORK:\n");
-                context.push_str("1. 🔍 ROOT CAUSE: Core issue identification\n");
-                context.push_str("2. 💡 SOLUTION OPTIONS: Alternative approaches\n");
-                context.push_str("3. ⚖️ TRADE-OFF ANALYSIS: Pros/cons of each option\n");
-                context.push_str("4. 🎯 RECOMMENDATION: Best path forward with rationale\n");
-            }
-            _ => {
-                context.push_str("🎯 GENERAL FRAMEWORK:\n");
-                context.push_str("1. 📊 SITUATION ANALYSIS: Current state assessment\n");
-                context.push_str("2. 💡 STRATEGIC OPTIONS: Available approaches\n");
-                context.push_str("3. 🎯 ACTIONABLE RECOMMENDATIONS: Specific next steps\n");
-                context.push_str("4. 📈 SUCCESS METRICS: How to measure progress\n");
-            }
-        }
-
-        context.push_str("\n💼 PROFESSIONAL EXCELLENCE STANDARDS:\n");
-        context.push_str("- Provide SPECIFIC, time-bound recommendations\n");
-        context.push_str("- Include SUCCESS METRICS for each suggestion\n");
-        context.push_str("- Consider STAKEHOLDER IMPACT and team dynamics\n");
-        context.push_str("- Balance SHORT-TERM wins with LONG-TERM strategy\n");
-        context.push_str("- Suggest FOLLOW-UP mechanisms for accountability\n\n");
-
-        context
+        json!({
+            "profile": prompts::profile_data(&self.basic_profile),
+            "query": query,
+            "intent": {
+                "type": intent.intent_type,
+                "context": intent.context_type,
+                "urgency": intent.urgency_level,
+                "confidence_percent": (intent.intent_confidence * 100.0).round() as u32,
+                "is": one_of(
+                    &intent.intent_type,
+                    &["meeting_preparation", "progress_reporting", "problem_solving"],
+                    "general",
+                ),
+            },
+            "has_notes": !notes.is_empty(),
+            "notes": notes,
+            "active_project": active_project,
+            "has_projects": !projects.is_empty(),
+            "projects": projects,
+            "challenges": challenges,
+            "has_goals": !goals.is_empty(),
+            "goals": goals,
+            "has_completed_goals": !completed_goals.is_empty(),
+            "completed_goals": completed_goals,
+            "first_goal": self
+                .goals
+                .iter()
+                .find(|g| g.is_active())
+                .map(|g| g.description.trim())
+                .unwrap_or(""),
+            "has_focus_areas": !self.preferences.focus_areas.is_empty(),
+            "preferences": preferences,
+            "learning": learning,
+            "activity": self.activity_patterns,
+            "history": {
+                "acceptance_rate_percent": format!(
+                    "{:.1}",
+                    self.interaction_history.suggestion_acceptance_rate * 100.0
+                ),
+                "engaged_categories": self.interaction_history.most_engaged_categories,
+                "activity_level": self.interaction_history.recent_topics.len(),
+            },
+        })
     }
 
     /// Update interaction history based on user feedback
@@ -407,14 +468,55 @@ impl UserContext {
         }
     }
 
-    /// Add a new goal
-    pub fn add_goal(&mut self, description: String, category: String, timeline: String) {
+    /// Add a new goal and return it
+    pub fn add_goal(&mut self, description: String, category: String, timeline: String) -> &Goal {
+        let now = Utc::now();
         self.goals.push(Goal {
+            id: new_goal_id(),
             description,
             category,
             timeline,
             progress: 0.0,
+            status: default_goal_status(),
+            linked_notes: vec![],
+            progress_summary: None,
+            created_at: Some(now),
+            updated_at: Some(now),
+            completed_at: None,
         });
+        self.goals.last().unwrap()
+    }
+
+    /// Finds a goal by its 1-based position in `goal list` or by (a prefix of) its id.
+    pub fn goal_index(&self, key: &str) -> Result<usize, String> {
+        let key = key.trim();
+        if let Ok(n) = key.parse::<usize>() {
+            if (1..=self.goals.len()).contains(&n) {
+                return Ok(n - 1);
+            }
+        }
+        let matches: Vec<usize> = (0..self.goals.len())
+            .filter(|&i| !key.is_empty() && self.goals[i].id.starts_with(key))
+            .collect();
+        match matches.as_slice() {
+            [i] => Ok(*i),
+            [] => Err(format!(
+                "No goal '{key}'. Run 'logswise-cli goal list' to see your goals."
+            )),
+            _ => Err(format!(
+                "'{key}' matches several goals; use more characters of the id."
+            )),
+        }
+    }
+
+    pub fn goal_mut(&mut self, key: &str) -> Result<&mut Goal, String> {
+        let index = self.goal_index(key)?;
+        Ok(&mut self.goals[index])
+    }
+
+    pub fn remove_goal(&mut self, key: &str) -> Result<Goal, String> {
+        let index = self.goal_index(key)?;
+        Ok(self.goals.remove(index))
     }
 
     /// Add current project context
@@ -422,85 +524,109 @@ impl UserContext {
         self.current_projects.push(project);
     }
 
-    /// Advanced query intent analysis for contextualized responses
-    fn analyze_query_intent(&self, query: &str) -> QueryIntent {
-        let query_lower = query.to_lowercase();
+    /// Names of the user's current (not archived) projects, used to recognise
+    /// project-specific queries.
+    pub fn project_names(&self) -> Vec<String> {
+        self.current_projects
+            .iter()
+            .filter(|p| !p.archived)
+            .map(|p| p.name.clone())
+            .collect()
+    }
 
-        let intent_type = if query_lower.contains("meeting")
-            || query_lower.contains("one-on-one")
-            || query_lower.contains("discuss")
-            || query_lower.contains("prepare")
-        {
-            "meeting_preparation"
-        } else if query_lower.contains("progress")
-            || query_lower.contains("update")
-            || query_lower.contains("accomplished")
-            || query_lower.contains("completed")
-        {
-            "progress_reporting"
-        } else if query_lower.contains("problem")
-            || query_lower.contains("issue")
-            || query_lower.contains("challenge")
-            || query_lower.contains("stuck")
-        {
-            "problem_solving"
-        } else if query_lower.contains("learn")
-            || query_lower.contains("skill")
-            || query_lower.contains("improve")
-            || query_lower.contains("develop")
-        {
-            "skill_development"
-        } else if query_lower.contains("career")
-            || query_lower.contains("promotion")
-            || query_lower.contains("senior")
-            || query_lower.contains("advancement")
-        {
-            "career_growth"
-        } else {
-            "general_advice"
-        };
+    /// Finds a project by name, ignoring case.
+    pub fn project_index(&self, name: &str) -> Result<usize, String> {
+        self.current_projects
+            .iter()
+            .position(|p| p.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| {
+                format!(
+                    "No project '{name}'. Run 'logswise-cli project list' to see your projects."
+                )
+            })
+    }
 
-        let context_type = if query_lower.contains("team") || query_lower.contains("colleague") {
-            "team_collaboration"
-        } else if query_lower.contains("project")
-            || query_lower.contains("vena")
-            || query_lower.contains("logswise")
-        {
-            "project_specific"
-        } else if query_lower.contains("technical")
-            || query_lower.contains("code")
-            || query_lower.contains("development")
-        {
-            "technical_focus"
-        } else {
-            "general_professional"
-        };
+    pub fn project_mut(&mut self, name: &str) -> Result<&mut ProjectContext, String> {
+        let index = self.project_index(name)?;
+        Ok(&mut self.current_projects[index])
+    }
+}
 
-        let urgency_level = if query_lower.contains("tomorrow")
-            || query_lower.contains("urgent")
-            || query_lower.contains("asap")
-            || query_lower.contains("immediately")
-        {
-            "high"
-        } else if query_lower.contains("soon") || query_lower.contains("this week") {
-            "medium"
-        } else {
-            "normal"
-        };
+impl ProjectContext {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            tech_stack: vec![],
+            current_challenges: vec![],
+            team_size: default_team_size(),
+            deadline_pressure: default_deadline_pressure(),
+            archived: false,
+            created_at: Some(Utc::now()),
+            challenges_updated_at: None,
+        }
+    }
 
-        QueryIntent {
-            intent_type: intent_type.to_string(),
-            context_type: context_type.to_string(),
-            urgency_level: urgency_level.to_string(),
+    /// Adds challenges not already listed (ignoring case), keeping the newest
+    /// `limit` entries. Returns how many were added.
+    pub fn merge_challenges(&mut self, found: Vec<String>, limit: usize) -> usize {
+        let mut added = 0;
+        for challenge in found {
+            let challenge = challenge.trim().to_string();
+            if challenge.is_empty()
+                || self
+                    .current_challenges
+                    .iter()
+                    .any(|c| c.eq_ignore_ascii_case(&challenge))
+            {
+                continue;
+            }
+            self.current_challenges.push(challenge);
+            added += 1;
+        }
+        let excess = self.current_challenges.len().saturating_sub(limit);
+        self.current_challenges.drain(..excess);
+        added
+    }
+}
+
+impl Goal {
+    pub fn is_active(&self) -> bool {
+        self.status == "active"
+    }
+
+    /// Sets progress (clamped to 0.0..=1.0), reopening the goal if it had been completed.
+    pub fn set_progress(&mut self, progress: f32) {
+        self.progress = progress.clamp(0.0, 1.0);
+        if self.progress < 1.0 && !self.is_active() {
+            self.status = default_goal_status();
+            self.completed_at = None;
+        }
+        self.updated_at = Some(Utc::now());
+    }
+
+    pub fn complete(&mut self) {
+        let now = Utc::now();
+        self.progress = 1.0;
+        self.status = "completed".to_string();
+        self.completed_at = Some(now);
+        self.updated_at = Some(now);
+    }
+
+    /// Links a note to the goal; returns false if it was already linked.
+    pub fn link_note(&mut self, note_id: &str) -> bool {
+        if self.linked_notes.iter().any(|id| id == note_id) {
+            return false;
         }
+        self.linked_notes.push(note_id.to_string());
+        self.updated_at = Some(Utc::now());
+        true
     }
 }
 
 impl UserPreferences {
-    fn from_profile(profile: &Value) -> Self {
+    fn from_profile(profile: &Config) -> Self {
         // Infer preferences from basic profile
-        let profession = profile["profession"].as_str().unwrap_or("").to_lowercase();
-        let _experience = profile["yearsExperience"].as_str().unwrap_or("");
+        let profession = profile.profession.to_lowercase();
 
         let communication_style = if profession.contains("manager") || profession.contains("lead") {
             "detailed".to_string()
@@ -526,50 +652,18 @@ impl UserPreferences {
             focus_areas,
         }
     }
+}
 
-    fn from_json(json: &Value) -> Self {
+impl Default for UserPreferences {
+    fn default() -> Self {
         Self {
-            suggestion_types: json["suggestion_types"]
-                .as_array()
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect()
-                })
-                .unwrap_or_default(),
-            communication_style: json["communication_style"]
-                .as_str()
-                .unwrap_or("balanced")
-                .to_string(),
-            frequency: json["frequency"].as_str().unwrap_or("weekly").to_string(),
-            time_of_day: json["time_of_day"]
-                .as_array()
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect()
-                })
-                .unwrap_or_default(),
-            focus_areas: json["focus_areas"]
-                .as_array()
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect()
-                })
-                .unwrap_or_default(),
+            suggestion_types: vec![],
+            communication_style: "balanced".to_string(),
+            frequency: "weekly".to_string(),
+            time_of_day: vec![],
+            focus_areas: vec![],
         }
     }
-
-    fn to_json(&self) -> Value {
-        json!({
-            "suggestion_types": self.suggestion_types,
-            "communication_style": self.communication_style,
-            "frequency": self.frequency,
-            "time_of_day": self.time_of_day,
-            "focus_areas": self.focus_areas
-        })
-    }
 }
 
 impl Default for ActivityPatterns {
@@ -580,62 +674,21 @@ impl Default for ActivityPatterns {
             common_topics: vec![],
             collaboration_frequency: "moderate".to_string(),
             learning_pace: "moderate".to_string(),
+            hour_histogram: vec![],
+            weekday_histogram: vec![],
+            notes_per_week: 0.0,
+            learned_at: None,
         }
     }
 }
 
-impl ActivityPatterns {
-    fn from_json(json: &Value) -> Self {
-        Self {
-            most_active_times: json["most_active_times"]
-                .as_array()
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect()
-                })
-                .unwrap_or_default(),
-            note_taking_frequency: json["note_taking_frequency"]
-                .as_str()
-                .unwrap_or("moderate")
-                .to_string(),
-            common_topics: json["common_topics"]
-                .as_array()
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect()
-                })
-                .unwrap_or_default(),
-            collaboration_frequency: json["collaboration_frequency"]
-                .as_str()
-                .unwrap_or("moderate")
-                .to_string(),
-            learning_pace: json["learning_pace"]
-                .as_str()
-                .unwrap_or("moderate")
-                .to_string(),
-        }
-    }
-
-    fn to_json(&self) -> Value {
-        json!({
-            "most_active_times": self.most_active_times,
-            "note_taking_frequency": self.note_taking_frequency,
-            "common_topics": self.common_topics,
-            "collaboration_frequency": self.collaboration_frequency,
-            "learning_pace": self.learning_pace
-        })
-    }
-}
-
 impl LearningStyle {
-    fn from_profile(profile: &Value) -> Self {
-        let profession = profile["profession"].as_str().unwrap_or("").to_lowercase();
-        let experience = profile["yearsExperience"].as_str().unwrap_or("");
+    fn from_profile(profile: &Config) -> Self {
+        let profession = profile.profession.to_lowercase();
+        let experience = profile.years_experience.as_str();
 
         let preferred_format = if profession.contains("developer") {
-            "hands-on".to_string()
+            "hands_on".to_string()
         } else {
             "reading".to_string()
         };
@@ -655,156 +708,186 @@ impl LearningStyle {
             feedback_preference: "periodic".to_string(),
         }
     }
+}
 
-    fn from_json(json: &Value) -> Self {
+impl Default for LearningStyle {
+    fn default() -> Self {
         Self {
-            preferred_format: json["preferred_format"]
-                .as_str()
-                .unwrap_or("hands-on")
-                .to_string(),
-            complexity_preference: json["complexity_preference"]
-                .as_str()
-                .unwrap_or("intermediate")
-                .to_string(),
-            feedback_preference: json["feedback_preference"]
-                .as_str()
-                .unwrap_or("periodic")
-                .to_string(),
+            preferred_format: "hands_on".to_string(),
+            complexity_preference: "intermediate".to_string(),
+            feedback_preference: "periodic".to_string(),
         }
     }
+}
 
-    fn to_json(&self) -> Value {
-        json!({
-            "preferred_format": self.preferred_format,
-            "complexity_preference": self.complexity_preference,
-            "feedback_preference": self.feedback_preference
-        })
+/// Flags for template sections: `{known: value == known, ..., fallback: not any known}`.
+fn one_of(value: &str, known: &[&str], fallback: &str) -> Value {
+    let mut flags = Map::new();
+    for k in known {
+        flags.insert(k.to_string(), Value::Bool(value == *k));
     }
+    flags.insert(fallback.to_string(), Value::Bool(!known.contains(&value)));
+    Value::Object(flags)
 }
 
-impl ProjectContext {
-    fn vec_from_json(json: &Value) -> Vec<Self> {
-        json.as_array()
-            .map(|arr| arr.iter().filter_map(Self::from_json).collect())
-            .unwrap_or_default()
-    }
-
-    fn from_json(json: &Value) -> Option<Self> {
-        Some(Self {
-            name: json["name"].as_str()?.to_string(),
-            tech_stack: json["tech_stack"]
-                .as_array()
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect()
-                })
-                .unwrap_or_default(),
-            current_challenges: json["current_challenges"]
-                .as_array()
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect()
-                })
-                .unwrap_or_default(),
-            team_size: json["team_size"].as_u64().unwrap_or(1) as u32,
-            deadline_pressure: json["deadline_pressure"]
-                .as_str()
-                .unwrap_or("medium")
-                .to_string(),
-        })
+/// Upgrades a raw `enhanced_context.json` document to [`CONTEXT_SCHEMA_VERSION`].
+///
+/// Returns the version the document started at when anything had to change.
+fn migrate(mut value: Value) -> Result<(Value, Option<u32>), String> {
+    let obj = value
+        .as_object_mut()
+        .ok_or("Failed to parse enhanced context")?;
+    let original = obj
+        .get("schemaVersion")
+        .and_then(Value::as_u64)
+        .unwrap_or(0) as u32;
+    if original > CONTEXT_SCHEMA_VERSION {
+        return Err(format!(
+            "enhanced_context.json uses schema v{original}, but this version of logswise-cli only understands up to v{CONTEXT_SCHEMA_VERSION}"
+        ));
     }
 
-    fn to_json(&self) -> Value {
-        json!({
-            "name": self.name,
-            "tech_stack": self.tech_stack,
-            "current_challenges": self.current_challenges,
-            "team_size": self.team_size,
-            "deadline_pressure": self.deadline_pressure
-        })
+    let mut version = original;
+    while version < CONTEXT_SCHEMA_VERSION {
+        match version {
+            0 => migrate_v0_to_v1(obj),
+            1 => migrate_v1_to_v2(obj),
+            _ => unreachable!("no migration from schema v{version}"),
+        }
+        version += 1;
     }
+    obj.insert("schemaVersion".to_string(), Value::from(version));
+
+    let migrated = (original != CONTEXT_SCHEMA_VERSION).then_some(original);
+    Ok((value, migrated))
 }
 
-impl Goal {
-    fn vec_from_json(json: &Value) -> Vec<Self> {
-        json.as_array()
-            .map(|arr| arr.iter().filter_map(Self::from_json).collect())
-            .unwrap_or_default()
-    }
-
-    fn from_json(json: &Value) -> Option<Self> {
-        Some(Self {
-            description: json["description"].as_str()?.to_string(),
-            category: json["category"].as_str().unwrap_or("personal").to_string(),
-            timeline: json["timeline"]
-                .as_str()
-                .unwrap_or("medium-term")
-                .to_string(),
-            progress: json["progress"].as_f64().unwrap_or(0.0) as f32,
-        })
+/// v0 files mixed `hands-on`/`hands_on` and `short-term`/`short_term` depending on whether
+/// they were inferred from the profile or entered in the setup wizard. v1 uses underscores.
+fn migrate_v0_to_v1(obj: &mut Map<String, Value>) {
+    if let Some(Value::String(format)) = obj
+        .get_mut("learning_style")
+        .and_then(|s| s.get_mut("preferred_format"))
+    {
+        *format = format.replace('-', "_");
     }
-
-    fn to_json(&self) -> Value {
-        json!({
-            "description": self.description,
-            "category": self.category,
-            "timeline": self.timeline,
-            "progress": self.progress
-        })
+    if let Some(Value::Array(goals)) = obj.get_mut("goals") {
+        for goal in goals {
+            if let Some(Value::String(timeline)) = goal.get_mut("timeline") {
+                *timeline = timeline.replace('-', "_");
+            }
+        }
     }
 }
 
-impl Default for InteractionHistory {
-    fn default() -> Self {
-        Self {
-            suggestion_acceptance_rate: 0.0,
-            most_engaged_categories: vec![],
-            recent_topics: vec![],
-            feedback_patterns: HashMap::new(),
+/// v2 gives goals an id so the `goal` commands can address them.
+fn migrate_v1_to_v2(obj: &mut Map<String, Value>) {
+    if let Some(Value::Array(goals)) = obj.get_mut("goals") {
+        for goal in goals.iter_mut().filter_map(Value::as_object_mut) {
+            let has_id = goal
+                .get("id")
+                .and_then(Value::as_str)
+                .is_some_and(|id| !id.is_empty());
+            if !has_id {
+                goal.insert("id".to_string(), Value::from(new_goal_id()));
+            }
         }
     }
 }
 
-impl InteractionHistory {
-    fn from_json(json: &Value) -> Self {
-        Self {
-            suggestion_acceptance_rate: json["suggestion_acceptance_rate"].as_f64().unwrap_or(0.0)
-                as f32,
-            most_engaged_categories: json["most_engaged_categories"]
-                .as_array()
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect()
-                })
-                .unwrap_or_default(),
-            recent_topics: json["recent_topics"]
-                .as_array()
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect()
-                })
-                .unwrap_or_default(),
-            feedback_patterns: json["feedback_patterns"]
-                .as_object()
-                .map(|obj| {
-                    obj.iter()
-                        .filter_map(|(k, v)| v.as_f64().map(|f| (k.clone(), f as f32)))
-                        .collect()
-                })
-                .unwrap_or_default(),
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_load_migrates_v0_context_with_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("enhanced_context.json");
+        let v0 = json!({
+            "preferences": { "communication_style": "concise" },
+            "learning_style": { "preferred_format": "hands-on" },
+            "current_projects": [{ "name": "Billing", "tech_stack": ["Rust"] }],
+            "goals": [{ "description": "Ship v2", "timeline": "short-term", "progress": 0.5 }],
+            "interaction_history": { "feedback_patterns": { "learning": 0.9 } }
+        });
+        fs::write(&path, v0.to_string()).unwrap();
+
+        let context = UserContext::load_from(&path, Config::default()).unwrap();
+        assert_eq!(context.schema_version, CONTEXT_SCHEMA_VERSION);
+        assert_eq!(context.preferences.communication_style, "concise");
+        assert_eq!(context.preferences.frequency, "weekly");
+        assert_eq!(context.learning_style.preferred_format, "hands_on");
+        assert_eq!(context.current_projects[0].team_size, 1);
+        assert_eq!(context.current_projects[0].deadline_pressure, "medium");
+        assert_eq!(context.goals[0].timeline, "short_term");
+        assert_eq!(context.goals[0].category, "personal");
+        assert_eq!(
+            context
+                .interaction_history
+                .feedback_patterns
+                .get("learning"),
+            Some(&0.9)
+        );
+        assert!(dir.path().join("enhanced_context.json.v0.bak").exists());
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["schemaVersion"], CONTEXT_SCHEMA_VERSION);
+        assert_eq!(saved["goals"][0]["timeline"], "short_term");
+        assert_eq!(saved["goals"][0]["id"], context.goals[0].id.as_str());
+        assert_eq!(context.goals[0].id.len(), 8);
+        assert_eq!(context.goals[0].status, "active");
     }
 
-    fn to_json(&self) -> Value {
-        json!({
-            "suggestion_acceptance_rate": self.suggestion_acceptance_rate,
-            "most_engaged_categories": self.most_engaged_categories,
-            "recent_topics": self.recent_topics,
-            "feedback_patterns": self.feedback_patterns
-        })
+    #[test]
+    fn test_unloadable_context_is_not_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("enhanced_context.json");
+        let newer = json!({ "schemaVersion": CONTEXT_SCHEMA_VERSION + 1, "goals": [] });
+        fs::write(&path, newer.to_string()).unwrap();
+
+        let mut context = UserContext::load_or_default(&path, Config::default());
+        assert!(context.goals.is_empty());
+        context.add_goal("Learn Rust".into(), "skill".into(), "short_term".into());
+        assert!(context.save_to(&path).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), newer.to_string());
+
+        let missing = dir.path().join("missing.json");
+        assert!(UserContext::load_or_default(&missing, Config::default())
+            .save_to(&missing)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_goal_lookup_progress_and_completion() {
+        let mut context = UserContext::from_profile(Config::default());
+        context.add_goal("Learn Rust".into(), "skill".into(), "short_term".into());
+        context.add_goal("Lead a project".into(), "career".into(), "long_term".into());
+        // Fixed ids, so no random id can start with a number used below
+        let id = "3f2a9c1d".to_string();
+        context.goals[0].id = id.clone();
+        context.goals[1].id = "b7e40a12".to_string();
+
+        assert_eq!(context.goal_index("2").unwrap(), 1);
+        assert_eq!(context.goal_index(&id[..4]).unwrap(), 0);
+        assert!(context.goal_index("9").is_err());
+
+        let goal = context.goal_mut(&id).unwrap();
+        assert!(goal.link_note("note-1"));
+        assert!(!goal.link_note("note-1"));
+        goal.complete();
+        assert!(!goal.is_active());
+        goal.set_progress(0.8);
+        assert!(goal.is_active());
+        assert!(goal.completed_at.is_none());
+
+        context.goal_mut("2").unwrap().complete();
+        let data = context.prompt_data("q", &crate::intent::keyword_intent("q", &[]), &[]);
+        assert_eq!(data["goals"].as_array().unwrap().len(), 1);
+        assert_eq!(data["goals"][0]["linked_notes"], 1);
+        assert_eq!(data["completed_goals"][0], "Lead a project");
+
+        assert_eq!(context.remove_goal("1").unwrap().description, "Learn Rust");
+        assert_eq!(context.goals.len(), 1);
     }
 }
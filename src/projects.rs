@@ -0,0 +1,157 @@
+use crate::context::AppContext;
+use crate::personalization::ProjectContext;
+use crate::prompts;
+use crate::thinking;
+use chrono::DateTime;
+use serde_json::{json, Value};
+
+/// Recent project notes the LLM reads when looking for challenges.
+const NOTES_FOR_CHALLENGES: usize = 20;
+
+/// Challenges kept per project; older ones are dropped first.
+pub const MAX_CHALLENGES: usize = 8;
+
+/// Asks the LLM which challenges the project's recent notes describe.
+pub fn extract_challenges(
+    ctx: &AppContext,
+    project: &ProjectContext,
+) -> Result<Vec<String>, String> {
+    ctx.check_generation()?;
+    let notes = ctx
+        .store
+        .recent_notes(NOTES_FOR_CHALLENGES, Some(&project.name))?;
+    if notes.is_empty() {
+        return Err(format!(
+            "No notes are tagged with '{}' yet. Run 'logswise-cli project use {}' before adding notes.",
+            project.name, project.name
+        ));
+    }
+
+    let data = json!({
+        "profile": prompts::profile_data(&ctx.config),
+        "project": {
+            "name": project.name,
+            "tech_stack": project.tech_stack,
+            "current_challenges": project.current_challenges,
+        },
+        "notes": notes
+            .iter()
+            .map(|n| json!({
+                "content": n.content,
+                "date": ctx.zone().day_label(&n.created_at),
+            }))
+            .collect::<Vec<Value>>(),
+    });
+    let reply = ctx
+        .llm
+        .generate(&ctx.prompts.render("project_challenges", &data))?;
+    Ok(parse_challenges(&reply))
+}
+
+/// True when notes tagged with `project` were written after its challenges were last
+/// extracted, so they are worth extracting again.
+pub fn challenges_stale(ctx: &AppContext, project: &ProjectContext) -> Result<bool, String> {
+    let newest = ctx.store.recent_notes(1, Some(&project.name))?;
+    let newest = newest
+        .first()
+        .and_then(|n| DateTime::parse_from_rfc3339(&n.created_at).ok());
+    Ok(newest.is_some_and(|t| project.challenges_updated_at.is_none_or(|u| t > u)))
+}
+
+/// Reads the `- challenge` lines from the model's reply; `NONE` means no challenges.
+fn parse_challenges(reply: &str) -> Vec<String> {
+    let answer = thinking::strip(reply);
+    answer
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let item = line
+                .strip_prefix("- ")
+                .or_else(|| line.strip_prefix("* "))
+                .or_else(|| {
+                    let rest = line.trim_start_matches(|c: char| c.is_ascii_digit());
+                    (rest.len() < line.len())
+                        .then(|| rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")))
+                        .flatten()
+                })?;
+            let item = item.trim().trim_end_matches('.').trim();
+            (!item.is_empty() && !item.eq_ignore_ascii_case("none")).then(|| item.to_string())
+        })
+        .take(5)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::{context, fake_llm, note, FakeStore};
+
+    #[test]
+    fn test_parse_challenges() {
+        let reply = "<think>...</think>\nHere you go:\n- Flaky CI on main.\n2. Unclear API ownership\n* \nNONE";
+        assert_eq!(
+            parse_challenges(reply),
+            vec!["Flaky CI on main", "Unclear API ownership"]
+        );
+        assert!(parse_challenges("NONE").is_empty());
+    }
+
+    #[test]
+    fn test_extract_challenges_reads_project_notes_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let llm = fake_llm("llama3", "- Slow test suite");
+        let prompts = llm.prompts.clone();
+        let store = FakeStore::default();
+        let mut tagged = note("1", "Test suite takes 40 minutes");
+        tagged.project = Some("Atlas".to_string());
+        store
+            .notes
+            .borrow_mut()
+            .extend([tagged, note("2", "Untagged lunch plans")]);
+        let ctx = context(llm, store, dir.path());
+
+        let mut project = ProjectContext::new("Atlas".to_string());
+        project.current_challenges = vec!["Hiring".to_string()];
+        let found = extract_challenges(&ctx, &project).unwrap();
+        assert_eq!(found, vec!["Slow test suite"]);
+        let prompt = &prompts.borrow()[0];
+        assert!(prompt.contains("Test suite takes 40 minutes"));
+        assert!(!prompt.contains("lunch"));
+        assert!(prompt.contains("Challenges already recorded:\n- Hiring"));
+
+        assert_eq!(project.merge_challenges(found, 2), 1);
+        assert_eq!(
+            project.merge_challenges(vec!["slow TEST suite".into()], 2),
+            0
+        );
+        assert_eq!(
+            project.current_challenges,
+            vec!["Hiring", "Slow test suite"]
+        );
+        project.merge_challenges(vec!["Scope creep".into()], 2);
+        assert_eq!(
+            project.current_challenges,
+            vec!["Slow test suite", "Scope creep"]
+        );
+
+        assert!(extract_challenges(&ctx, &ProjectContext::new("Other".into())).is_err());
+    }
+
+    #[test]
+    fn test_challenges_stale_after_new_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FakeStore::default();
+        let mut tagged = note("1", "Release slipped again");
+        tagged.project = Some("Atlas".to_string());
+        store.notes.borrow_mut().push(tagged);
+        let ctx = context(fake_llm("llama3", ""), store, dir.path());
+
+        let mut project = ProjectContext::new("Atlas".to_string());
+        assert!(challenges_stale(&ctx, &project).unwrap());
+        project.challenges_updated_at = "2026-10-02T09:00:00Z".parse().ok();
+        assert!(!challenges_stale(&ctx, &project).unwrap());
+        project.challenges_updated_at = "2026-09-30T09:00:00Z".parse().ok();
+        assert!(challenges_stale(&ctx, &project).unwrap());
+        assert!(!challenges_stale(&ctx, &ProjectContext::new("Other".into())).unwrap());
+    }
+}
@@ -0,0 +1,458 @@
+use crate::config::Config;
+use colored::*;
+use dirs::home_dir;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+
+/// A prompt that ships with the CLI and can be overridden by the user.
+pub struct BuiltinPrompt {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub source: &'static str,
+}
+
+pub const BUILTIN_PROMPTS: [BuiltinPrompt; 7] = [
+    BuiltinPrompt {
+        name: "chat",
+        description: "Prompt for `chat`: profile, relevant notes, earlier turns and the message",
+        source: include_str!("prompts/chat.tmpl"),
+    },
+    BuiltinPrompt {
+        name: "suggestion",
+        description: "Prompt for `suggestion`: full personalization context and response rules",
+        source: include_str!("prompts/suggestion.tmpl"),
+    },
+    BuiltinPrompt {
+        name: "goal_progress",
+        description: "Prompt for `goal update --estimate`: a goal and its linked notes",
+        source: include_str!("prompts/goal_progress.tmpl"),
+    },
+    BuiltinPrompt {
+        name: "project_challenges",
+        description: "Prompt for `project challenges`: a project and its recent notes",
+        source: include_str!("prompts/project_challenges.tmpl"),
+    },
+    BuiltinPrompt {
+        name: "report",
+        description: "Prompt for `report`: a period's notes grouped by project or tag",
+        source: include_str!("prompts/report.tmpl"),
+    },
+    BuiltinPrompt {
+        name: "report_map",
+        description:
+            "Prompt for `report` periods too long for one prompt: condenses part of a group",
+        source: include_str!("prompts/report_map.tmpl"),
+    },
+    BuiltinPrompt {
+        name: "standup",
+        description: "Prompt for `standup`: notes since the last working day",
+        source: include_str!("prompts/standup.tmpl"),
+    },
+];
+
+pub fn builtin(name: &str) -> Option<&'static BuiltinPrompt> {
+    BUILTIN_PROMPTS.iter().find(|p| p.name == name)
+}
+
+/// Prompt templates, read from `~/.logswise/prompts/<name>.tmpl` when present and
+/// from the built-in defaults otherwise.
+pub struct Prompts {
+    dir: PathBuf,
+}
+
+impl Prompts {
+    pub fn open_default() -> Result<Self, String> {
+        let mut dir = home_dir().ok_or("Could not determine home directory")?;
+        dir.push(".logswise/prompts");
+        Ok(Self::at(dir))
+    }
+
+    pub fn at(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.tmpl"))
+    }
+
+    pub fn is_customized(&self, name: &str) -> bool {
+        self.path(name).exists()
+    }
+
+    /// Returns the template source in effect for `name`.
+    pub fn source(&self, name: &str) -> Result<String, String> {
+        let builtin = builtin(name).ok_or_else(|| unknown_prompt(name))?;
+        match fs::read_to_string(self.path(name)) {
+            Ok(source) => Ok(source),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(builtin.source.to_string()),
+            Err(e) => Err(format!("Failed to read prompt '{name}': {e}")),
+        }
+    }
+
+    /// Renders the prompt `name` with `data`.
+    ///
+    /// A user template that cannot be read or parsed is reported and the built-in one used,
+    /// so a typo never breaks chat or suggestions.
+    pub fn render(&self, name: &str, data: &Value) -> String {
+        let builtin = builtin(name).unwrap_or_else(|| panic!("no built-in prompt '{name}'"));
+        let template = self
+            .source(name)
+            .and_then(|source| Template::parse(&source))
+            .unwrap_or_else(|e| {
+                eprintln!(
+                    "{}",
+                    format!("⚠️  Prompt '{name}' is invalid ({e}); using the built-in prompt.")
+                        .yellow()
+                );
+                Template::parse(builtin.source).expect("built-in prompts are valid")
+            });
+        template.render(data).trim_end().to_string()
+    }
+
+    /// Copies the built-in template to the user directory unless it was already customized.
+    pub fn ensure_file(&self, name: &str) -> Result<PathBuf, String> {
+        let builtin = builtin(name).ok_or_else(|| unknown_prompt(name))?;
+        let path = self.path(name);
+        if !path.exists() {
+            fs::create_dir_all(&self.dir)
+                .map_err(|e| format!("Failed to create directory: {e}"))?;
+            fs::write(&path, builtin.source)
+                .map_err(|e| format!("Failed to write prompt '{name}': {e}"))?;
+        }
+        Ok(path)
+    }
+
+    /// Removes the user's copy of `name`. Returns false when it was not customized.
+    pub fn reset(&self, name: &str) -> Result<bool, String> {
+        builtin(name).ok_or_else(|| unknown_prompt(name))?;
+        match fs::remove_file(self.path(name)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(format!("Failed to reset prompt '{name}': {e}")),
+        }
+    }
+}
+
+fn unknown_prompt(name: &str) -> String {
+    let names: Vec<&str> = BUILTIN_PROMPTS.iter().map(|p| p.name).collect();
+    format!(
+        "Unknown prompt '{name}'. Available prompts: {}",
+        names.join(", ")
+    )
+}
+
+/// Profile fields available to every template as `profile.*`.
+pub fn profile_data(config: &Config) -> Value {
+    json!({
+        "profession": config.profession,
+        "job_title": config.job_title,
+        "company_name": config.company_name,
+        "company_size": config.company_size,
+        "years_experience": config.years_experience,
+        "preferred_language": config.preferred_language,
+        "work_mode": config.work_mode,
+    })
+}
+
+/// A parsed prompt template.
+///
+/// The syntax is a small subset of Mustache:
+/// - `{{path.to.value}}` inserts a value (lists are joined with `, `);
+///   `{{value|fallback}}` inserts `fallback` when the value is empty
+/// - `{{#name}}...{{/name}}` repeats for every item of a list, or renders once when the
+///   value is set; `{{^name}}...{{/name}}` renders when it is empty or false
+/// - inside a list, `{{.}}` is the current item and `{{@number}}`, `{{@first}}` and
+///   `{{@last}}` describe its position
+/// - `{{! comment }}` is ignored
+///
+/// Tags must not span lines. Lines holding only a section tag or comment are dropped.
+#[derive(Debug)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Var {
+        path: String,
+        fallback: Option<String>,
+    },
+    Section {
+        path: String,
+        inverted: bool,
+        children: Vec<Node>,
+    },
+}
+
+enum Token {
+    Text(String),
+    Var(String, Option<String>),
+    Open(String, bool),
+    Close(String),
+    Comment,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut tokens = vec![];
+        for (i, line) in source.split_inclusive('\n').enumerate() {
+            let line_tokens = tokenize_line(line).map_err(|e| format!("line {}: {e}", i + 1))?;
+            let tags: Vec<&Token> = line_tokens
+                .iter()
+                .filter(|t| !matches!(t, Token::Text(_)))
+                .collect();
+            let standalone = tags.len() == 1
+                && !matches!(tags[0], Token::Var(..))
+                && line_tokens
+                    .iter()
+                    .all(|t| !matches!(t, Token::Text(s) if !s.trim().is_empty()));
+            if standalone {
+                tokens.extend(
+                    line_tokens
+                        .into_iter()
+                        .filter(|t| !matches!(t, Token::Text(_))),
+                );
+            } else {
+                tokens.extend(line_tokens);
+            }
+        }
+
+        // Assemble sections into a tree
+        let mut stack: Vec<(String, bool, Vec<Node>)> = vec![(String::new(), false, vec![])];
+        for token in tokens {
+            match token {
+                Token::Text(text) => stack.last_mut().unwrap().2.push(Node::Text(text)),
+                Token::Var(path, fallback) => stack
+                    .last_mut()
+                    .unwrap()
+                    .2
+                    .push(Node::Var { path, fallback }),
+                Token::Open(path, inverted) => stack.push((path, inverted, vec![])),
+                Token::Close(path) => {
+                    if stack.len() == 1 || stack.last().unwrap().0 != path {
+                        return Err(format!("unexpected {{{{/{path}}}}}"));
+                    }
+                    let (path, inverted, children) = stack.pop().unwrap();
+                    stack.last_mut().unwrap().2.push(Node::Section {
+                        path,
+                        inverted,
+                        children,
+                    });
+                }
+                Token::Comment => {}
+            }
+        }
+        if stack.len() > 1 {
+            return Err(format!(
+                "section '{}' is never closed",
+                stack.last().unwrap().0
+            ));
+        }
+        Ok(Self {
+            nodes: stack.pop().unwrap().2,
+        })
+    }
+
+    pub fn render(&self, data: &Value) -> String {
+        let mut out = String::new();
+        let mut stack = vec![Frame {
+            value: data.clone(),
+            position: None,
+        }];
+        render_nodes(&self.nodes, &mut stack, &mut out);
+        out
+    }
+}
+
+fn tokenize_line(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut rest = line;
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            tokens.push(Token::Text(rest[..start].to_string()));
+        }
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or("'{{' without matching '}}'")?;
+        tokens.push(parse_tag(after[..end].trim())?);
+        rest = &after[end + 2..];
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+    Ok(tokens)
+}
+
+fn parse_tag(tag: &str) -> Result<Token, String> {
+    if tag.starts_with('!') {
+        return Ok(Token::Comment);
+    }
+    let (kind, name) = match tag.chars().next() {
+        Some(c @ ('#' | '^' | '/')) => (Some(c), tag[1..].trim()),
+        _ => (None, tag),
+    };
+    if name.is_empty() {
+        return Err(format!("empty tag '{{{{{tag}}}}}'"));
+    }
+    Ok(match kind {
+        Some('#') => Token::Open(name.to_string(), false),
+        Some('^') => Token::Open(name.to_string(), true),
+        Some(_) => Token::Close(name.to_string()),
+        None => match name.split_once('|') {
+            Some((path, fallback)) => {
+                Token::Var(path.trim().to_string(), Some(fallback.trim().to_string()))
+            }
+            None => Token::Var(name.to_string(), None),
+        },
+    })
+}
+
+struct Frame {
+    value: Value,
+    /// Index and length when this frame is an item of a list section.
+    position: Option<(usize, usize)>,
+}
+
+fn render_nodes(nodes: &[Node], stack: &mut Vec<Frame>, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var { path, fallback } => {
+                let value = lookup(path, stack).unwrap_or(Value::Null);
+                match fallback {
+                    Some(fallback) if !truthy(&value) => out.push_str(fallback),
+                    _ => out.push_str(&display(&value)),
+                }
+            }
+            Node::Section {
+                path,
+                inverted,
+                children,
+            } => {
+                let value = lookup(path, stack).unwrap_or(Value::Null);
+                if *inverted {
+                    if !truthy(&value) {
+                        render_nodes(children, stack, out);
+                    }
+                } else if let Value::Array(items) = &value {
+                    for (i, item) in items.iter().enumerate() {
+                        stack.push(Frame {
+                            value: item.clone(),
+                            position: Some((i, items.len())),
+                        });
+                        render_nodes(children, stack, out);
+                        stack.pop();
+                    }
+                } else if truthy(&value) {
+                    stack.push(Frame {
+                        value,
+                        position: None,
+                    });
+                    render_nodes(children, stack, out);
+                    stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Resolves `path` against the innermost frame that defines its first segment.
+fn lookup(path: &str, stack: &[Frame]) -> Option<Value> {
+    if path == "." {
+        return stack.last().map(|f| f.value.clone());
+    }
+    if let Some(meta) = path.strip_prefix('@') {
+        let (index, len) = stack.iter().rev().find_map(|f| f.position)?;
+        return match meta {
+            "number" => Some(Value::from(index + 1)),
+            "index" => Some(Value::from(index)),
+            "first" => Some(Value::Bool(index == 0)),
+            "last" => Some(Value::Bool(index + 1 == len)),
+            _ => None,
+        };
+    }
+    let mut parts = path.split('.');
+    let first = parts.next()?;
+    let mut value = stack.iter().rev().find_map(|f| f.value.get(first))?;
+    for part in parts {
+        value = value.get(part)?;
+    }
+    Some(value.clone())
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        _ => true,
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Array(items) => items.iter().map(display).collect::<Vec<_>>().join(", "),
+        Value::Null | Value::Object(_) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_variables_sections_and_fallbacks() {
+        let template = Template::parse(
+            "{{! header }}\nHi {{user.name|there}} ({{tags}})\n{{#notes}}\n{{@number}}. {{text}}{{^@last}};{{/@last}}\n{{/notes}}\n{{^notes}}\nNo notes\n{{/notes}}\nEnd",
+        )
+        .unwrap();
+
+        let out = template.render(&json!({
+            "user": { "name": "Ada" },
+            "tags": ["rust", "cli"],
+            "notes": [{ "text": "one" }, { "text": "two" }]
+        }));
+        assert_eq!(out, "Hi Ada (rust, cli)\n1. one;\n2. two\nEnd");
+
+        let out = template.render(&json!({ "user": { "name": "" }, "notes": [] }));
+        assert_eq!(out, "Hi there ()\nNo notes\nEnd");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Template::parse("{{#a}}open")
+            .unwrap_err()
+            .contains("never closed"));
+        assert!(Template::parse("{{/a}}")
+            .unwrap_err()
+            .contains("unexpected"));
+        assert!(Template::parse("ok\n{{broken")
+            .unwrap_err()
+            .starts_with("line 2"));
+    }
+
+    #[test]
+    fn test_user_template_overrides_builtin_and_falls_back_when_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let prompts = Prompts::at(dir.path().to_path_buf());
+        let data = json!({ "message": "hello", "profile": {}, "notes": [] });
+        assert!(prompts
+            .render("chat", &data)
+            .ends_with("User: hello\nAssistant:"));
+
+        fs::write(prompts.path("chat"), "Q: {{message}}").unwrap();
+        assert_eq!(prompts.render("chat", &data), "Q: hello");
+
+        fs::write(prompts.path("chat"), "{{#message}}").unwrap();
+        assert!(prompts.render("chat", &data).starts_with("User Info:"));
+
+        assert!(prompts.reset("chat").unwrap());
+        assert!(!prompts.is_customized("chat"));
+        assert!(prompts.reset("nope").is_err());
+    }
+}
@@ -0,0 +1,278 @@
+use crate::context::AppContext;
+use crate::dates::{DateRange, Zone};
+use crate::prompts;
+use crate::thinking;
+use crate::tokens;
+use crate::types::Note;
+use serde_json::{json, Value};
+
+/// Tokens kept free for the template text and the model's answer.
+const PROMPT_RESERVE: usize = 1600;
+
+/// Smallest budget worth summarising with; below it every chunk would be one note.
+const MIN_BUDGET: usize = 256;
+
+/// Reduce passes before giving up on fitting the notes into the context window.
+const MAX_PASSES: usize = 4;
+
+/// Group name for notes without a project or hashtag.
+pub const UNGROUPED: &str = "General";
+
+/// A generated accomplishments summary.
+pub struct Report {
+    /// Markdown body written by the model.
+    pub markdown: String,
+    pub note_count: usize,
+    pub group_count: usize,
+    /// Summarisation passes needed before the notes fit in one prompt (0 if they did).
+    pub passes: usize,
+}
+
+/// Notes (or summaries of them) that belong to one project or tag.
+pub struct Group {
+    pub name: String,
+    pub items: Vec<String>,
+}
+
+/// Note tokens that fit in one prompt for a model with `context_tokens` of context.
+pub fn note_budget(context_tokens: usize) -> usize {
+    context_tokens
+        .saturating_sub(PROMPT_RESERVE)
+        .max(MIN_BUDGET)
+}
+
+/// Fetches the notes in `range` and asks the LLM for an accomplishments summary.
+///
+/// `progress` receives a short status line before each LLM call.
+pub fn generate_report(
+    ctx: &AppContext,
+    range: &DateRange,
+    project: Option<&str>,
+    progress: &dyn Fn(&str),
+) -> Result<Report, String> {
+    ctx.check_generation()?;
+    progress("Fetching notes...");
+    let zone = ctx.zone();
+    let notes = ctx
+        .store
+        .notes_between(range.start_utc(zone), range.end_utc(zone), project)?;
+    if notes.is_empty() {
+        return Err(format!("No notes found for {}.", range.label()));
+    }
+    build_report(
+        ctx,
+        &notes,
+        range,
+        note_budget(ctx.context_tokens()),
+        progress,
+    )
+}
+
+/// Summarises `notes` in one prompt when they fit in `budget` tokens, otherwise
+/// condenses each group chunk by chunk (map) and repeats until they do (reduce).
+pub fn build_report(
+    ctx: &AppContext,
+    notes: &[Note],
+    range: &DateRange,
+    budget: usize,
+    progress: &dyn Fn(&str),
+) -> Result<Report, String> {
+    let mut groups = group_notes(notes, ctx.zone());
+    let mut passes = 0;
+    let mut size = total_tokens(&groups);
+    while size > budget {
+        if passes == MAX_PASSES {
+            return Err(format!(
+                "Too many notes to summarise for {}. Try a shorter period or --project.",
+                range.label()
+            ));
+        }
+        passes += 1;
+        for group in &mut groups {
+            let chunks = tokens::chunk_by_budget(std::mem::take(&mut group.items), budget, |i| {
+                tokens::estimate(i)
+            });
+            let count = chunks.len();
+            for (i, chunk) in chunks.into_iter().enumerate() {
+                progress(&format!(
+                    "Summarising {} (pass {passes}, part {}/{count})...",
+                    group.name,
+                    i + 1
+                ));
+                let data = json!({
+                    "profile": prompts::profile_data(&ctx.config),
+                    "period": range.label(),
+                    "group": group.name,
+                    "items": chunk,
+                });
+                let reply = ctx.llm.generate(&ctx.prompts.render("report_map", &data))?;
+                group.items.extend(parse_bullets(&reply));
+            }
+        }
+        let reduced = total_tokens(&groups);
+        if reduced >= size {
+            return Err(
+                "The model's summaries did not get shorter; try a shorter period.".to_string(),
+            );
+        }
+        size = reduced;
+    }
+
+    progress("Writing the report...");
+    let data = json!({
+        "profile": prompts::profile_data(&ctx.config),
+        "period": range.label(),
+        "note_count": notes.len(),
+        "summarized": passes > 0,
+        "groups": groups
+            .iter()
+            .map(|g| json!({ "name": g.name, "items": g.items }))
+            .collect::<Vec<Value>>(),
+    });
+    let reply = ctx.llm.generate(&ctx.prompts.render("report", &data))?;
+    Ok(Report {
+        markdown: thinking::strip(&reply),
+        note_count: notes.len(),
+        group_count: groups.len(),
+        passes,
+    })
+}
+
+/// Groups notes by project, then by their first `#tag`, keeping first-seen order.
+pub fn group_notes(notes: &[Note], zone: Zone) -> Vec<Group> {
+    let mut groups: Vec<Group> = vec![];
+    for note in notes {
+        let name = note
+            .project
+            .clone()
+            .or_else(|| first_tag(&note.content))
+            .unwrap_or_else(|| UNGROUPED.to_string());
+        let date = zone.day_label(&note.created_at);
+        let item = format!("[{date}] {}", note.content.trim());
+        match groups
+            .iter_mut()
+            .find(|g| g.name.eq_ignore_ascii_case(&name))
+        {
+            Some(group) => group.items.push(item),
+            None => groups.push(Group {
+                name,
+                items: vec![item],
+            }),
+        }
+    }
+    groups
+}
+
+fn first_tag(content: &str) -> Option<String> {
+    content
+        .split_whitespace()
+        .filter_map(|word| word.strip_prefix('#'))
+        .map(|tag| tag.trim_end_matches(|c: char| !c.is_alphanumeric()))
+        .find(|tag| !tag.is_empty())
+        .map(|tag| format!("#{tag}"))
+}
+
+fn total_tokens(groups: &[Group]) -> usize {
+    groups
+        .iter()
+        .map(|g| {
+            tokens::estimate(&g.name) + g.items.iter().map(|i| tokens::estimate(i)).sum::<usize>()
+        })
+        .sum()
+}
+
+/// Reads the `- item` lines of a partial summary; a reply without bullets is kept whole.
+fn parse_bullets(reply: &str) -> Vec<String> {
+    let answer = thinking::strip(reply);
+    let bullets: Vec<String> = answer
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            line.strip_prefix("- ").or_else(|| line.strip_prefix("* "))
+        })
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect();
+    if bullets.is_empty() && !answer.trim().is_empty() {
+        vec![answer.trim().to_string()]
+    } else {
+        bullets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::{context, fake_llm, note, FakeStore};
+    use crate::dates::{parse_date, Period};
+
+    fn range() -> DateRange {
+        DateRange::for_period(Period::Week, parse_date("2026-10-19").unwrap(), None, None).unwrap()
+    }
+
+    fn notes() -> Vec<Note> {
+        let mut atlas = note("1", "Shipped the billing export");
+        atlas.project = Some("Atlas".to_string());
+        vec![
+            atlas,
+            note("2", "Paired on the #oncall runbook."),
+            note("3", "Read a paper on CRDTs"),
+        ]
+    }
+
+    #[test]
+    fn test_groups_by_project_then_tag() {
+        let groups = group_notes(&notes(), Zone::parse("UTC").unwrap());
+        let names: Vec<&str> = groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec!["Atlas", "#oncall", "General"]);
+        assert!(groups[0].items[0].ends_with("Shipped the billing export"));
+        assert_eq!(
+            parse_bullets("<think>x</think>\n- One\n* Two\n"),
+            vec!["One", "Two"]
+        );
+        assert_eq!(parse_bullets("Just prose"), vec!["Just prose"]);
+    }
+
+    #[test]
+    fn test_report_fits_in_one_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        let llm = fake_llm("llama3", "## Highlights\n- Shipped billing export");
+        let prompts = llm.prompts.clone();
+        let ctx = context(llm, FakeStore::default(), dir.path());
+
+        let report = build_report(&ctx, &notes(), &range(), 4096, &|_| {}).unwrap();
+        assert_eq!(report.passes, 0);
+        assert_eq!(report.note_count, 3);
+        assert_eq!(report.group_count, 3);
+        assert!(report.markdown.starts_with("## Highlights"));
+        let prompts = prompts.borrow();
+        assert_eq!(prompts.len(), 1);
+        assert!(prompts[0].contains("### Atlas"));
+        assert!(prompts[0].contains("Read a paper on CRDTs"));
+    }
+
+    #[test]
+    fn test_report_map_reduces_large_periods() {
+        let dir = tempfile::tempdir().unwrap();
+        let llm = fake_llm("llama3", "- Did things");
+        let prompts = llm.prompts.clone();
+        let ctx = context(llm, FakeStore::default(), dir.path());
+        let many: Vec<Note> = (0..12)
+            .map(|i| {
+                note(
+                    &i.to_string(),
+                    &format!("Long note number {i} {}", "x".repeat(80)),
+                )
+            })
+            .collect();
+
+        let report = build_report(&ctx, &many, &range(), 90, &|_| {}).unwrap();
+        assert_eq!(report.passes, 1);
+        let prompts = prompts.borrow();
+        // Each map chunk fits three notes, then one final prompt
+        assert_eq!(prompts.len(), 5);
+        assert!(prompts[0].contains("Long note number 0"));
+        assert!(!prompts[4].contains("Long note number"));
+        assert!(prompts[4].contains("Did things"));
+    }
+}
@@ -1,17 +1,42 @@
-use crate::cli::{Commands, PersonalizeAction};
+use crate::cli::{
+    Commands, GoalAction, ModelsAction, PersonalizeAction, ProjectAction, PromptAction,
+    SuggestionAction,
+};
+use crate::context::AppContext;
+use crate::generation::GenerationOptions;
 use crate::handlers::{
-    chat::ChatHandler, help::HelpHandler, interactive::InteractiveHandler, note::NoteHandler,
-    personalization::PersonalizationHandler, setup::SetupHandler, suggestion::SuggestionHandler,
+    chat::ChatHandler,
+    goal::{GoalChanges, GoalHandler},
+    help::HelpHandler,
+    interactive::InteractiveHandler,
+    models::ModelsHandler,
+    note::NoteHandler,
+    personalization::PersonalizationHandler,
+    project::{ProjectChanges, ProjectHandler},
+    prompt::PromptHandler,
+    report::{ReportHandler, ReportOptions},
+    setup::SetupHandler,
+    standup::StandupHandler,
+    suggestion::SuggestionHandler,
     system::SystemHandler,
 };
+use crate::note_handler::NoteFilters;
+use crate::suggestion_handler::FeedbackInput;
+use colored::*;
 
 pub struct CommandRouter {
     chat_handler: ChatHandler,
+    goal_handler: GoalHandler,
     help_handler: HelpHandler,
     interactive_handler: InteractiveHandler,
+    models_handler: ModelsHandler,
     note_handler: NoteHandler,
     personalization_handler: PersonalizationHandler,
+    project_handler: ProjectHandler,
+    prompt_handler: PromptHandler,
+    report_handler: ReportHandler,
     setup_handler: SetupHandler,
+    standup_handler: StandupHandler,
     suggestion_handler: SuggestionHandler,
     system_handler: SystemHandler,
 }
@@ -20,40 +45,175 @@ impl CommandRouter {
     pub fn new() -> Self {
         Self {
             chat_handler: ChatHandler::new(),
+            goal_handler: GoalHandler::new(),
             help_handler: HelpHandler::new(),
             interactive_handler: InteractiveHandler::new(),
+            models_handler: ModelsHandler::new(),
             note_handler: NoteHandler::new(),
             personalization_handler: PersonalizationHandler::new(),
+            project_handler: ProjectHandler::new(),
+            prompt_handler: PromptHandler::new(),
+            report_handler: ReportHandler::new(),
             setup_handler: SetupHandler::new(),
+            standup_handler: StandupHandler::new(),
             suggestion_handler: SuggestionHandler::new(),
             system_handler: SystemHandler::new(),
         }
     }
 
+    /// Loads the shared application context, explaining how to fix a missing setup.
+    fn load_context(&self) -> Option<AppContext> {
+        match AppContext::load() {
+            Ok(ctx) => Some(ctx),
+            Err(e) => {
+                println!("{}", format!("Error loading configuration: {e}").red());
+                println!("Please run 'logswise-cli setup' first.");
+                None
+            }
+        }
+    }
+
+    /// Runs `f` with the shared context after flushing any notes queued while offline.
+    fn with_context<F: FnOnce(&AppContext)>(&self, f: F) {
+        if let Some(ctx) = self.load_context() {
+            self.note_handler.auto_sync(&ctx);
+            f(&ctx);
+        }
+    }
+
+    /// Like [`Self::with_context`], with the generation settings for `command` applied.
+    fn with_generation<F: FnOnce(&AppContext)>(
+        &self,
+        command: &str,
+        flags: &GenerationOptions,
+        f: F,
+    ) {
+        self.with_context_mut(|ctx| match ctx.configure_generation(command, flags) {
+            Ok(()) => f(ctx),
+            Err(e) => println!("{}", format!("❌ {e}").red()),
+        });
+    }
+
+    /// Like [`Self::with_context`], for long-running sessions that change the context.
+    fn with_context_mut<F: FnOnce(&mut AppContext)>(&self, f: F) {
+        if let Some(mut ctx) = self.load_context() {
+            self.note_handler.auto_sync(&ctx);
+            f(&mut ctx);
+        }
+    }
+
     pub fn route(&self, command: Commands) {
         match command {
             // Setup and onboarding
             Commands::Setup => self.setup_handler.run_setup(),
 
             // Note-related commands
-            Commands::Note { content } | Commands::N { content } => {
-                self.note_handler.add_note(&content);
+            Commands::Note { content, goal } | Commands::N { content, goal } => {
+                self.with_context(|ctx| self.note_handler.add_note(ctx, &content, goal.as_deref()));
+            }
+            Commands::Recent {
+                count,
+                all,
+                since,
+                until,
+            } => {
+                let filters = NoteFilters { all, since, until };
+                self.with_context(|ctx| self.note_handler.show_recent_notes(ctx, count, &filters));
+            }
+            Commands::Search {
+                text,
+                count,
+                all,
+                since,
+                until,
+            } => {
+                let filters = NoteFilters { all, since, until };
+                self.with_context(|ctx| self.note_handler.search(ctx, &text, count, &filters));
             }
-            Commands::Recent { count } => {
-                self.note_handler.show_recent_notes(count);
+            Commands::Export {
+                format,
+                output,
+                all,
+                since,
+                until,
+            } => {
+                let filters = NoteFilters { all, since, until };
+                self.with_context(|ctx| {
+                    self.note_handler
+                        .export(ctx, format, &filters, output.as_deref())
+                });
+            }
+            Commands::Sync => {
+                if let Some(ctx) = self.load_context() {
+                    self.note_handler.sync(&ctx);
+                }
             }
 
             // AI interaction commands
-            Commands::Suggestion { query } | Commands::S { query } => {
-                self.suggestion_handler.get_suggestions(&query);
+            Commands::Suggestion(args) | Commands::S(args) => {
+                let flags = args.generation.options();
+                self.with_generation("suggestion", &flags, |ctx| {
+                    match (args.action, args.query) {
+                        (Some(SuggestionAction::History { count, unrated }), _) => {
+                            self.suggestion_handler.history(ctx, count, unrated)
+                        }
+                        (Some(SuggestionAction::Show { id }), _) => {
+                            self.suggestion_handler.show(ctx, id.as_deref())
+                        }
+                        (None, query) => self.suggestion_handler.get_suggestions(
+                            ctx,
+                            query.as_deref().unwrap_or_default(),
+                            args.explain,
+                        ),
+                    }
+                })
+            }
+            Commands::Feedback {
+                id,
+                rating,
+                helpful,
+                not_helpful,
+                reasons,
+                comment,
+            } => {
+                let input = FeedbackInput {
+                    helpful: (helpful || not_helpful).then_some(helpful),
+                    rating,
+                    reasons,
+                    comment,
+                };
+                self.with_context(|ctx| {
+                    self.suggestion_handler.feedback(ctx, id.as_deref(), input)
+                });
+            }
+            Commands::Chat {
+                message,
+                explain,
+                generation,
             }
-            Commands::Chat { message } | Commands::C { message } => {
-                self.chat_handler.chat_with_assistant(&message);
+            | Commands::C {
+                message,
+                explain,
+                generation,
+            } => {
+                self.with_generation("chat", &generation.options(), |ctx| {
+                    self.chat_handler
+                        .chat_with_assistant(ctx, &message, explain)
+                });
             }
 
             // Interactive mode
             Commands::Interactive => {
-                self.interactive_handler.run();
+                self.with_context_mut(|ctx| {
+                    // Interactive sessions are chats, so they share chat's settings
+                    match ctx.configure_generation("chat", &GenerationOptions::default()) {
+                        Ok(()) => self.interactive_handler.run(ctx),
+                        Err(e) => println!("{}", format!("❌ {e}").red()),
+                    }
+                });
+            }
+            Commands::Tui => {
+                self.with_context(|ctx| self.interactive_handler.run_tui(ctx));
             }
 
             // Personalization commands
@@ -68,9 +228,18 @@ impl CommandRouter {
                     Some(PersonalizeAction::Show) => {
                         self.personalization_handler.show_personalization();
                     }
-                    Some(PersonalizeAction::Feedback { category }) => {
-                        self.personalization_handler.feedback(category);
+                    Some(PersonalizeAction::Feedback { id }) => self.with_context(|ctx| {
+                        self.suggestion_handler.feedback(
+                            ctx,
+                            id.as_deref(),
+                            FeedbackInput::default(),
+                        )
+                    }),
+                    Some(PersonalizeAction::Learn) => {
+                        self.with_context(|ctx| self.personalization_handler.learn(ctx))
                     }
+                    Some(PersonalizeAction::Explain { query }) => self
+                        .with_context(|ctx| self.suggestion_handler.explain(ctx, query.as_deref())),
                     None => {
                         // Default to showing current personalization
                         self.personalization_handler.show_personalization();
@@ -78,17 +247,170 @@ impl CommandRouter {
                 }
             }
 
+            // Projects
+            Commands::Project { action } => self.with_context(|ctx| match action {
+                ProjectAction::Add {
+                    name,
+                    tech,
+                    team_size,
+                    pressure,
+                    challenges,
+                } => self.project_handler.add(
+                    ctx,
+                    &name,
+                    ProjectChanges {
+                        tech,
+                        team_size,
+                        pressure,
+                        add_challenges: challenges,
+                        ..ProjectChanges::default()
+                    },
+                ),
+                ProjectAction::List { all } => self.project_handler.list(ctx, all),
+                ProjectAction::Show { name } => self.project_handler.show(ctx, name.as_deref()),
+                ProjectAction::Edit {
+                    name,
+                    rename,
+                    tech,
+                    team_size,
+                    pressure,
+                    add_challenges,
+                    remove_challenges,
+                } => self.project_handler.edit(
+                    ctx,
+                    &name,
+                    ProjectChanges {
+                        rename,
+                        tech,
+                        team_size,
+                        pressure,
+                        add_challenges,
+                        remove_challenges,
+                    },
+                ),
+                ProjectAction::Archive { name, undo } => {
+                    self.project_handler.archive(ctx, &name, undo)
+                }
+                ProjectAction::Use { name, clear } => {
+                    self.project_handler
+                        .use_project(ctx, name.as_deref(), clear)
+                }
+                ProjectAction::Challenges { name } => {
+                    self.project_handler.challenges(ctx, name.as_deref())
+                }
+            }),
+
+            // Goal tracking
+            Commands::Goal { action } => self.with_context(|ctx| match action {
+                GoalAction::Add {
+                    description,
+                    category,
+                    timeline,
+                } => self
+                    .goal_handler
+                    .add(ctx, &description, &category, &timeline),
+                GoalAction::List { all } => self.goal_handler.list(ctx, all),
+                GoalAction::Update {
+                    goal,
+                    progress,
+                    estimate,
+                    description,
+                    category,
+                    timeline,
+                } => self.goal_handler.update(
+                    ctx,
+                    &goal,
+                    GoalChanges {
+                        progress,
+                        estimate,
+                        description,
+                        category,
+                        timeline,
+                    },
+                ),
+                GoalAction::Complete { goal } => self.goal_handler.complete(ctx, &goal),
+                GoalAction::Remove { goal } => self.goal_handler.remove(ctx, &goal),
+                GoalAction::Link { goal, note } => self.goal_handler.link(ctx, &goal, &note),
+                GoalAction::Report => self.goal_handler.report(ctx),
+            }),
+
+            // Reports
+            Commands::Report {
+                period,
+                from,
+                to,
+                project,
+                output,
+            } => self.with_context(|ctx| {
+                self.report_handler.report(
+                    ctx,
+                    ReportOptions {
+                        period,
+                        from,
+                        to,
+                        project,
+                        output,
+                    },
+                )
+            }),
+
+            Commands::Standup { no_llm, all } => {
+                self.with_context(|ctx| self.standup_handler.standup(ctx, no_llm, all));
+            }
+
+            // Prompt templates
+            Commands::Prompt { action } => match action {
+                PromptAction::List => self.prompt_handler.list(),
+                PromptAction::Show { name, default } => self.prompt_handler.show(&name, default),
+                PromptAction::Edit { name } => self.prompt_handler.edit(&name),
+                PromptAction::Reset { name } => self.prompt_handler.reset(&name),
+            },
+
             // Information and help commands
             Commands::About => self.help_handler.print_about(),
             Commands::How => self.help_handler.print_how(),
-            Commands::Models => self.help_handler.print_models(),
+            Commands::Models { action, refresh } => match action {
+                None => {
+                    self.help_handler.print_models();
+                    self.with_context(|ctx| self.models_handler.list_models(ctx, refresh));
+                }
+                Some(ModelsAction::List { refresh: again }) => {
+                    self.with_context(|ctx| self.models_handler.list_models(ctx, refresh || again))
+                }
+                Some(ModelsAction::Pull { name }) => {
+                    self.with_context(|ctx| self.models_handler.pull(ctx, &name))
+                }
+                Some(ModelsAction::Remove { name, yes }) => {
+                    self.with_context(|ctx| self.models_handler.remove(ctx, &name, yes))
+                }
+                Some(ModelsAction::Info { name }) => {
+                    self.with_context(|ctx| self.models_handler.info(ctx, &name))
+                }
+                Some(ModelsAction::Set {
+                    llm,
+                    embedding,
+                    yes,
+                }) => self.with_context(|ctx| {
+                    self.models_handler
+                        .set(ctx, llm.as_deref(), embedding.as_deref(), yes)
+                }),
+            },
             Commands::Context => self.help_handler.print_context(),
             Commands::Guide => self.help_handler.print_guide(),
             Commands::Troubleshoot => self.help_handler.print_troubleshoot(),
 
             // System commands
-            Commands::Stats => self.system_handler.print_stats(),
-            Commands::Doctor => self.system_handler.run_doctor(),
+            Commands::Stats { since, json } => self
+                .with_context(|ctx| self.system_handler.print_stats(ctx, since.as_deref(), json)),
+            Commands::Doctor { fix, json, bundle } => {
+                let healthy = self.system_handler.run_doctor(fix, json);
+                if bundle {
+                    self.system_handler.write_bundle(json);
+                }
+                if !healthy {
+                    std::process::exit(1);
+                }
+            }
             Commands::Init => self.system_handler.run_init(),
             Commands::Completions { shell } => {
                 self.system_handler.generate_completions(&shell);
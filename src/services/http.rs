@@ -0,0 +1,299 @@
+use crate::config::Config;
+use chrono::DateTime;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::{Certificate, Proxy, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
+
+/// Longest we are willing to sleep between two attempts, even if the server asks for more.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Network settings shared by the Ollama and Supabase clients (`http` in setup.json).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HttpSettings {
+    /// Seconds allowed to establish a connection.
+    pub connect_timeout_secs: u64,
+    /// Seconds allowed for a whole request, including reading the response. Generation
+    /// and model downloads have their own, longer limits.
+    #[serde(alias = "readTimeoutSecs")]
+    pub request_timeout_secs: u64,
+    /// Seconds allowed for one generation, which can include loading the model.
+    pub generation_timeout_secs: u64,
+    /// Extra attempts made after a retryable failure.
+    pub max_retries: u32,
+    /// Delay before the first retry in milliseconds; doubled on every attempt.
+    pub backoff_base_ms: u64,
+    /// Proxy URL used for all requests (e.g. `http://proxy.local:3128`).
+    pub proxy: Option<String>,
+    /// Path to an extra PEM-encoded root certificate.
+    pub ca_cert_path: Option<String>,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 10,
+            request_timeout_secs: 120,
+            generation_timeout_secs: 1800,
+            max_retries: 3,
+            backoff_base_ms: 500,
+            proxy: None,
+            ca_cert_path: None,
+        }
+    }
+}
+
+/// Whether a request may safely be repeated after a failure whose outcome is unknown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Retry {
+    /// Retried on connection errors, timeouts, 429 and 5xx responses.
+    Idempotent,
+    /// Only retried when the server explicitly did not process it (429 and 503).
+    NonIdempotent,
+}
+
+/// HTTP client with configured timeouts, proxy and retry behaviour.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: Client,
+    settings: HttpSettings,
+}
+
+impl HttpClient {
+    pub fn new(settings: HttpSettings) -> Result<Self, String> {
+        let mut builder = Client::builder()
+            .connect_timeout(Duration::from_secs(settings.connect_timeout_secs))
+            .timeout(Duration::from_secs(settings.request_timeout_secs));
+
+        if let Some(proxy) = settings.proxy.as_deref().filter(|p| !p.trim().is_empty()) {
+            let proxy = Proxy::all(proxy).map_err(|e| format!("Invalid proxy '{proxy}': {e}"))?;
+            builder = builder.proxy(proxy);
+        }
+
+        if let Some(path) = settings
+            .ca_cert_path
+            .as_deref()
+            .filter(|p| !p.trim().is_empty())
+        {
+            let pem = std::fs::read(path)
+                .map_err(|e| format!("Could not read CA certificate '{path}': {e}"))?;
+            let cert = Certificate::from_pem(&pem)
+                .map_err(|e| format!("Invalid CA certificate '{path}': {e}"))?;
+            builder = builder.add_root_certificate(cert);
+        }
+
+        let client = builder
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+        Ok(Self { client, settings })
+    }
+
+    /// Builds a client from the `http` section of the configuration.
+    ///
+    /// Invalid proxy or certificate settings are reported and the defaults used instead,
+    /// so a typo in the config never makes every command unusable.
+    pub fn from_config(config: &Config) -> Self {
+        match Self::new(config.http.clone()) {
+            Ok(client) => client,
+            Err(e) => {
+                log::warn!("{e}. Falling back to default network settings.");
+                Self::default()
+            }
+        }
+    }
+
+    /// Time limit for generation requests, which outlast the general request timeout.
+    pub fn generation_timeout(&self) -> Duration {
+        Duration::from_secs(self.settings.generation_timeout_secs)
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.client.post(url)
+    }
+
+    pub fn patch(&self, url: &str) -> RequestBuilder {
+        self.client.patch(url)
+    }
+
+    pub fn delete(&self, url: &str) -> RequestBuilder {
+        self.client.delete(url)
+    }
+
+    /// Sends a request, retrying transient failures with jittered exponential backoff.
+    ///
+    /// A `Retry-After` header on 429/503 responses takes precedence over the computed delay.
+    pub fn send(&self, request: RequestBuilder, retry: Retry) -> reqwest::Result<Response> {
+        let target = describe(&request);
+        let mut attempt = 0;
+        loop {
+            // Requests with streaming bodies cannot be cloned, so they get a single attempt
+            let Some(current) = request.try_clone() else {
+                return request.send();
+            };
+            let started = Instant::now();
+            let result = current.send();
+            let elapsed = started.elapsed().as_millis();
+            match &result {
+                Ok(resp) => log::debug!("{target} -> {} in {elapsed}ms", resp.status()),
+                Err(e) => log::debug!("{target} failed after {elapsed}ms: {e}"),
+            }
+            if attempt >= self.settings.max_retries {
+                return result;
+            }
+
+            let delay = match &result {
+                Ok(resp) if should_retry_status(resp.status(), retry) => {
+                    retry_after(resp).unwrap_or_else(|| self.backoff(attempt))
+                }
+                Ok(_) => return result,
+                Err(e) if retry == Retry::Idempotent && (e.is_connect() || e.is_timeout()) => {
+                    self.backoff(attempt)
+                }
+                Err(_) => return result,
+            };
+
+            let delay = delay.min(MAX_BACKOFF);
+            log::info!(
+                "Retrying {target} in {}ms (attempt {} of {})",
+                delay.as_millis(),
+                attempt + 2,
+                self.settings.max_retries + 1
+            );
+            std::thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+
+    /// Equal-jitter backoff: a random delay between half and all of `base * 2^attempt`.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .settings
+            .backoff_base_ms
+            .saturating_mul(1u64 << attempt.min(16));
+        let floor = ceiling / 2;
+        let jitter = random_u64() % (ceiling - floor + 1);
+        Duration::from_millis(floor + jitter)
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self {
+            client: Client::new(),
+            settings: HttpSettings::default(),
+        }
+    }
+}
+
+/// `METHOD /path` for log messages; the full URL only appears at trace level.
+fn describe(request: &RequestBuilder) -> String {
+    let Some(Ok(request)) = request.try_clone().map(|r| r.build()) else {
+        return "request".to_string();
+    };
+    log::trace!("{} {}", request.method(), request.url());
+    format!("{} {}", request.method(), request.url().path())
+}
+
+fn should_retry_status(status: StatusCode, retry: Retry) -> bool {
+    match retry {
+        Retry::Idempotent => status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error(),
+        Retry::NonIdempotent => {
+            status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE
+        }
+    }
+}
+
+/// Parses a `Retry-After` header given either in seconds or as an HTTP date.
+fn retry_after(resp: &Response) -> Option<Duration> {
+    let value = resp.headers().get("retry-after")?.to_str().ok()?;
+    parse_retry_after(value, chrono::Utc::now())
+}
+
+fn parse_retry_after(value: &str, now: DateTime<chrono::Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.signed_duration_since(now).to_std().unwrap_or_default();
+    Some(wait)
+}
+
+fn random_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    #[test]
+    fn test_settings_from_config_fills_defaults() {
+        let profile = json!({ "http": { "readTimeoutSecs": 5, "proxy": "http://proxy:3128" } });
+        let settings = serde_json::from_value::<Config>(profile).unwrap().http;
+        // Older configs name the request timeout `readTimeoutSecs`
+        assert_eq!(settings.request_timeout_secs, 5);
+        assert_eq!(settings.proxy.as_deref(), Some("http://proxy:3128"));
+        assert_eq!(settings.max_retries, HttpSettings::default().max_retries);
+
+        let settings = serde_json::from_value::<Config>(json!({})).unwrap().http;
+        assert_eq!(settings, HttpSettings::default());
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(should_retry_status(
+            StatusCode::BAD_GATEWAY,
+            Retry::Idempotent
+        ));
+        assert!(should_retry_status(
+            StatusCode::TOO_MANY_REQUESTS,
+            Retry::NonIdempotent
+        ));
+        assert!(!should_retry_status(
+            StatusCode::BAD_GATEWAY,
+            Retry::NonIdempotent
+        ));
+        assert!(!should_retry_status(
+            StatusCode::NOT_FOUND,
+            Retry::Idempotent
+        ));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = chrono::Utc.with_ymd_and_hms(2026, 10, 1, 12, 0, 0).unwrap();
+        assert_eq!(parse_retry_after("7", now), Some(Duration::from_secs(7)));
+        assert_eq!(
+            parse_retry_after("Thu, 01 Oct 2026 12:00:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_backoff_grows_within_bounds() {
+        let client = HttpClient::default();
+        for attempt in 0..4 {
+            let ceiling = 500 * (1 << attempt);
+            let delay = client.backoff(attempt).as_millis() as u64;
+            assert!(delay >= ceiling / 2 && delay <= ceiling);
+        }
+    }
+}
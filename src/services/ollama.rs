@@ -1,12 +1,128 @@
-use reqwest::blocking::Client;
+use crate::generation::GenerationOptions;
+use crate::logging;
+use crate::metrics::{Metric, MetricsLog};
+use crate::performance::{OperationContext, Timer};
+use crate::services::http::{HttpClient, Retry};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::io::{BufRead, BufReader};
 use std::time::Duration;
 
-/// Default timeout for Ollama requests (30 seconds)
-const OLLAMA_TIMEOUT: Duration = Duration::from_secs(30);
+/// Language model backend used for embeddings and text generation.
+pub trait LlmProvider {
+    /// Name of the model used for generation.
+    fn model(&self) -> &str;
+    /// Name of the model used for embeddings.
+    fn embedding_model(&self) -> &str;
+    fn embed(&self, text: &str) -> Result<Vec<f32>, String>;
+    fn generate(&self, prompt: &str) -> Result<String, String>;
+}
+
+/// [`LlmProvider`] backed by a local Ollama server.
+pub struct OllamaProvider {
+    client: HttpClient,
+    base_url: String,
+    model: String,
+    embedding_model: String,
+    /// Sampling settings sent with every generation request.
+    options: GenerationOptions,
+    /// Where call latencies and token counts are recorded, if anywhere.
+    metrics: Option<MetricsLog>,
+}
+
+impl OllamaProvider {
+    pub fn new(client: HttpClient, base_url: &str, model: &str, embedding_model: &str) -> Self {
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            embedding_model: embedding_model.to_string(),
+            options: GenerationOptions::default(),
+            metrics: None,
+        }
+    }
+
+    /// Sends `options` (temperature, token limits, ...) with every generation request.
+    pub fn with_options(mut self, options: GenerationOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Records every call to `metrics`.
+    pub fn with_metrics(mut self, metrics: MetricsLog) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    fn record(&self, metric: Metric) {
+        // Metrics are best effort; a failed write must not fail the call
+        if let Some(metrics) = &self.metrics {
+            let _ = metrics.record(&metric);
+        }
+    }
+
+    /// Adds what was being attempted, and against which server, to an error message.
+    fn context(&self, operation: &str, model: &str) -> OperationContext {
+        let mut context = OperationContext::new(operation);
+        context.add_detail(&format!("Model: {model}"));
+        context.add_detail(&format!("Ollama: {}", self.base_url));
+        context
+    }
+}
+
+/// Text generated by Ollama with the token counts it reported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generation {
+    pub text: String,
+    pub prompt_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+}
+
+impl LlmProvider for OllamaProvider {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn embedding_model(&self) -> &str {
+        &self.embedding_model
+    }
+
+    fn embed(&self, text: &str) -> Result<Vec<f32>, String> {
+        let url = format!("{}/api/embeddings", self.base_url);
+        let timer = Timer::new("embed");
+        let result = generate_embedding(&self.client, &url, &self.embedding_model, text);
+        let elapsed = timer.finish(result.is_ok());
+        self.record(Metric {
+            bytes: Some(text.len() as u64),
+            ..Metric::new("embed", &self.embedding_model, elapsed, result.is_ok())
+        });
+        result.map_err(|e| {
+            self.context("create an embedding", &self.embedding_model)
+                .format_error(&e)
+        })
+    }
+
+    fn generate(&self, prompt: &str) -> Result<String, String> {
+        let url = format!("{}/api/generate", self.base_url);
+        let timer = Timer::new("generate");
+        let result = generate_suggestion(&self.client, &url, &self.model, prompt, &self.options);
+        let elapsed = timer.finish(result.is_ok());
+        let generation = result.as_ref().ok();
+        self.record(Metric {
+            bytes: Some((prompt.len() + generation.map_or(0, |g| g.text.len())) as u64),
+            prompt_tokens: generation.and_then(|g| g.prompt_tokens),
+            output_tokens: generation.and_then(|g| g.output_tokens),
+            ..Metric::new("generate", &self.model, elapsed, result.is_ok())
+        });
+        result.map(|g| g.text).map_err(|e| {
+            self.context("generate a response", &self.model)
+                .format_error(&e)
+        })
+    }
+}
 
 pub fn generate_embedding(
-    client: &Client,
+    client: &HttpClient,
     ollama_url: &str,
     model: &str,
     prompt: &str,
@@ -24,12 +140,13 @@ pub fn generate_embedding(
         "prompt": prompt
     });
 
-    let embedding_res = client
-        .post(ollama_url)
-        .timeout(OLLAMA_TIMEOUT)
-        .header("Content-Type", "application/json")
-        .json(&embedding_body)
-        .send();
+    let embedding_res = client.send(
+        client
+            .post(ollama_url)
+            .header("Content-Type", "application/json")
+            .json(&embedding_body),
+        Retry::Idempotent,
+    );
     match embedding_res {
         Ok(resp) if resp.status().is_success() => {
             let resp_text = resp.text().unwrap_or_default();
@@ -60,9 +177,10 @@ pub fn generate_embedding(
         Ok(resp) => {
             let status = resp.status();
             let err_text = resp.text().unwrap_or_default();
+            log::debug!("Ollama embedding error body: {err_text}");
             if status.as_u16() == 404 {
                 Err(format!(
-                    "Model '{model}' not found. Try: ollama pull {model}"
+                    "Model '{model}' not found. Try: logswise-cli models pull {model}"
                 ))
             } else {
                 Err(format!("Ollama returned error status {status}: {err_text}"))
@@ -87,11 +205,12 @@ pub fn generate_embedding(
 }
 
 pub fn generate_suggestion(
-    client: &Client,
+    client: &HttpClient,
     ollama_url: &str,
     model: &str,
     prompt: &str,
-) -> Result<String, String> {
+    options: &GenerationOptions,
+) -> Result<Generation, String> {
     if prompt.trim().is_empty() {
         return Err("Prompt cannot be empty".to_string());
     }
@@ -100,16 +219,26 @@ pub fn generate_suggestion(
         return Err("Model name cannot be empty".to_string());
     }
 
-    let ollama_body = serde_json::json!({
+    log::trace!("Prompt for {model}: {}", logging::note_text(prompt));
+    let mut ollama_body = serde_json::json!({
         "model": model,
         "prompt": prompt
     });
-    let ollama_res = client
-        .post(ollama_url)
-        .timeout(OLLAMA_TIMEOUT)
-        .header("Content-Type", "application/json")
-        .json(&ollama_body)
-        .send();
+    if let Some(think) = options.think {
+        ollama_body["think"] = Value::Bool(think);
+    }
+    let options = options.ollama_options();
+    if !options.is_empty() {
+        ollama_body["options"] = Value::Object(options);
+    }
+    let ollama_res = client.send(
+        client
+            .post(ollama_url)
+            .header("Content-Type", "application/json")
+            .json(&ollama_body)
+            .timeout(client.generation_timeout()),
+        Retry::NonIdempotent,
+    );
     match ollama_res {
         Ok(resp) if resp.status().is_success() => {
             let raw_body = resp.text().unwrap_or_default();
@@ -118,11 +247,24 @@ pub fn generate_suggestion(
             }
 
             let mut final_response = String::new();
+            let mut thinking = String::new();
+            let (mut prompt_tokens, mut output_tokens) = (None, None);
             for line in raw_body.lines() {
                 if let Ok(data) = serde_json::from_str::<Value>(line) {
                     if let Some(resp_str) = data.get("response").and_then(|v| v.as_str()) {
                         final_response.push_str(resp_str);
                     }
+                    // With `think` set, reasoning streams separately from the answer
+                    if let Some(thought) = data.get("thinking").and_then(Value::as_str) {
+                        thinking.push_str(thought);
+                    }
+                    // Counts arrive on the final (`done`) line
+                    if let Some(count) = data.get("prompt_eval_count").and_then(Value::as_u64) {
+                        prompt_tokens = Some(count);
+                    }
+                    if let Some(count) = data.get("eval_count").and_then(Value::as_u64) {
+                        output_tokens = Some(count);
+                    }
                     // Check for errors in streaming response
                     if let Some(error) = data.get("error").and_then(|v| v.as_str()) {
                         return Err(format!("Ollama error: {error}"));
@@ -130,18 +272,28 @@ pub fn generate_suggestion(
                 }
             }
 
-            if final_response.trim().is_empty() {
+            if final_response.trim().is_empty() && thinking.trim().is_empty() {
                 Err("Model generated empty response. Try a different prompt or model.".to_string())
             } else {
-                Ok(final_response)
+                // Rejoin the reasoning in the inline form models without `think` use,
+                // so callers parse both the same way
+                if !thinking.trim().is_empty() {
+                    final_response = format!("<think>{thinking}</think>\n{final_response}");
+                }
+                Ok(Generation {
+                    text: final_response,
+                    prompt_tokens,
+                    output_tokens,
+                })
             }
         }
         Ok(resp) => {
             let status = resp.status();
             let err_body = resp.text().unwrap_or_default();
+            log::debug!("Ollama generation error body: {err_body}");
             if status.as_u16() == 404 {
                 Err(format!(
-                    "Model '{model}' not found. Try: ollama pull {model}"
+                    "Model '{model}' not found. Try: logswise-cli models pull {model}"
                 ))
             } else {
                 Err(format!(
@@ -163,3 +315,314 @@ pub fn generate_suggestion(
         }
     }
 }
+
+/// Metadata about an installed Ollama model, from `/api/tags` and `/api/show`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ModelInfo {
+    /// Full model name including tag (e.g. `llama3:latest`).
+    pub name: String,
+    #[serde(default)]
+    pub family: Option<String>,
+    #[serde(default)]
+    pub families: Vec<String>,
+    #[serde(default)]
+    pub parameter_size: Option<String>,
+    /// Weight format, e.g. `Q4_K_M`.
+    #[serde(default)]
+    pub quantization: Option<String>,
+    #[serde(default)]
+    pub context_length: Option<u64>,
+    /// What the model can do, as reported by Ollama (`completion`, `embedding`, ...).
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    /// Size on disk in bytes.
+    #[serde(default)]
+    pub size: u64,
+}
+
+impl ModelInfo {
+    pub fn supports_embedding(&self) -> bool {
+        self.capabilities.iter().any(|c| c == "embedding")
+    }
+
+    pub fn supports_generation(&self) -> bool {
+        self.capabilities.iter().any(|c| c == "completion")
+    }
+
+    /// True when `name` refers to this model, with or without the `:latest` tag.
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.trim().to_lowercase();
+        let own = self.name.to_lowercase();
+        own == name || own.strip_suffix(":latest") == Some(name.as_str())
+    }
+}
+
+/// Lists installed models via `/api/tags`. Capabilities are not filled in.
+pub fn list_models(client: &HttpClient, base_url: &str) -> Result<Vec<ModelInfo>, String> {
+    let url = format!("{}/api/tags", base_url.trim_end_matches('/'));
+    let resp = client
+        .send(client.get(&url), Retry::Idempotent)
+        .map_err(|e| format!("Failed to connect to Ollama: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!(
+            "Ollama returned error status {} for /api/tags",
+            resp.status()
+        ));
+    }
+    let body: Value = resp
+        .json()
+        .map_err(|e| format!("Failed to parse Ollama response: {e}"))?;
+    Ok(models_from_tags(&body))
+}
+
+/// Fetches details for one model via `/api/show`, merged into `model`.
+pub fn show_model(
+    client: &HttpClient,
+    base_url: &str,
+    model: &ModelInfo,
+) -> Result<ModelInfo, String> {
+    let url = format!("{}/api/show", base_url.trim_end_matches('/'));
+    // Older Ollama versions expect `name`, newer ones `model`
+    let body = serde_json::json!({ "model": model.name, "name": model.name });
+    let resp = client
+        .send(client.post(&url).json(&body), Retry::Idempotent)
+        .map_err(|e| format!("Failed to connect to Ollama: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!(
+            "Ollama returned error status {} for model '{}'",
+            resp.status(),
+            model.name
+        ));
+    }
+    let body: Value = resp
+        .json()
+        .map_err(|e| format!("Failed to parse Ollama response: {e}"))?;
+    Ok(apply_show_response(model.clone(), &body))
+}
+
+/// Deletes an installed model via `/api/delete`.
+pub fn delete_model(client: &HttpClient, base_url: &str, model: &str) -> Result<(), String> {
+    let url = format!("{}/api/delete", base_url.trim_end_matches('/'));
+    let body = serde_json::json!({ "model": model, "name": model });
+    let resp = client
+        .send(client.delete(&url).json(&body), Retry::Idempotent)
+        .map_err(|e| format!("Failed to connect to Ollama: {e}"))?;
+    match resp.status().as_u16() {
+        200..=299 => Ok(()),
+        404 => Err(format!("Model '{model}' is not installed")),
+        _ => {
+            let status = resp.status();
+            log::debug!(
+                "Ollama delete error body: {}",
+                resp.text().unwrap_or_default()
+            );
+            Err(format!(
+                "Ollama returned error status {status} while deleting '{model}'"
+            ))
+        }
+    }
+}
+
+/// One status line streamed by `/api/pull`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PullProgress {
+    /// `pulling manifest`, `downloading <digest>`, `verifying sha256 digest`, `success`, ...
+    pub status: String,
+    /// Layer being downloaded, when `status` is about one.
+    pub digest: Option<String>,
+    pub total: Option<u64>,
+    pub completed: Option<u64>,
+}
+
+/// Longest a model download may take before it is abandoned.
+const PULL_TIMEOUT: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Downloads `model` via `/api/pull`, calling `on_progress` for every status update.
+pub fn pull_model(
+    client: &HttpClient,
+    base_url: &str,
+    model: &str,
+    mut on_progress: impl FnMut(&PullProgress),
+) -> Result<(), String> {
+    let url = format!("{}/api/pull", base_url.trim_end_matches('/'));
+    // Older Ollama versions expect `name`, newer ones `model`
+    let body = serde_json::json!({ "model": model, "name": model, "stream": true });
+    let resp = client
+        .send(
+            client.post(&url).json(&body).timeout(PULL_TIMEOUT),
+            Retry::Idempotent,
+        )
+        .map_err(|e| format!("Failed to connect to Ollama: {e}"))?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let err_body = resp.text().unwrap_or_default();
+        log::debug!("Ollama pull error body: {err_body}");
+        return Err(format!(
+            "Ollama returned error status {status} while pulling '{model}'"
+        ));
+    }
+
+    let mut succeeded = false;
+    for line in BufReader::new(resp).lines() {
+        let line = line.map_err(|e| format!("Download of '{model}' was interrupted: {e}"))?;
+        if let Some(progress) = parse_pull_line(&line)? {
+            succeeded |= progress.status == "success";
+            on_progress(&progress);
+        }
+    }
+    if succeeded {
+        Ok(())
+    } else {
+        Err(format!("Download of '{model}' ended before it finished"))
+    }
+}
+
+/// Parses one line of the `/api/pull` stream; an `error` field becomes an `Err`.
+fn parse_pull_line(line: &str) -> Result<Option<PullProgress>, String> {
+    let Ok(data) = serde_json::from_str::<Value>(line) else {
+        return Ok(None);
+    };
+    if let Some(error) = data["error"].as_str() {
+        return Err(format!("Ollama error: {error}"));
+    }
+    Ok(Some(PullProgress {
+        status: data["status"].as_str().unwrap_or_default().to_string(),
+        digest: data["digest"].as_str().map(str::to_string),
+        total: data["total"].as_u64(),
+        completed: data["completed"].as_u64(),
+    }))
+}
+
+fn models_from_tags(body: &Value) -> Vec<ModelInfo> {
+    body["models"]
+        .as_array()
+        .map(|models| {
+            models
+                .iter()
+                .filter_map(|m| {
+                    let details = &m["details"];
+                    let mut info = ModelInfo {
+                        name: m["name"].as_str().or(m["model"].as_str())?.to_string(),
+                        family: details["family"].as_str().map(str::to_string),
+                        families: string_list(&details["families"]),
+                        parameter_size: details["parameter_size"].as_str().map(str::to_string),
+                        quantization: details["quantization_level"].as_str().map(str::to_string),
+                        context_length: None,
+                        capabilities: vec![],
+                        size: m["size"].as_u64().unwrap_or(0),
+                    };
+                    info.capabilities = inferred_capabilities(&info);
+                    Some(info)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn apply_show_response(mut info: ModelInfo, body: &Value) -> ModelInfo {
+    let details = &body["details"];
+    if let Some(family) = details["family"].as_str() {
+        info.family = Some(family.to_string());
+    }
+    if let Some(quantization) = details["quantization_level"].as_str() {
+        info.quantization = Some(quantization.to_string());
+    }
+    if !string_list(&details["families"]).is_empty() {
+        info.families = string_list(&details["families"]);
+    }
+
+    let model_info = &body["model_info"];
+    if let Some(arch) = model_info["general.architecture"].as_str() {
+        info.context_length = model_info[format!("{arch}.context_length")].as_u64();
+    }
+
+    let capabilities = string_list(&body["capabilities"]);
+    info.capabilities = if capabilities.is_empty() {
+        inferred_capabilities(&info)
+    } else {
+        capabilities
+    };
+    info
+}
+
+/// Best guess for Ollama versions that do not report capabilities: BERT-family models
+/// only produce embeddings, everything else generates text.
+fn inferred_capabilities(info: &ModelInfo) -> Vec<String> {
+    let bert = info
+        .family
+        .iter()
+        .chain(info.families.iter())
+        .any(|f| f.contains("bert"));
+    if bert || looks_like_embedding_model(&info.name) {
+        vec!["embedding".to_string()]
+    } else {
+        vec!["completion".to_string()]
+    }
+}
+
+/// Name-based fallback used when Ollama cannot be asked about a model.
+pub fn looks_like_embedding_model(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains("embed") || name.starts_with("bge") || name.contains("minilm")
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_models_from_tags_and_show() {
+        let tags = json!({ "models": [
+            { "name": "llama3:latest", "size": 4661224676u64,
+              "details": { "family": "llama", "families": ["llama"], "parameter_size": "8.0B",
+                           "quantization_level": "Q4_0" } },
+            { "name": "nomic-embed-text:latest", "size": 274302450,
+              "details": { "family": "nomic-bert", "families": ["nomic-bert"], "parameter_size": "137M" } }
+        ]});
+        let models = models_from_tags(&tags);
+        assert_eq!(models.len(), 2);
+        assert!(models[0].supports_generation());
+        assert!(models[1].supports_embedding() && !models[1].supports_generation());
+        assert!(models[0].matches("llama3") && models[0].matches("LLaMA3:latest"));
+        assert!(!models[0].matches("llama3.1"));
+        assert_eq!(models[0].quantization.as_deref(), Some("Q4_0"));
+
+        let show = json!({
+            "details": { "family": "llama" },
+            "model_info": { "general.architecture": "llama", "llama.context_length": 8192 },
+            "capabilities": ["completion", "tools"]
+        });
+        let info = apply_show_response(models[0].clone(), &show);
+        assert_eq!(info.context_length, Some(8192));
+        assert_eq!(info.capabilities, vec!["completion", "tools"]);
+    }
+
+    #[test]
+    fn test_parse_pull_line() {
+        let progress = parse_pull_line(
+            r#"{"status":"pulling 8eeb52dfb3bb","digest":"sha256:8eeb","total":4000,"completed":1000}"#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(progress.total, Some(4000));
+        assert_eq!(progress.completed, Some(1000));
+        assert_eq!(progress.digest.as_deref(), Some("sha256:8eeb"));
+        assert_eq!(parse_pull_line("").unwrap(), None);
+        assert_eq!(
+            parse_pull_line(r#"{"error":"pull model manifest: file does not exist"}"#),
+            Err("Ollama error: pull model manifest: file does not exist".to_string())
+        );
+    }
+}
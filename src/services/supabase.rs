@@ -1,15 +1,263 @@
-use crate::types::SupabaseConfig;
+use crate::metrics::{Metric, MetricsLog};
+use crate::performance::{OperationContext, Timer};
+use crate::services::http::{HttpClient, Retry};
+use crate::types::{Note, SupabaseConfig};
+use chrono::{DateTime, SecondsFormat, Utc};
 use colored::*;
-use reqwest::blocking::Client;
 use serde_json::Value;
 use std::time::Duration;
 
+/// Columns every notes table has. `project` is only selected when filtering by it, so
+/// databases created before projects existed keep working.
+const NOTE_COLUMNS: &str = "id,content,created_at";
+
+/// Rows requested per page when fetching every note in a date range.
+const PAGE_SIZE: usize = 500;
+
+/// Filters for [`NoteStore::find_notes`]. Unset fields do not filter.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoteQuery<'a> {
+    pub project: Option<&'a str>,
+    /// Only notes created at or after this instant.
+    pub since: Option<DateTime<Utc>>,
+    /// Only notes created before this instant.
+    pub until: Option<DateTime<Utc>>,
+    /// Text the content must contain, ignoring case.
+    pub text: Option<&'a str>,
+    pub limit: usize,
+    /// Also fetch each note's embedding (large; only for analysis).
+    pub with_embeddings: bool,
+    /// Only notes with (`true`) or without (`false`) an embedding.
+    pub embedded: Option<bool>,
+}
+
+/// Fields to change on an existing note. `None` leaves a field as it is.
+#[derive(Debug, Default, Clone)]
+pub struct NoteChanges {
+    /// New content together with its embedding, if one could be generated.
+    pub content: Option<(String, Option<Vec<f32>>)>,
+    /// New project tag; `Some(None)` clears it.
+    pub project: Option<Option<String>>,
+}
+
+/// A failed note insert: the status Supabase answered with, if any, and the message to show.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreError {
+    /// HTTP status of the response; `None` when no response arrived.
+    pub status: Option<u16>,
+    pub message: String,
+}
+
+impl StoreError {
+    /// True when Supabase refused the note itself (400, 409 or 422), which retrying
+    /// cannot fix. Authentication, permission and missing-table errors are not
+    /// rejections: they affect every note and go away once the setup is fixed.
+    pub fn is_rejection(&self) -> bool {
+        matches!(self.status, Some(400 | 409 | 422))
+    }
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Persistent storage for notes.
+pub trait NoteStore {
+    fn insert_note(&self, note: &Note) -> Result<(), StoreError>;
+    fn update_note(&self, id: &str, changes: &NoteChanges) -> Result<(), String>;
+    fn delete_note(&self, id: &str) -> Result<(), String>;
+    /// Returns the notes most similar to `embedding`, best match first. With `project`,
+    /// only notes tagged with that project are considered.
+    fn semantic_search(
+        &self,
+        embedding: &[f32],
+        match_count: usize,
+        project: Option<&str>,
+    ) -> Result<Vec<Note>, String>;
+    /// Returns up to `query.limit` notes matching `query`, newest first.
+    fn find_notes(&self, query: &NoteQuery) -> Result<Vec<Note>, String>;
+    /// Counts the notes matching `query`, ignoring its `limit`.
+    fn count_notes(&self, query: &NoteQuery) -> Result<usize, String>;
+    /// Returns the most recently created notes, newest first, optionally for one project.
+    fn recent_notes(&self, count: usize, project: Option<&str>) -> Result<Vec<Note>, String> {
+        self.find_notes(&NoteQuery {
+            project,
+            limit: count,
+            ..NoteQuery::default()
+        })
+    }
+    /// Returns the notes with the given ids, oldest first. Unknown ids are skipped.
+    fn notes_by_ids(&self, ids: &[String]) -> Result<Vec<Note>, String>;
+    /// Returns every note created in `[start, end)`, oldest first, optionally for one project.
+    fn notes_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        project: Option<&str>,
+    ) -> Result<Vec<Note>, String>;
+}
+
+/// [`NoteStore`] backed by the Supabase REST API.
+pub struct SupabaseStore {
+    client: HttpClient,
+    config: SupabaseConfig,
+    /// Where call latencies are recorded, if anywhere.
+    metrics: Option<MetricsLog>,
+}
+
+impl SupabaseStore {
+    pub fn new(client: HttpClient, config: SupabaseConfig) -> Self {
+        Self {
+            client,
+            config,
+            metrics: None,
+        }
+    }
+
+    /// Records every call to `metrics`.
+    pub fn with_metrics(mut self, metrics: MetricsLog) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Runs one storage call as `store.<call>`, recording its latency and the note text
+    /// moved (from `bytes`), and describing `action` in any error.
+    fn timed<T>(
+        &self,
+        call: &str,
+        action: &str,
+        bytes: impl Fn(&T) -> usize,
+        f: impl FnOnce() -> Result<T, String>,
+    ) -> Result<T, String> {
+        let operation = format!("store.{call}");
+        let timer = Timer::new(&operation);
+        let result = f();
+        let elapsed = timer.finish(result.is_ok());
+        match &result {
+            Ok(_) => log::debug!("{operation} took {}ms", elapsed.as_millis()),
+            Err(e) => log::debug!("{operation} failed after {}ms: {e}", elapsed.as_millis()),
+        }
+        if let Some(metrics) = &self.metrics {
+            // Metrics are best effort; a failed write must not fail the call
+            let _ = metrics.record(&Metric {
+                bytes: result.as_ref().ok().map(|value| bytes(value) as u64),
+                ..Metric::new(&operation, "", elapsed, result.is_ok())
+            });
+        }
+        result.map_err(|e| {
+            let mut context = OperationContext::new(action);
+            context.add_detail(&format!("Supabase: {}", self.config.project_url));
+            context.format_error(&e)
+        })
+    }
+}
+
+/// Characters of note text in `notes`.
+fn content_bytes(notes: &[Note]) -> usize {
+    notes.iter().map(|n| n.content.len()).sum()
+}
+
+impl NoteStore for SupabaseStore {
+    fn insert_note(&self, note: &Note) -> Result<(), StoreError> {
+        let mut status = None;
+        self.timed(
+            "insert",
+            "save the note",
+            |_| note.content.len(),
+            || {
+                insert_note(&self.client, &self.config, note).map_err(|e| {
+                    status = e.status;
+                    e.message
+                })
+            },
+        )
+        .map_err(|message| StoreError { status, message })
+    }
+
+    fn update_note(&self, id: &str, changes: &NoteChanges) -> Result<(), String> {
+        let size = changes.content.as_ref().map_or(0, |(c, _)| c.len());
+        self.timed(
+            "update",
+            "update the note",
+            |_| size,
+            || update_note(&self.client, &self.config, id, changes),
+        )
+    }
+
+    fn delete_note(&self, id: &str) -> Result<(), String> {
+        self.timed(
+            "delete",
+            "delete the note",
+            |_| 0,
+            || delete_note(&self.client, &self.config, id),
+        )
+    }
+
+    fn semantic_search(
+        &self,
+        embedding: &[f32],
+        match_count: usize,
+        project: Option<&str>,
+    ) -> Result<Vec<Note>, String> {
+        self.timed(
+            "search",
+            "search notes",
+            |n: &Vec<Note>| content_bytes(n),
+            || semantic_search_notes(&self.client, &self.config, embedding, match_count, project),
+        )
+    }
+
+    fn find_notes(&self, query: &NoteQuery) -> Result<Vec<Note>, String> {
+        self.timed(
+            "fetch",
+            "fetch notes",
+            |n: &Vec<Note>| content_bytes(n),
+            || fetch_notes(&self.client, &self.config, query),
+        )
+    }
+
+    fn count_notes(&self, query: &NoteQuery) -> Result<usize, String> {
+        self.timed(
+            "count",
+            "count notes",
+            |_| 0,
+            || count_notes(&self.client, &self.config, query),
+        )
+    }
+
+    fn notes_by_ids(&self, ids: &[String]) -> Result<Vec<Note>, String> {
+        self.timed(
+            "fetch",
+            "fetch notes",
+            |n: &Vec<Note>| content_bytes(n),
+            || fetch_notes_by_ids(&self.client, &self.config, ids),
+        )
+    }
+
+    fn notes_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        project: Option<&str>,
+    ) -> Result<Vec<Note>, String> {
+        self.timed(
+            "fetch",
+            "fetch notes",
+            |n: &Vec<Note>| content_bytes(n),
+            || fetch_notes_between(&self.client, &self.config, start, end, project),
+        )
+    }
+}
+
 pub fn semantic_search_notes(
-    client: &Client,
+    client: &HttpClient,
     config: &SupabaseConfig,
     embedding: &[f32],
     match_count: usize,
-) -> Vec<String> {
+    project: Option<&str>,
+) -> Result<Vec<Note>, String> {
     let embedding_str = format!(
         "[{}]",
         embedding
@@ -18,41 +266,497 @@ pub fn semantic_search_notes(
             .collect::<Vec<_>>()
             .join(",")
     );
-    let sql_url = format!("{}/rest/v1/rpc/semantic_search_notes", config.project_url);
-    let sql_body =
+    // Arguments of the original function; `filter_project` is only added when scoped
+    let unscoped =
         serde_json::json!({ "query_embedding": embedding_str, "match_count": match_count });
-    let notes_res = client
-        .post(&sql_url)
-        .header("apikey", &config.api_key)
-        .header("Authorization", format!("Bearer {}", &config.api_key))
-        .header("Content-Type", "application/json")
-        .json(&sql_body)
-        .send();
-    let mut notes = Vec::new();
-    if let Ok(resp) = notes_res {
-        if let Ok(notes_val) = resp.json::<Value>() {
-            if let Some(arr) = notes_val.as_array() {
-                for n in arr {
-                    if let Some(content) = n["content"].as_str() {
-                        notes.push(content.to_string());
-                    }
-                }
-            }
+    let Some(project) = project else {
+        return search_rpc(client, config, &unscoped);
+    };
+    let mut scoped = unscoped.clone();
+    scoped["filter_project"] = Value::from(project);
+    match search_rpc(client, config, &scoped) {
+        // PGRST202: no function with these arguments, i.e. one created before projects
+        Err(e) if e.contains("PGRST202") => {
+            log::warn!(
+                "semantic_search_notes has no filter_project argument; searching all notes. Run 'logswise-cli init' for the updated function."
+            );
+            let notes = search_rpc(client, config, &unscoped)?;
+            // Rows only carry a project when the table has the column
+            Ok(notes
+                .into_iter()
+                .filter(|n| n.project.as_deref().is_none_or(|p| p == project))
+                .collect())
+        }
+        result => result,
+    }
+}
+
+/// Calls the `semantic_search_notes` function with `body`.
+fn search_rpc(
+    client: &HttpClient,
+    config: &SupabaseConfig,
+    body: &Value,
+) -> Result<Vec<Note>, String> {
+    let sql_url = format!("{}/rest/v1/rpc/semantic_search_notes", config.project_url);
+    let response = client
+        .send(
+            client
+                .post(&sql_url)
+                .header("apikey", &config.api_key)
+                .header("Authorization", format!("Bearer {}", &config.api_key))
+                .header("Content-Type", "application/json")
+                .json(body),
+            Retry::Idempotent,
+        )
+        .map_err(|e| format!("Network error: {e}"))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().unwrap_or_default();
+        return Err(format!(
+            "Semantic search failed: HTTP {status}: {error_text}"
+        ));
+    }
+    let rows = response
+        .json::<Vec<Value>>()
+        .map_err(|e| format!("Error parsing notes: {e}"))?;
+    Ok(rows.iter().filter_map(note_from_row).collect())
+}
+
+/// Fetch the newest notes matching `filter`.
+pub fn fetch_notes(
+    client: &HttpClient,
+    config: &SupabaseConfig,
+    filter: &NoteQuery,
+) -> Result<Vec<Note>, String> {
+    let url = format!("{}/rest/v1/notes", config.project_url);
+    let mut query = vec![
+        ("select", NOTE_COLUMNS.to_string()),
+        ("order", "created_at.desc".to_string()),
+        ("limit", filter.limit.to_string()),
+    ];
+    if filter.with_embeddings {
+        query[0].1.push_str(",embedding");
+    }
+    if filter.project.is_some() {
+        query[0].1.push_str(",project");
+    }
+    query.extend(filter_params(filter));
+    let response = client
+        .send(
+            client
+                .get(&url)
+                .header("apikey", &config.api_key)
+                .header("Authorization", format!("Bearer {}", config.api_key))
+                .query(&query),
+            Retry::Idempotent,
+        )
+        .map_err(|e| format!("Network error: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!("Error fetching notes: HTTP {}", response.status()));
+    }
+    let rows = response
+        .json::<Vec<Value>>()
+        .map_err(|e| format!("Error parsing notes: {e}"))?;
+    Ok(rows.iter().filter_map(note_from_row).collect())
+}
+
+/// Count the notes matching `filter` from PostgREST's `Content-Range` header.
+pub fn count_notes(
+    client: &HttpClient,
+    config: &SupabaseConfig,
+    filter: &NoteQuery,
+) -> Result<usize, String> {
+    let url = format!("{}/rest/v1/notes", config.project_url);
+    let mut query = vec![("select", "id".to_string()), ("limit", "1".to_string())];
+    query.extend(filter_params(filter));
+    let response = client
+        .send(
+            client
+                .get(&url)
+                .header("apikey", &config.api_key)
+                .header("Authorization", format!("Bearer {}", config.api_key))
+                .header("Prefer", "count=exact")
+                .query(&query),
+            Retry::Idempotent,
+        )
+        .map_err(|e| format!("Network error: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!("Error counting notes: HTTP {}", response.status()));
+    }
+    // `0-0/42`, or `*/0` when nothing matches
+    response
+        .headers()
+        .get("content-range")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.rsplit('/').next())
+        .and_then(|total| total.parse().ok())
+        .ok_or_else(|| "Error counting notes: no count in response".to_string())
+}
+
+/// PostgREST filters for the fields of `filter` other than `limit`.
+fn filter_params(filter: &NoteQuery) -> Vec<(&'static str, String)> {
+    let mut query = vec![];
+    if let Some(project) = filter.project {
+        query.push(("project", format!("eq.{project}")));
+    }
+    if let Some(since) = filter.since {
+        query.push((
+            "created_at",
+            format!("gte.{}", since.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        ));
+    }
+    if let Some(until) = filter.until {
+        query.push((
+            "created_at",
+            format!("lt.{}", until.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        ));
+    }
+    if let Some(text) = filter.text {
+        query.push(("content", format!("ilike.*{}*", escape_like(text))));
+    }
+    match filter.embedded {
+        Some(true) => query.push(("embedding", "not.is.null".to_string())),
+        Some(false) => query.push(("embedding", "is.null".to_string())),
+        None => {}
+    }
+    query
+}
+
+/// Escapes the characters `ilike` treats as wildcards, so searches match them literally.
+fn escape_like(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| match c {
+            '%' | '_' | '\\' => vec!['\\', c],
+            c => vec![c],
+        })
+        .collect()
+}
+
+/// Fetch specific notes by id, oldest first.
+pub fn fetch_notes_by_ids(
+    client: &HttpClient,
+    config: &SupabaseConfig,
+    ids: &[String],
+) -> Result<Vec<Note>, String> {
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let url = format!("{}/rest/v1/notes", config.project_url);
+    let response = client
+        .send(
+            client
+                .get(&url)
+                .header("apikey", &config.api_key)
+                .header("Authorization", format!("Bearer {}", config.api_key))
+                .query(&[
+                    ("select", NOTE_COLUMNS.to_string()),
+                    ("id", format!("in.({})", ids.join(","))),
+                    ("order", "created_at.asc".to_string()),
+                ]),
+            Retry::Idempotent,
+        )
+        .map_err(|e| format!("Network error: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!("Error fetching notes: HTTP {}", response.status()));
+    }
+    let rows = response
+        .json::<Vec<Value>>()
+        .map_err(|e| format!("Error parsing notes: {e}"))?;
+    Ok(rows.iter().filter_map(note_from_row).collect())
+}
+
+/// Fetch every note created in `[start, end)`, oldest first, a page at a time.
+///
+/// The `project` column is included when the table has one, so reports can group by it.
+pub fn fetch_notes_between(
+    client: &HttpClient,
+    config: &SupabaseConfig,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    project: Option<&str>,
+) -> Result<Vec<Note>, String> {
+    let with_project = format!("{NOTE_COLUMNS},project");
+    match fetch_range_pages(client, config, &with_project, start, end, project) {
+        // 400 means the column doesn't exist; without a project filter we can do without it
+        Err(e) if project.is_none() && e.contains("HTTP 400") => {
+            fetch_range_pages(client, config, NOTE_COLUMNS, start, end, None)
+        }
+        result => result,
+    }
+}
+
+fn fetch_range_pages(
+    client: &HttpClient,
+    config: &SupabaseConfig,
+    columns: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    project: Option<&str>,
+) -> Result<Vec<Note>, String> {
+    let url = format!("{}/rest/v1/notes", config.project_url);
+    let mut notes = vec![];
+    // Rows fetched so far, including any `note_from_row` skips, so pages never overlap
+    let mut offset = 0;
+    loop {
+        let mut query = vec![
+            ("select", columns.to_string()),
+            (
+                "created_at",
+                format!("gte.{}", start.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            ),
+            (
+                "created_at",
+                format!("lt.{}", end.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            ),
+            // `id` breaks ties so equal timestamps keep their order between pages
+            ("order", "created_at.asc,id.asc".to_string()),
+            ("limit", PAGE_SIZE.to_string()),
+            ("offset", offset.to_string()),
+        ];
+        if let Some(project) = project {
+            query.push(("project", format!("eq.{project}")));
+        }
+        let response = client
+            .send(
+                client
+                    .get(&url)
+                    .header("apikey", &config.api_key)
+                    .header("Authorization", format!("Bearer {}", config.api_key))
+                    .query(&query),
+                Retry::Idempotent,
+            )
+            .map_err(|e| format!("Network error: {e}"))?;
+        if !response.status().is_success() {
+            return Err(format!("Error fetching notes: HTTP {}", response.status()));
+        }
+        let rows = response
+            .json::<Vec<Value>>()
+            .map_err(|e| format!("Error parsing notes: {e}"))?;
+        let page = rows.len();
+        offset += page;
+        notes.extend(rows.iter().filter_map(note_from_row));
+        if page < PAGE_SIZE {
+            return Ok(notes);
+        }
+    }
+}
+
+/// Builds a [`Note`] from a PostgREST row, tolerating missing optional columns.
+fn note_from_row(row: &Value) -> Option<Note> {
+    Some(Note {
+        id: row["id"].as_str().unwrap_or_default().to_string(),
+        content: row["content"].as_str()?.to_string(),
+        created_at: row["created_at"].as_str().unwrap_or_default().to_string(),
+        embedding: embedding_from_value(&row["embedding"]),
+        project: row["project"].as_str().map(str::to_string),
+    })
+}
+
+/// Reads a pgvector column, which PostgREST returns as a `"[0.1,0.2]"` string.
+fn embedding_from_value(value: &Value) -> Option<Vec<f32>> {
+    let parsed;
+    let values = match value {
+        Value::String(text) => {
+            parsed = serde_json::from_str::<Value>(text).ok()?;
+            parsed.as_array()?
         }
+        Value::Array(values) => values,
+        _ => return None,
+    };
+    values
+        .iter()
+        .map(|v| v.as_f64().map(|f| f as f32))
+        .collect()
+}
+
+/// Insert a note, ignoring the request if a note with the same id already exists.
+///
+/// Notes carry a client-generated id, so retrying an insert (or replaying the outbox)
+/// never creates duplicates.
+pub fn insert_note(
+    client: &HttpClient,
+    config: &SupabaseConfig,
+    note: &Note,
+) -> Result<(), StoreError> {
+    let url = format!("{}/rest/v1/notes", config.project_url);
+    // Safe to retry: the client-generated id makes the insert idempotent
+    let response = client
+        .send(
+            client
+                .post(&url)
+                .header("apikey", &config.api_key)
+                .header("Authorization", format!("Bearer {}", &config.api_key))
+                .header("Content-Type", "application/json")
+                .header("Prefer", "resolution=ignore-duplicates,return=minimal")
+                .query(&[("on_conflict", "id")])
+                .json(note),
+            Retry::Idempotent,
+        )
+        .map_err(|e| StoreError {
+            status: None,
+            message: format!("Network error: {e}"),
+        })?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        let status = response.status();
+        let error_text = response.text().unwrap_or_default();
+        Err(StoreError {
+            status: Some(status.as_u16()),
+            message: format!("HTTP {status}: {error_text}"),
+        })
     }
-    notes
+}
+
+/// SQL that adds the `project` column to a notes table created before projects existed.
+pub const PROJECT_COLUMN_SQL: &str = "ALTER TABLE notes ADD COLUMN IF NOT EXISTS project text;";
+
+/// True when `error` says the notes table has no `project` column (PostgREST's
+/// PGRST204, or Postgres' 42703 undefined column).
+pub fn is_missing_project_column(error: &StoreError) -> bool {
+    let message = &error.message;
+    error.is_rejection()
+        && (message.contains("PGRST204") || message.contains("42703"))
+        && message.contains("project")
+}
+
+/// Inserts `note`, retrying without its project tag when the notes table predates the
+/// `project` column. Returns whether the tag had to be dropped.
+pub fn insert_note_untagged_if_needed(
+    store: &dyn NoteStore,
+    note: &Note,
+) -> Result<bool, StoreError> {
+    match store.insert_note(note) {
+        Ok(()) => Ok(false),
+        Err(e) if note.project.is_some() && is_missing_project_column(&e) => {
+            log::warn!("The notes table has no project column; saving the note untagged");
+            let untagged = Note {
+                project: None,
+                ..note.clone()
+            };
+            store.insert_note(&untagged).map(|_| true)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Applies `changes` to the note with `id`.
+pub fn update_note(
+    client: &HttpClient,
+    config: &SupabaseConfig,
+    id: &str,
+    changes: &NoteChanges,
+) -> Result<(), String> {
+    let mut body = serde_json::Map::new();
+    if let Some((content, embedding)) = &changes.content {
+        body.insert("content".to_string(), Value::from(content.as_str()));
+        // A stale embedding would keep matching the old text, so clear it if none was made
+        body.insert(
+            "embedding".to_string(),
+            serde_json::to_value(embedding).unwrap_or(Value::Null),
+        );
+    }
+    // Only sent when changed, so databases without the project column keep working
+    if let Some(project) = &changes.project {
+        body.insert(
+            "project".to_string(),
+            serde_json::to_value(project).unwrap_or(Value::Null),
+        );
+    }
+    if body.is_empty() {
+        return Ok(());
+    }
+
+    let url = format!("{}/rest/v1/notes", config.project_url);
+    let response = client
+        .send(
+            client
+                .patch(&url)
+                .header("apikey", &config.api_key)
+                .header("Authorization", format!("Bearer {}", &config.api_key))
+                .header("Prefer", "return=minimal")
+                .query(&[("id", format!("eq.{id}"))])
+                .json(&body),
+            Retry::Idempotent,
+        )
+        .map_err(|e| format!("Network error: {e}"))?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        let status = response.status();
+        let error_text = response.text().unwrap_or_default();
+        Err(format!("HTTP {status}: {error_text}"))
+    }
+}
+
+/// Deletes the note with `id`; deleting a missing note is not an error.
+pub fn delete_note(client: &HttpClient, config: &SupabaseConfig, id: &str) -> Result<(), String> {
+    let url = format!("{}/rest/v1/notes", config.project_url);
+    let response = client
+        .send(
+            client
+                .delete(&url)
+                .header("apikey", &config.api_key)
+                .header("Authorization", format!("Bearer {}", &config.api_key))
+                .query(&[("id", format!("eq.{id}"))]),
+            Retry::Idempotent,
+        )
+        .map_err(|e| format!("Network error: {e}"))?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        let status = response.status();
+        let error_text = response.text().unwrap_or_default();
+        Err(format!("HTTP {status}: {error_text}"))
+    }
+}
+
+/// Ids of the notes whose content is exactly `content`.
+pub fn find_notes_with_content(
+    client: &HttpClient,
+    config: &SupabaseConfig,
+    content: &str,
+) -> Result<Vec<String>, String> {
+    let url = format!("{}/rest/v1/notes", config.project_url);
+    let response = client
+        .send(
+            client
+                .get(&url)
+                .header("apikey", &config.api_key)
+                .header("Authorization", format!("Bearer {}", config.api_key))
+                .query(&[
+                    ("select", "id".to_string()),
+                    ("content", format!("eq.{content}")),
+                ]),
+            Retry::Idempotent,
+        )
+        .map_err(|e| format!("Network error: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!("Error searching notes: HTTP {}", response.status()));
+    }
+    let rows = response
+        .json::<Vec<Value>>()
+        .map_err(|e| format!("Error parsing notes: {e}"))?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| row["id"].as_str().map(str::to_string))
+        .collect())
 }
 
 /// Test Supabase connection by making a simple query
-pub fn test_connection(client: &Client, config: &SupabaseConfig) -> Result<(), String> {
+pub fn test_connection(client: &HttpClient, config: &SupabaseConfig) -> Result<(), String> {
     let url = format!("{}/rest/v1/", config.project_url);
 
     let response = client
-        .get(&url)
-        .header("apikey", &config.api_key)
-        .header("Authorization", format!("Bearer {}", &config.api_key))
-        .timeout(Duration::from_secs(10))
-        .send()
+        .send(
+            client
+                .get(&url)
+                .header("apikey", &config.api_key)
+                .header("Authorization", format!("Bearer {}", &config.api_key))
+                .timeout(Duration::from_secs(10)),
+            Retry::Idempotent,
+        )
         .map_err(|e| format!("Connection failed: {e}"))?;
 
     if response.status().is_success() {
@@ -63,16 +767,22 @@ pub fn test_connection(client: &Client, config: &SupabaseConfig) -> Result<(), S
 }
 
 /// Check if the notes table exists
-pub fn check_notes_table_exists(client: &Client, config: &SupabaseConfig) -> Result<bool, String> {
+pub fn check_notes_table_exists(
+    client: &HttpClient,
+    config: &SupabaseConfig,
+) -> Result<bool, String> {
     let url = format!("{}/rest/v1/notes", config.project_url);
 
     let response = client
-        .get(&url)
-        .header("apikey", &config.api_key)
-        .header("Authorization", format!("Bearer {}", &config.api_key))
-        .query(&[("limit", "1")])
-        .timeout(Duration::from_secs(10))
-        .send()
+        .send(
+            client
+                .get(&url)
+                .header("apikey", &config.api_key)
+                .header("Authorization", format!("Bearer {}", &config.api_key))
+                .query(&[("limit", "1")])
+                .timeout(Duration::from_secs(10)),
+            Retry::Idempotent,
+        )
         .map_err(|e| format!("Failed to check table: {e}"))?;
 
     // If we get a 200, table exists. If we get 404, table doesn't exist.
@@ -98,26 +808,20 @@ pub fn check_notes_table_exists(client: &Client, config: &SupabaseConfig) -> Res
 }
 
 /// Execute SQL commands to set up the database schema
-pub fn setup_database_schema(client: &Client, config: &SupabaseConfig) -> Result<(), String> {
+pub fn setup_database_schema(client: &HttpClient, config: &SupabaseConfig) -> Result<(), String> {
     println!();
     println!("{}", "🔧 Setting up database schema...".cyan());
     println!();
 
-    // Instead of trying to execute SQL automatically (which most Supabase instances don't support),
-    // we'll provide clear instructions and optionally try to create the table via the REST API
-
-    // First, try to create the table using the REST API
-    match create_notes_table_via_api(client, config) {
-        Ok(_) => {
-            println!("{} Successfully created notes table!", "✅".green());
-
-            // Now show the additional SQL that needs to be run manually
+    // The REST API cannot run DDL, so the SQL is shown for the Supabase SQL Editor. The
+    // table may exist already (e.g. created by hand), in which case only the rest is needed
+    match check_notes_table_exists(client, config) {
+        Ok(true) => {
+            println!("{} Found the notes table.", "✅".green());
             show_manual_setup_instructions();
-
             Ok(())
         }
-        Err(_) => {
-            // If that fails, show all the SQL that needs to be run manually
+        _ => {
             println!("{}", "Automatic table creation not available. Please run the following SQL commands manually:".yellow());
             show_complete_sql_setup();
 
@@ -126,35 +830,47 @@ pub fn setup_database_schema(client: &Client, config: &SupabaseConfig) -> Result
     }
 }
 
-/// Try to create the notes table using Supabase REST API
-fn create_notes_table_via_api(client: &Client, config: &SupabaseConfig) -> Result<(), String> {
-    // This approach uses the direct table creation - some Supabase instances allow this
-    let url = format!("{}/rest/v1/notes", config.project_url);
+/// Content of the test note older versions wrote while setting up the schema; `doctor`
+/// finds and deletes leftovers.
+pub const SETUP_TEST_NOTE: &str = "Setup test note - you can delete this";
 
-    // Try to create with a dummy record to trigger table creation if it's enabled
+/// Whether the API key may insert notes.
+#[derive(Debug, PartialEq)]
+pub enum WriteAccess {
+    Allowed,
+    /// Refused by table privileges or row level security, with Supabase's message.
+    Denied(String),
+}
+
+/// Checks that the API key may insert notes without writing one. The probe sends a null
+/// `id`, which the primary key refuses (23502) only after privileges and row level
+/// security have allowed the insert; a refused insert fails earlier with 42501.
+pub fn check_write_access(
+    client: &HttpClient,
+    config: &SupabaseConfig,
+) -> Result<WriteAccess, String> {
+    let url = format!("{}/rest/v1/notes", config.project_url);
     let response = client
-        .post(&url)
-        .header("apikey", &config.api_key)
-        .header("Authorization", format!("Bearer {}", &config.api_key))
-        .header("Content-Type", "application/json")
-        .header("Prefer", "return=minimal")
-        .json(&serde_json::json!({
-            "content": "Setup test note - you can delete this",
-            "embedding": null
-        }))
-        .timeout(Duration::from_secs(10))
-        .send()
+        .send(
+            client
+                .post(&url)
+                .header("apikey", &config.api_key)
+                .header("Authorization", format!("Bearer {}", &config.api_key))
+                .header("Content-Type", "application/json")
+                .header("Prefer", "return=minimal")
+                .json(&serde_json::json!({ "id": null, "content": "" }))
+                .timeout(Duration::from_secs(10)),
+            Retry::Idempotent,
+        )
         .map_err(|e| format!("Request failed: {e}"))?;
-
-    if response.status().is_success() {
-        // If successful, the table exists - clean up the test note
-        Ok(())
-    } else if response.status().as_u16() == 404 {
-        // Table doesn't exist and can't be auto-created
-        Err("Table does not exist".to_string())
+    let status = response.status();
+    let body = response.text().unwrap_or_default();
+    if body.contains("23502") {
+        Ok(WriteAccess::Allowed)
+    } else if body.contains("42501") || status.as_u16() == 401 || status.as_u16() == 403 {
+        Ok(WriteAccess::Denied(body))
     } else {
-        let error_text = response.text().unwrap_or_default();
-        Err(format!("API error: {error_text}"))
+        Err(format!("Unexpected response: HTTP {status}: {body}"))
     }
 }
 
@@ -188,6 +904,13 @@ fn show_manual_setup_instructions() {
     );
     println!();
 
+    println!(
+        "{}",
+        "-- Add project column for 'project use' (if not already present)".bright_black()
+    );
+    println!("{}", PROJECT_COLUMN_SQL.bright_white());
+    println!();
+
     println!(
         "{}",
         "-- Create indexes for better performance".bright_black()
@@ -196,20 +919,31 @@ fn show_manual_setup_instructions() {
         "{}",
         "CREATE INDEX IF NOT EXISTS idx_notes_created_at ON notes (created_at);".bright_white()
     );
+    println!(
+        "{}",
+        "CREATE INDEX IF NOT EXISTS idx_notes_project ON notes (project);".bright_white()
+    );
     println!("{}", "CREATE INDEX IF NOT EXISTS idx_notes_embedding ON notes USING ivfflat (embedding vector_cosine_ops);".bright_white());
     println!();
 
-    println!("{}", "-- Create semantic search function".bright_black());
     println!(
         "{}",
-        r#"CREATE OR REPLACE FUNCTION semantic_search_notes(
+        "-- Create semantic search function (replacing the version without project filtering)"
+            .bright_black()
+    );
+    println!(
+        "{}",
+        r#"DROP FUNCTION IF EXISTS semantic_search_notes(vector, int);
+CREATE OR REPLACE FUNCTION semantic_search_notes(
     query_embedding vector(768),
-    match_count int DEFAULT 5
+    match_count int DEFAULT 5,
+    filter_project text DEFAULT NULL
 )
 RETURNS TABLE (
     id uuid,
     content text,
     created_at timestamp with time zone,
+    project text,
     similarity float
 )
 LANGUAGE sql
@@ -218,9 +952,11 @@ AS $$
         notes.id,
         notes.content,
         notes.created_at,
+        notes.project,
         1 - (notes.embedding <=> query_embedding) AS similarity
     FROM notes
     WHERE notes.embedding IS NOT NULL
+      AND (filter_project IS NULL OR notes.project = filter_project)
     ORDER BY notes.embedding <=> query_embedding
     LIMIT match_count;
 $$;"#
@@ -229,6 +965,14 @@ $$;"#
     println!();
 }
 
+/// SQL that creates the notes table; the rest of the schema is in
+/// [`show_manual_setup_instructions`].
+pub const NOTES_TABLE_SQL: &str = r#"CREATE TABLE IF NOT EXISTS notes (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    content text NOT NULL,
+    created_at timestamp with time zone DEFAULT timezone('utc'::text, now())
+);"#;
+
 /// Show complete SQL setup when automatic creation fails
 fn show_complete_sql_setup() {
     println!();
@@ -239,15 +983,7 @@ fn show_complete_sql_setup() {
     println!();
 
     println!("{}", "-- 1. Create the notes table".bright_black());
-    println!(
-        "{}",
-        r#"CREATE TABLE IF NOT EXISTS notes (
-    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
-    content text NOT NULL,
-    created_at timestamp with time zone DEFAULT timezone('utc'::text, now())
-);"#
-        .bright_white()
-    );
+    println!("{}", NOTES_TABLE_SQL.bright_white());
     println!();
 
     show_manual_setup_instructions();
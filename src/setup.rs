@@ -2,13 +2,15 @@ use colored::*;
 use dialoguer::{Confirm, Input, Select};
 use dirs::home_dir;
 use indicatif::{ProgressBar, ProgressStyle};
-use reqwest::blocking::Client;
-use serde_json::json;
 use std::fs;
 use std::path::PathBuf;
 use std::time::Duration;
 
+use crate::config::{Config, DEFAULT_EMBEDDING_MODEL, DEFAULT_OLLAMA_BASE_URL};
+use crate::models::ModelCatalog;
+use crate::services::ollama::ModelInfo;
 // Import our Supabase service functions
+use crate::services::http::HttpClient;
 use crate::services::supabase::{check_notes_table_exists, setup_database_schema, test_connection};
 use crate::types::SupabaseConfig;
 
@@ -99,48 +101,57 @@ pub fn run_setup() {
         .default(0)
         .interact()
         .unwrap();
-    let llm_name: String = Input::new()
-        .with_prompt("Enter the LLM name (e.g., ollama, llama.cpp):")
-        .interact_text()
-        .unwrap();
     let ollama_url: String = Input::new()
         .with_prompt("Enter the Ollama base URL (default: http://localhost:11434):")
-        .default("http://localhost:11434".to_string())
-        .interact_text()
-        .unwrap();
-    let ollama_embedding_model: String = Input::new()
-        .with_prompt("Enter the embedding model name (default: nomic-embed-text):")
-        .default("nomic-embed-text".to_string())
+        .default(DEFAULT_OLLAMA_BASE_URL.to_string())
         .interact_text()
         .unwrap();
 
+    // Offer the models installed on that server instead of asking for names blindly
+    let client = HttpClient::from_config(&Config::load().unwrap_or_default());
+    let catalog = match ModelCatalog::refresh(&client, &ollama_url) {
+        Ok(catalog) => catalog,
+        Err(e) => {
+            println!(
+                "{} Could not list Ollama models ({}). Enter model names manually.",
+                "⚠️".yellow(),
+                e
+            );
+            ModelCatalog::default()
+        }
+    };
+    let llm_name = pick_model(
+        "Select the LLM used for chat and suggestions:",
+        "Enter the LLM name (e.g., llama3, mistral):",
+        &catalog.generation_models(),
+        None,
+    );
+    let ollama_embedding_model = pick_model(
+        "Select the embedding model:",
+        "Enter the embedding model name (default: nomic-embed-text):",
+        &catalog.embedding_models(),
+        Some(DEFAULT_EMBEDDING_MODEL),
+    );
+
     // Supabase setup with validation and automatic database setup
     let (supabase_url, supabase_api_key) = setup_supabase_with_validation();
 
-    let profile_data = json!({
-        "profession": profession_options[profession],
-        "jobTitle": job_title_options[job_title],
-        "companyName": company_name,
-        "companySize": company_size_options[company_size],
-        "yearsExperience": experience_options[years_experience],
-        "preferredLanguage": language_options[preferred_language],
-        "workMode": work_mode_options[work_mode],
-        "llmName": llm_name,
-        "ollamaBaseUrl": ollama_url,
-        "embeddingModel": ollama_embedding_model,
-        "supabaseUrl": supabase_url,
-        "supabaseApiKey": supabase_api_key
-    });
-
-    let mut setup_path = home_dir().unwrap_or(PathBuf::from("."));
-    setup_path.push(".logswise");
-    fs::create_dir_all(&setup_path).unwrap();
-    setup_path.push("setup.json");
-    fs::write(
-        &setup_path,
-        serde_json::to_string_pretty(&profile_data).unwrap(),
-    )
-    .unwrap();
+    // Start from the existing configuration so advanced settings not asked for during
+    // setup (network options, keys from newer versions) are kept
+    let mut config = Config::load().unwrap_or_default();
+    config.profession = profession_options[profession].to_string();
+    config.job_title = job_title_options[job_title].to_string();
+    config.company_name = company_name;
+    config.company_size = company_size_options[company_size].to_string();
+    config.years_experience = experience_options[years_experience].to_string();
+    config.preferred_language = language_options[preferred_language].to_string();
+    config.work_mode = work_mode_options[work_mode].to_string();
+    config.llm_name = llm_name;
+    config.ollama_base_url = ollama_url;
+    config.embedding_model = ollama_embedding_model;
+    config.supabase_url = supabase_url;
+    config.supabase_api_key = supabase_api_key;
+    config.save().unwrap();
     println!(
         "{}",
         "✅ Setup complete! You are ready to use Logswise CLI!".green()
@@ -178,6 +189,42 @@ pub fn run_setup() {
     }
 }
 
+/// Lets the user pick one of the discovered `models`, or type a name when none fit.
+fn pick_model(
+    select_prompt: &str,
+    input_prompt: &str,
+    models: &[&ModelInfo],
+    default: Option<&str>,
+) -> String {
+    let typed = || {
+        let mut input = Input::<String>::new().with_prompt(input_prompt);
+        if let Some(default) = default {
+            input = input.default(default.to_string());
+        }
+        input.interact_text().unwrap()
+    };
+    if models.is_empty() {
+        return typed();
+    }
+
+    let mut items: Vec<String> = models.iter().map(|m| m.name.clone()).collect();
+    items.push("Other (type a name)".to_string());
+    let default_idx = default
+        .and_then(|d| models.iter().position(|m| m.matches(d)))
+        .unwrap_or(0);
+    let selection = Select::new()
+        .with_prompt(select_prompt)
+        .items(&items)
+        .default(default_idx)
+        .interact()
+        .unwrap();
+    if selection == models.len() {
+        typed()
+    } else {
+        models[selection].name.clone()
+    }
+}
+
 /// Sets up Supabase configuration with validation and automatic database schema setup.
 fn setup_supabase_with_validation() -> (String, String) {
     let supabase_url: String = Input::new()
@@ -206,7 +253,7 @@ fn setup_supabase_with_validation() -> (String, String) {
     spinner.enable_steady_tick(Duration::from_millis(100));
 
     // Test Supabase connection
-    let client = Client::new();
+    let client = HttpClient::from_config(&Config::load().unwrap_or_default());
     spinner.set_message("Testing Supabase connection...");
 
     let test_result = test_connection(&client, &config);
@@ -0,0 +1,163 @@
+use crate::context::AppContext;
+use crate::dates::{previous_working_day, Zone};
+use crate::prompts;
+use crate::report::{group_notes, Group, UNGROUPED};
+use crate::thinking;
+use crate::types::Note;
+use chrono::{Days, NaiveDate};
+use serde_json::{json, Value};
+
+/// Notes for a standup: everything since the start of the previous working day.
+pub struct StandupNotes {
+    pub since: NaiveDate,
+    pub today: NaiveDate,
+    /// Notes from `since` up to yesterday, grouped by project or tag.
+    pub done: Vec<Group>,
+    /// Notes written today, which often say what is planned.
+    pub today_notes: Vec<Note>,
+}
+
+impl StandupNotes {
+    pub fn is_empty(&self) -> bool {
+        self.done.is_empty() && self.today_notes.is_empty()
+    }
+
+    /// `Friday, Oct 16` when the last working day was not yesterday, `yesterday` otherwise.
+    pub fn since_label(&self) -> String {
+        if self.today - Days::new(1) == self.since {
+            "yesterday".to_string()
+        } else {
+            self.since.format("%A, %b %-d").to_string()
+        }
+    }
+}
+
+/// Fetches the notes written since the previous working day in `zone`.
+pub fn collect(
+    ctx: &AppContext,
+    zone: Zone,
+    project: Option<&str>,
+) -> Result<StandupNotes, String> {
+    let today = zone.today();
+    let since = previous_working_day(today);
+    let notes = ctx.store.notes_between(
+        zone.midnight_utc(since),
+        zone.midnight_utc(today + Days::new(1)),
+        project,
+    )?;
+    Ok(split(notes, zone, since, today))
+}
+
+fn split(notes: Vec<Note>, zone: Zone, since: NaiveDate, today: NaiveDate) -> StandupNotes {
+    let (today_notes, done): (Vec<Note>, Vec<Note>) = notes
+        .into_iter()
+        .partition(|n| zone.date_of(&n.created_at) == Some(today));
+    StandupNotes {
+        since,
+        today,
+        done: group_notes(&done, zone),
+        today_notes,
+    }
+}
+
+/// Formats the notes as a Yesterday/Today/Blockers draft without the LLM.
+pub fn format_raw(notes: &StandupNotes) -> String {
+    let mut out = format!("**Yesterday** ({})\n", notes.since_label());
+    if notes.done.is_empty() {
+        out.push_str("- No notes\n");
+    }
+    for group in &notes.done {
+        for item in &group.items {
+            // Drop the `[date] ` prefix the report groups carry
+            let text = item.split_once("] ").map_or(item.as_str(), |(_, t)| t);
+            if group.name == UNGROUPED {
+                out.push_str(&format!("- {text}\n"));
+            } else {
+                out.push_str(&format!("- {}: {text}\n", group.name));
+            }
+        }
+    }
+    out.push_str("\n**Today**\n");
+    if notes.today_notes.is_empty() {
+        out.push_str("- \n");
+    }
+    for note in &notes.today_notes {
+        out.push_str(&format!("- {}\n", note.content.trim()));
+    }
+    out.push_str("\n**Blockers**\n- None");
+    out
+}
+
+/// Asks the LLM to turn the notes into a Yesterday/Today/Blockers update.
+pub fn draft(ctx: &AppContext, notes: &StandupNotes) -> Result<String, String> {
+    ctx.check_generation()?;
+    let data = json!({
+        "profile": prompts::profile_data(&ctx.config),
+        "since": notes.since_label(),
+        "groups": notes
+            .done
+            .iter()
+            .map(|g| json!({ "name": g.name, "items": g.items }))
+            .collect::<Vec<Value>>(),
+        "today_notes": notes
+            .today_notes
+            .iter()
+            .map(|n| n.content.trim())
+            .collect::<Vec<&str>>(),
+    });
+    let reply = ctx.llm.generate(&ctx.prompts.render("standup", &data))?;
+    Ok(thinking::strip(&reply))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::{context, fake_llm, note, FakeStore};
+    use crate::dates::parse_date;
+
+    fn notes() -> StandupNotes {
+        let at = |id, content, time: &str| {
+            let mut n = note(id, content);
+            n.created_at = time.to_string();
+            n
+        };
+        let mut atlas = at("1", "Fixed the flaky login test", "2026-10-16T15:00:00Z");
+        atlas.project = Some("Atlas".to_string());
+        split(
+            vec![
+                atlas,
+                at("2", "Reviewed two PRs", "2026-10-16T16:00:00Z"),
+                at("3", "Plan: finish the export job", "2026-10-19T08:00:00Z"),
+            ],
+            Zone::parse("UTC").unwrap(),
+            parse_date("2026-10-16").unwrap(),
+            parse_date("2026-10-19").unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_format_raw_splits_done_and_today() {
+        let notes = notes();
+        assert_eq!(notes.since_label(), "Friday, Oct 16");
+        assert_eq!(
+            format_raw(&notes),
+            "**Yesterday** (Friday, Oct 16)\n- Atlas: Fixed the flaky login test\n- Reviewed two PRs\n\n**Today**\n- Plan: finish the export job\n\n**Blockers**\n- None"
+        );
+    }
+
+    #[test]
+    fn test_draft_sends_grouped_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let llm = fake_llm("llama3", "<think>ok</think>\n**Yesterday**\n- Fixed tests");
+        let prompts = llm.prompts.clone();
+        let ctx = context(llm, FakeStore::default(), dir.path());
+
+        assert_eq!(
+            draft(&ctx, &notes()).unwrap(),
+            "**Yesterday**\n- Fixed tests"
+        );
+        let prompt = &prompts.borrow()[0];
+        assert!(prompt.contains("Atlas:\n- [2026-10-16] Fixed the flaky login test"));
+        assert!(prompt.contains("- Plan: finish the export job"));
+    }
+}
@@ -0,0 +1,338 @@
+use crate::dates::{parse_timestamp, Zone};
+use crate::metrics::Metric;
+use crate::suggestion_log::SuggestionRecord;
+use crate::types::Note;
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Weeks shown in the notes-per-week chart when no `--since` is given.
+pub const DEFAULT_WEEKS: u64 = 12;
+/// Entries listed in each breakdown.
+const TOP_ENTRIES: usize = 5;
+/// Name used in the project breakdown for notes without one.
+const NO_PROJECT: &str = "(none)";
+
+/// Usage figures for `stats`, over all time or since a given instant.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageStats {
+    pub since: Option<DateTime<Utc>>,
+    pub notes: NoteStats,
+    /// Topics learned by `personalize learn`.
+    pub topics: Vec<String>,
+    pub generation: ModelStats,
+    pub embeddings: ModelStats,
+    pub suggestions: SuggestionStats,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteStats {
+    pub total: usize,
+    /// Notes with an embedding, when the store could count them.
+    pub embedded: Option<usize>,
+    pub embedding_coverage_percent: Option<f32>,
+    /// Notes per week, oldest week first.
+    pub per_week: Vec<WeekCount>,
+    pub by_project: Vec<Count>,
+    pub top_tags: Vec<Count>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WeekCount {
+    /// Monday the week starts on, in the user's time zone.
+    pub week_start: NaiveDate,
+    pub notes: usize,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Count {
+    pub name: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStats {
+    pub calls: usize,
+    pub failed: usize,
+    pub average_latency_ms: Option<u64>,
+    pub prompt_tokens: u64,
+    pub output_tokens: u64,
+}
+
+#[derive(Debug, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestionStats {
+    pub total: usize,
+    pub rated: usize,
+    pub helpful: usize,
+    /// Share of rated suggestions marked helpful.
+    pub acceptance_rate_percent: Option<f32>,
+}
+
+/// Inputs for [`UsageStats::compute`], already limited to the period.
+pub struct StatsInput<'a> {
+    pub notes: &'a [Note],
+    pub embedded: Option<usize>,
+    pub topics: Vec<String>,
+    pub metrics: &'a [Metric],
+    pub suggestions: &'a [SuggestionRecord],
+}
+
+impl UsageStats {
+    pub fn compute(
+        input: StatsInput,
+        since: Option<DateTime<Utc>>,
+        zone: Zone,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let in_period = |at: DateTime<Utc>| since.is_none_or(|s| at >= s);
+        let metrics: Vec<&Metric> = input.metrics.iter().filter(|m| in_period(m.at)).collect();
+        let suggestions: Vec<&SuggestionRecord> = input
+            .suggestions
+            .iter()
+            .filter(|s| in_period(s.created_at))
+            .collect();
+        let rated = suggestions.iter().filter(|s| s.feedback.is_some()).count();
+        let helpful = suggestions
+            .iter()
+            .filter(|s| s.feedback.as_ref().is_some_and(|f| f.helpful))
+            .count();
+
+        let total = input.notes.len();
+        Self {
+            since,
+            notes: NoteStats {
+                total,
+                embedded: input.embedded,
+                embedding_coverage_percent: input
+                    .embedded
+                    .filter(|_| total > 0)
+                    .map(|e| e as f32 * 100.0 / total as f32),
+                per_week: per_week(input.notes, since, zone, now),
+                by_project: top(input
+                    .notes
+                    .iter()
+                    .map(|n| n.project.clone().unwrap_or_else(|| NO_PROJECT.to_string()))),
+                top_tags: top(input.notes.iter().flat_map(|n| hashtags(&n.content))),
+            },
+            topics: input.topics,
+            generation: model_stats(&metrics, "generate"),
+            embeddings: model_stats(&metrics, "embed"),
+            suggestions: SuggestionStats {
+                total: suggestions.len(),
+                rated,
+                helpful,
+                acceptance_rate_percent: (rated > 0).then(|| helpful as f32 * 100.0 / rated as f32),
+            },
+        }
+    }
+}
+
+/// Note counts per week from the week of `since` (or [`DEFAULT_WEEKS`] back) to now.
+fn per_week(
+    notes: &[Note],
+    since: Option<DateTime<Utc>>,
+    zone: Zone,
+    now: DateTime<Utc>,
+) -> Vec<WeekCount> {
+    let monday = |date: NaiveDate| date - Days::new(date.weekday().num_days_from_monday().into());
+    let this_week = monday(zone.local(now).date());
+    let first = match since {
+        Some(since) => monday(zone.local(since).date()).min(this_week),
+        None => this_week - Days::new((DEFAULT_WEEKS - 1) * 7),
+    };
+    let mut weeks: Vec<WeekCount> = vec![];
+    let mut week = first;
+    while week <= this_week {
+        weeks.push(WeekCount {
+            week_start: week,
+            notes: 0,
+        });
+        week = week + Days::new(7);
+    }
+    for note in notes {
+        let Some(time) = parse_timestamp(&note.created_at) else {
+            continue;
+        };
+        let start = monday(zone.local(time).date());
+        if let Some(entry) = weeks.iter_mut().find(|w| w.week_start == start) {
+            entry.notes += 1;
+        }
+    }
+    weeks
+}
+
+/// The most frequent names, most frequent first (ties by name).
+fn top(names: impl Iterator<Item = String>) -> Vec<Count> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for name in names {
+        *counts.entry(name).or_default() += 1;
+    }
+    let mut counts: Vec<Count> = counts
+        .into_iter()
+        .map(|(name, count)| Count { name, count })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    counts.truncate(TOP_ENTRIES);
+    counts
+}
+
+/// Distinct lowercase `#tags` in a note.
+fn hashtags(content: &str) -> Vec<String> {
+    let mut tags: Vec<String> = content
+        .split_whitespace()
+        .filter_map(|word| word.strip_prefix('#'))
+        .map(|tag| tag.trim_end_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| format!("#{}", tag.to_lowercase()))
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+fn model_stats(metrics: &[&Metric], operation: &str) -> ModelStats {
+    let calls: Vec<&&Metric> = metrics
+        .iter()
+        .filter(|m| m.operation == operation)
+        .collect();
+    let succeeded: Vec<u64> = calls
+        .iter()
+        .filter(|m| m.success)
+        .map(|m| m.latency_ms)
+        .collect();
+    ModelStats {
+        calls: calls.len(),
+        failed: calls.len() - succeeded.len(),
+        average_latency_ms: (!succeeded.is_empty())
+            .then(|| succeeded.iter().sum::<u64>() / succeeded.len() as u64),
+        prompt_tokens: calls.iter().filter_map(|m| m.prompt_tokens).sum(),
+        output_tokens: calls.iter().filter_map(|m| m.output_tokens).sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::note;
+    use crate::suggestion_log::Feedback;
+
+    fn note_on(id: &str, content: &str, at: &str, project: Option<&str>) -> Note {
+        Note {
+            created_at: at.to_string(),
+            project: project.map(str::to_string),
+            ..note(id, content)
+        }
+    }
+
+    fn metric(operation: &str, latency_ms: u64, success: bool, tokens: Option<u64>) -> Metric {
+        Metric {
+            at: parse_timestamp("2026-10-15T12:00:00Z").unwrap(),
+            operation: operation.to_string(),
+            model: "llama3".to_string(),
+            latency_ms,
+            bytes: None,
+            prompt_tokens: tokens,
+            output_tokens: tokens,
+            success,
+        }
+    }
+
+    #[test]
+    fn test_compute_usage_stats() {
+        let zone = Zone::parse("UTC").unwrap();
+        let now = parse_timestamp("2026-10-19T18:00:00Z").unwrap();
+        let since = parse_timestamp("2026-10-06T00:00:00Z").unwrap();
+        let notes = vec![
+            note_on(
+                "1",
+                "Fixed #CI flake",
+                "2026-10-06T09:00:00Z",
+                Some("Atlas"),
+            ),
+            note_on(
+                "2",
+                "More #ci work, #infra",
+                "2026-10-08T09:00:00Z",
+                Some("Atlas"),
+            ),
+            note_on("3", "Hiring sync", "2026-10-19T09:00:00Z", None),
+        ];
+        let mut rated = SuggestionRecord::new("q", "planning", "work", "llama3", "text");
+        rated.feedback = Some(Feedback {
+            helpful: true,
+            rating: 5,
+            reasons: vec![],
+            comment: None,
+            rated_at: now,
+        });
+        let mut old = SuggestionRecord::new("q", "planning", "work", "llama3", "text");
+        old.created_at = parse_timestamp("2026-01-01T00:00:00Z").unwrap();
+        let suggestions = vec![
+            rated,
+            SuggestionRecord::new("q", "planning", "work", "llama3", "text"),
+            old,
+        ];
+        let metrics = vec![
+            metric("generate", 1000, true, Some(100)),
+            metric("generate", 3000, true, Some(50)),
+            metric("generate", 9000, false, None),
+            metric("embed", 40, true, None),
+        ];
+
+        let stats = UsageStats::compute(
+            StatsInput {
+                notes: &notes,
+                embedded: Some(2),
+                topics: vec!["deploy kubernetes".to_string()],
+                metrics: &metrics,
+                suggestions: &suggestions,
+            },
+            Some(since),
+            zone,
+            now,
+        );
+
+        assert_eq!(stats.notes.total, 3);
+        let coverage = stats.notes.embedding_coverage_percent.unwrap();
+        assert!((coverage - 66.7).abs() < 0.1);
+        let weeks: Vec<usize> = stats.notes.per_week.iter().map(|w| w.notes).collect();
+        assert_eq!(weeks, vec![2, 0, 1]);
+        assert_eq!(stats.notes.by_project[0].name, "Atlas");
+        assert_eq!(stats.notes.by_project[0].count, 2);
+        assert_eq!(stats.notes.top_tags[0].name, "#ci");
+        assert_eq!(stats.notes.top_tags[0].count, 2);
+
+        assert_eq!(stats.generation.calls, 3);
+        assert_eq!(stats.generation.failed, 1);
+        assert_eq!(stats.generation.average_latency_ms, Some(2000));
+        assert_eq!(stats.generation.output_tokens, 150);
+        assert_eq!(stats.embeddings.calls, 1);
+
+        assert_eq!(stats.suggestions.total, 2);
+        assert_eq!(stats.suggestions.acceptance_rate_percent, Some(100.0));
+    }
+
+    #[test]
+    fn test_default_window_has_twelve_weeks() {
+        let stats = UsageStats::compute(
+            StatsInput {
+                notes: &[],
+                embedded: None,
+                topics: vec![],
+                metrics: &[],
+                suggestions: &[],
+            },
+            None,
+            Zone::Local,
+            Utc::now(),
+        );
+        assert_eq!(stats.notes.per_week.len(), DEFAULT_WEEKS as usize);
+        assert_eq!(stats.notes.embedding_coverage_percent, None);
+        assert_eq!(stats.suggestions.acceptance_rate_percent, None);
+    }
+}
@@ -1,39 +1,45 @@
+use chrono::Utc;
 use colored::*;
-use indicatif::{ProgressBar, ProgressStyle};
-use reqwest::blocking::Client;
-use std::time::Duration;
 
+use crate::adaptation::Adaptation;
+use crate::budget::{self, BudgetReport};
+use crate::context::AppContext;
+use crate::intent::{IntentMethod, QueryIntent};
 use crate::personalization::UserContext;
-use crate::services::ollama;
-use crate::services::supabase;
-use crate::utils;
-
-pub fn get_suggestions(query: &str) {
-    let profile = match utils::load_profile() {
-        Ok(p) => p,
-        Err(e) => {
-            println!("{}", format!("Error loading profile: {e}").red());
-            println!("Please run 'logswise-cli setup' first.");
-            return;
-        }
-    };
-
-    // Load enhanced user context for personalization
-    let mut user_context = match UserContext::load_or_create() {
-        Ok(ctx) => ctx,
-        Err(e) => {
-            println!(
-                "{}",
-                format!("Warning: Could not load enhanced context: {e}").yellow()
-            );
-            println!("Using basic personalization. Run 'logswise-cli personalize' for better suggestions.");
-            // Continue with basic profile-based suggestions
-            get_basic_suggestions(query, &profile);
-            return;
-        }
-    };
+use crate::prompts::Prompts;
+use crate::suggestion_log::{Feedback, FeedbackReason, SuggestionRecord};
+use crate::thinking::Reply;
+use crate::types::Note;
+use crate::utils::spinner;
+
+/// Width queries are cut to in `suggestion history`.
+const QUERY_WIDTH: usize = 50;
+/// Notes given to the model as context.
+const CONTEXT_NOTES: usize = 5;
+/// Notes retrieved before re-ranking by feedback, so demoted ones can be replaced.
+const RETRIEVAL_POOL: usize = 10;
+/// Query used by `personalize explain` when none is given.
+const EXPLAIN_QUERY: &str = "What should I focus on next?";
+
+/// Result of a suggestion request.
+#[derive(Debug)]
+pub enum SuggestionReply {
+    /// Embedding-only mode: the notes most relevant to the query.
+    Notes(Vec<Note>),
+    /// Normal LLM mode: the personalized suggestions with the model's reasoning, the
+    /// intent that shaped them, the notes given as context and how the prompt was fitted
+    /// into the context window.
+    Suggestions {
+        reply: Reply,
+        budget: Box<BudgetReport>,
+        intent: QueryIntent,
+        notes: Vec<Note>,
+    },
+}
 
-    let llm_name = profile["llmName"].as_str().unwrap_or("").to_lowercase();
+/// Prints suggestions for `query`. With `explain`, also shows how the query was classified.
+pub fn get_suggestions(ctx: &AppContext, query: &str, explain: bool) {
+    let llm_name = ctx.llm_name();
     if llm_name.is_empty() {
         println!(
             "{}",
@@ -42,334 +48,551 @@ pub fn get_suggestions(query: &str) {
         return;
     }
 
-    let ollama_base_url = profile["ollamaBaseUrl"]
-        .as_str()
-        .unwrap_or("http://localhost:11434");
-    let ollama_embedding_url = format!("{ollama_base_url}/api/embeddings");
-    let ollama_generate_url = format!("{ollama_base_url}/api/generate");
-    let ollama_model = profile["embeddingModel"]
-        .as_str()
-        .unwrap_or("nomic-embed-text");
-
-    let spinner = ProgressBar::new_spinner();
-    spinner.set_style(
-        ProgressStyle::default_spinner()
-            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    spinner.enable_steady_tick(Duration::from_millis(100));
-    spinner.set_message("Loading enhanced user context and preparing personalized suggestions...");
-
-    let _notes_context = String::new();
-    let config = match utils::load_supabase_config() {
-        Ok(cfg) => cfg,
-        Err(e) => {
-            println!("{}", format!("Error loading Supabase config: {e}").red());
-            println!("Please run 'logswise-cli setup' first.");
-            return;
-        }
-    };
+    // Load enhanced user context for personalization
+    let mut user_context = UserContext::load_for(ctx.config.clone());
 
-    let client = Client::new();
-    let embedding_models = [
-        "nomic-embed-text",
-        "bge-base-en",
-        "all-minilm",
-        // Add more known embedding models here if needed
-    ];
-    let is_embedding = embedding_models
-        .iter()
-        .any(|m| llm_name == *m || llm_name.starts_with(m));
+    let spinner =
+        spinner("Loading enhanced user context and preparing personalized suggestions...");
 
-    if is_embedding {
+    if ctx.is_embedding_only() {
         // Only perform embedding and semantic search, print results, and exit (no LLM generation)
         println!(
             "⚡ Running in embedding-only mode (semantic search, no LLM generation). Model: {}",
             llm_name.cyan()
         );
-        // 1. Generate embedding for the query using local Ollama
-        let query_embedding = match ollama::generate_embedding(
-            &client,
-            &ollama_embedding_url,
-            ollama_model,
-            query,
-        ) {
-            Ok(embedding) => embedding,
-            Err(msg) => {
-                spinner.finish_and_clear();
+    } else {
+        println!(
+            "🔎 Using Ollama model: {} with enhanced personalization",
+            llm_name.cyan()
+        );
+    }
+
+    let result = suggest(ctx, &user_context, query, |msg| {
+        spinner.set_message(msg.to_string())
+    });
+    spinner.finish_and_clear();
+
+    match result {
+        Ok(SuggestionReply::Notes(notes)) => {
+            if !notes.is_empty() {
+                println!("\nRelevant Notes:");
+                for (i, note) in notes.iter().enumerate() {
+                    println!("{}. {}", i + 1, note.content);
+                }
+            } else {
+                println!("No relevant notes found.");
+            }
+            if explain {
                 println!(
                     "{}",
-                    "❌ Could not generate embedding for query. No semantic search can be made."
-                        .red()
+                    "ℹ️  Intent detection is skipped in embedding-only mode.".bright_black()
                 );
-                println!("{}", msg.yellow());
-                println!("➡️  Please check that your embedding model is pulled and running in Ollama (e.g., 'ollama pull nomic-embed-text'), and that OLLAMA_EMBEDDING_MODEL is set correctly.");
-                return;
             }
-        };
-        // 2. Query Supabase for most similar notes (top 5)
-        let notes = supabase::semantic_search_notes(&client, &config, &query_embedding, 5);
-        if !notes.is_empty() {
-            println!("\nRelevant Notes:");
-            for (i, content) in notes.iter().enumerate() {
-                println!("{}. {}", i + 1, content);
-            }
-        } else {
-            println!("No relevant notes found.");
         }
-        spinner.finish_and_clear();
-        return;
-    }
-
-    // 1. Generate embedding for the query using local Ollama
-    let query_embedding = match ollama::generate_embedding(
-        &client,
-        &ollama_embedding_url,
-        ollama_model,
-        query,
-    ) {
-        Ok(embedding) => embedding,
-        Err(msg) => {
-            spinner.finish_and_clear();
-            println!(
-                "{}",
-                "❌ Could not generate embedding for query. No suggestions can be made.".red()
-            );
-            println!("{}", msg.yellow());
-            println!("➡️  Please check that your embedding model is pulled and running in Ollama (e.g., 'ollama pull nomic-embed-text'), and that OLLAMA_EMBEDDING_MODEL is set correctly.");
-            return;
-        }
-    };
-
-    // 2. Query Supabase for most similar notes (top 5)
-    let notes = supabase::semantic_search_notes(&client, &config, &query_embedding, 5);
-
-    // Generate enhanced prompt using user context
-    let full_prompt = user_context.generate_llm_context(query, &notes);
-
-    // Create personalized instruction based on user preferences
-    let personalized_instruction = create_personalized_instruction(&user_context);
-    let complete_prompt = format!("{full_prompt}\n\n{personalized_instruction}");
-
-    println!(
-        "🔎 Using Ollama model: {} with enhanced personalization",
-        llm_name.cyan()
-    );
-    spinner.set_message("Ollama: Generating personalized suggestions...");
-
-    match ollama::generate_suggestion(&client, &ollama_generate_url, &llm_name, &complete_prompt) {
-        Ok(final_response) => {
-            spinner.finish_and_clear();
-            if !final_response.trim().is_empty() {
+        Ok(SuggestionReply::Suggestions {
+            reply,
+            budget,
+            intent,
+            notes,
+        }) => {
+            if explain {
+                print_intent(&intent);
+                budget.print();
+            }
+            if !reply.answer.is_empty() {
                 println!(
                     "\n==================== 💡 Personalized Suggestions ====================\n"
                 );
-                let final_answer = if let Some(idx) = final_response.find("</think>") {
-                    final_response[(idx + "</think>".len())..].trim()
-                } else {
-                    final_response.trim()
-                };
+                reply.print_thinking(ctx.generation.thinking.unwrap_or_default());
+                let final_answer = &reply.answer;
                 println!(
                     "----------------------------------------\n{final_answer}\n----------------------------------------\n"
                 );
 
-                // Update interaction history (simplified for now)
-                // Note: We don't store the actual query to prevent hallucination
+                let mut record = SuggestionRecord::new(
+                    query,
+                    &intent.intent_type,
+                    &intent.context_type,
+                    llm_name,
+                    final_answer,
+                );
+                record.project = ctx.active_project().map(str::to_string);
+                record.note_ids = notes.into_iter().map(|n| n.id).collect();
+                match ctx.suggestions.record(&record) {
+                    Ok(_) => println!(
+                        "{}",
+                        format!(
+                            "Suggestion {} · rate it with 'logswise-cli feedback {}'",
+                            record.id, record.id
+                        )
+                        .bright_black()
+                    ),
+                    Err(e) => println!(
+                        "{}",
+                        format!("Warning: Could not save suggestion history: {e}").yellow()
+                    ),
+                }
+
+                // Only the intent is kept as a topic; the query itself stays in the
+                // suggestion history so it never leaks into later prompts
                 user_context
                     .interaction_history
                     .recent_topics
-                    .push("suggestion_request".to_string());
+                    .push(intent.intent_type.clone());
                 if user_context.interaction_history.recent_topics.len() > 10 {
                     user_context.interaction_history.recent_topics.remove(0);
                 }
                 let _ = user_context.save(); // Save updated context
             } else {
                 println!("{} {}", "❌ No suggestion from model:".red(), llm_name);
+                if !reply.thinking.is_empty() {
+                    println!(
+                        "{}",
+                        "The model stopped while still thinking; try a higher --max-tokens or --think false."
+                            .yellow()
+                    );
+                }
             }
         }
         Err(msg) => {
-            spinner.finish_and_clear();
             println!("{}", msg.red());
         }
     }
 }
 
-// Fallback to basic suggestions if enhanced context is not available
-fn get_basic_suggestions(_query: &str, profile: &serde_json::Value) {
-    // This is the original basic implementation
-    let _user_info = format!(
-        "User Info:\n- Profession: {}\n- Job Title: {}\n- Company Name: {}\n- Company Size: {}",
-        profile["profession"].as_str().unwrap_or(""),
-        profile["jobTitle"].as_str().unwrap_or(""),
-        profile["companyName"].as_str().unwrap_or(""),
-        profile["companySize"].as_str().unwrap_or("")
+/// Retrieves the notes relevant to `query` and, unless in embedding-only mode, generates
+/// personalized suggestions. `progress` receives status updates for the UI.
+pub fn suggest<F: Fn(&str)>(
+    ctx: &AppContext,
+    user_context: &UserContext,
+    query: &str,
+    progress: F,
+) -> Result<SuggestionReply, String> {
+    // 1. Generate embedding for the query using local Ollama
+    let query_embedding = ctx.llm.embed(query).map_err(|msg| {
+        format!(
+            "❌ Could not generate embedding for query. No suggestions can be made.\n{msg}\n➡️  Please check that your embedding model is pulled and running in Ollama (e.g., 'ollama pull {}').",
+            ctx.llm.embedding_model()
+        )
+    })?;
+
+    // 2. Query Supabase for the most similar notes, moving those that went into
+    //    rejected suggestions to the back
+    let adaptation = Adaptation::from_feedback(
+        &user_context.interaction_history,
+        &ctx.suggestions.all().unwrap_or_default(),
     );
+    let notes = ctx
+        .store
+        .semantic_search(&query_embedding, RETRIEVAL_POOL, ctx.active_project())
+        .unwrap_or_default();
+    let notes = adaptation.rerank(notes, CONTEXT_NOTES);
+    if ctx.is_embedding_only() {
+        return Ok(SuggestionReply::Notes(notes));
+    }
 
-    // Continue with basic implementation...
-    println!(
-        "Using basic suggestion mode. Run 'logswise-cli personalize' for enhanced suggestions."
+    // 3. Classify the query against labelled examples (keywords if embedding fails)
+    progress("Detecting query intent...");
+    let intent = ctx.intents.classify(
+        ctx.llm.as_ref(),
+        query,
+        Some(&query_embedding),
+        &user_context.project_names(),
     );
-}
-
-fn create_personalized_instruction(context: &UserContext) -> String {
-    let mut instruction = String::new();
 
-    // Advanced AI system prompt with enterprise-grade constraints
-    instruction.push_str("=== ADVANCED AI ASSISTANT CONFIGURATION ===\n");
-    instruction.push_str("You are an elite AI advisor specializing in professional development.\n");
-    instruction.push_str(
-        "Your expertise: Strategic thinking, project management, career advancement.\n\n",
+    let (complete_prompt, budget) = build_suggestion_prompt(
+        &ctx.prompts,
+        user_context,
+        query,
+        &intent,
+        &notes,
+        &adaptation,
+        ctx.prompt_budget(),
     );
+    progress("Ollama: Generating personalized suggestions...");
+    let text = ctx.llm.generate(&complete_prompt)?;
+    Ok(SuggestionReply::Suggestions {
+        reply: Reply::parse(&text),
+        budget: Box::new(budget),
+        intent,
+        notes,
+    })
+}
 
-    instruction.push_str("🎯 CORE PRINCIPLES:\n");
-    instruction.push_str("1. EVIDENCE-BASED: All suggestions grounded in verified user data\n");
-    instruction.push_str("2. CONTEXT-AWARE: Use conversation context without inventing details\n");
-    instruction.push_str("3. ACTIONABLE: Provide specific, measurable recommendations\n");
-    instruction.push_str("4. STRATEGIC: Balance immediate needs with long-term goals\n");
-    instruction.push_str("5. PROFESSIONAL: Maintain enterprise-level communication standards\n\n");
-
-    instruction.push_str("🚫 ABSOLUTE CONSTRAINTS:\n");
-    instruction.push_str("- NEVER fabricate relationships, conversations, or commitments\n");
-    instruction
-        .push_str("- NEVER assume details about team dynamics or organizational structure\n");
-    instruction.push_str("- ALWAYS ground advice in documented projects and verified challenges\n");
-    instruction.push_str("- ALWAYS include success metrics and follow-up mechanisms\n\n");
-
-    // Advanced response formatting based on communication style
-    instruction.push_str("📝 RESPONSE STYLE CONFIGURATION:\n");
-    match context.preferences.communication_style.as_str() {
-        "concise" => {
-            instruction.push_str("FORMAT: Executive Summary Style\n");
-            instruction.push_str("- Use bullet points and numbered lists\n");
-            instruction.push_str("- Maximum 3 key recommendations\n");
-            instruction.push_str("- Include one-line success metrics for each\n");
-            instruction.push_str("- Total response: 150-250 words\n");
-        }
-        "detailed" => {
-            instruction.push_str("FORMAT: Comprehensive Analysis Style\n");
-            instruction.push_str("- Provide detailed reasoning and context\n");
-            instruction.push_str("- Include implementation steps and timeline\n");
-            instruction.push_str("- Address potential challenges and mitigation\n");
-            instruction.push_str("- Total response: 300-500 words\n");
-        }
-        "casual" => {
-            instruction.push_str("FORMAT: Collaborative Advisor Style\n");
-            instruction.push_str("- Use friendly, encouraging language\n");
-            instruction.push_str("- Include motivational elements\n");
-            instruction.push_str("- Make suggestions feel approachable and doable\n");
-            instruction.push_str("- Use emojis strategically for clarity\n");
+/// Shows the detected intent, context type and the examples that decided them.
+fn print_intent(intent: &QueryIntent) {
+    println!("\n{}", "🧭 Query Analysis".cyan().bold());
+    match intent.method {
+        IntentMethod::Embeddings => {
+            println!(
+                "  Intent: {} ({:.0}% confidence)",
+                intent.intent_type.green(),
+                intent.intent_confidence * 100.0
+            );
+            println!(
+                "  Context: {} ({:.0}% confidence)",
+                intent.context_type.green(),
+                intent.context_confidence * 100.0
+            );
         }
-        "professional" => {
-            instruction.push_str("FORMAT: Enterprise Consultant Style\n");
-            instruction.push_str("- Use formal, structured language\n");
-            instruction.push_str("- Focus on business impact and ROI\n");
-            instruction.push_str("- Include stakeholder considerations\n");
-            instruction.push_str("- Emphasize measurable outcomes\n");
+        IntentMethod::Keywords => {
+            println!("  Intent: {}", intent.intent_type.green());
+            println!("  Context: {}", intent.context_type.green());
         }
-        _ => {
-            instruction.push_str("FORMAT: Balanced Professional Style\n");
-            instruction.push_str("- Clear, structured communication\n");
-            instruction.push_str("- Professional yet approachable tone\n");
-            instruction.push_str("- Focus on practical implementation\n");
+    }
+    println!("  Urgency: {}", intent.urgency_level);
+    match intent.method {
+        IntentMethod::Embeddings => {
+            println!("  Nearest examples:");
+            for n in &intent.neighbours {
+                println!(
+                    "    {:.2}  {}  \"{}\"",
+                    n.similarity,
+                    n.label.bright_black(),
+                    n.example
+                );
+            }
         }
+        IntentMethod::Keywords => println!(
+            "  {}",
+            "Detected by keywords (embeddings unavailable)".yellow()
+        ),
     }
+}
 
-    // Adapt based on learning style
-    match context.learning_style.preferred_format.as_str() {
-        "hands_on" => {
-            instruction.push_str("LEARNING APPROACH: Prioritize practical exercises, code examples, and actionable tasks. Include specific implementation steps.\n");
-        }
-        "reading" => {
-            instruction.push_str("LEARNING APPROACH: Suggest articles, documentation, and written resources. Include book recommendations when relevant.\n");
-        }
-        "videos" => {
-            instruction.push_str("LEARNING APPROACH: Suggest video tutorials, online courses, and visual learning resources.\n");
+/// Renders the `suggestion` prompt from the user context, relevant notes and what
+/// earlier feedback asks for, trimmed to `budget` tokens.
+fn build_suggestion_prompt(
+    prompts: &Prompts,
+    user_context: &UserContext,
+    query: &str,
+    intent: &QueryIntent,
+    notes: &[Note],
+    adaptation: &Adaptation,
+    budget: usize,
+) -> (String, BudgetReport) {
+    let note_contents: Vec<String> = notes.iter().map(|n| n.content.clone()).collect();
+    let mut data = user_context.prompt_data(query, intent, &note_contents);
+    data["feedback"] = adaptation.prompt_data(&intent.intent_type);
+    budget::assemble(
+        prompts,
+        "suggestion",
+        data,
+        &budget::SUGGESTION_SECTIONS,
+        budget,
+    )
+}
+
+/// Shows what the recorded feedback has learned and the lines it adds to the suggestion
+/// prompt for `query`.
+pub fn explain_adaptation(ctx: &AppContext, query: Option<&str>) {
+    let user_context = UserContext::load_for(ctx.config.clone());
+    let records = match ctx.suggestions.all() {
+        Ok(records) => records,
+        Err(e) => {
+            println!("{}", format!("❌ {e}").red());
+            return;
         }
-        "peer_learning" => {
-            instruction.push_str("LEARNING APPROACH: Emphasize collaborative learning, team discussions, mentoring opportunities, and community engagement.\n");
+    };
+    let adaptation = Adaptation::from_feedback(&user_context.interaction_history, &records);
+    println!(
+        "{}",
+        "🔁 How feedback shapes your suggestions".cyan().bold()
+    );
+    if adaptation.rated == 0 {
+        println!(
+            "No rated suggestions yet. Rate one with 'logswise-cli feedback' and suggestions will adapt."
+        );
+        return;
+    }
+    println!(
+        "Rated suggestions: {} ({:.0}% helpful)",
+        adaptation.rated,
+        adaptation.helpful_share * 100.0
+    );
+
+    if !adaptation.intent_scores.is_empty() {
+        println!("\n{}", "Satisfaction by kind of request:".yellow().bold());
+        for (intent, score) in &adaptation.intent_scores {
+            println!("  {:<22} {:.0}%", intent.replace('_', " "), score * 100.0);
         }
-        _ => {
-            instruction.push_str(
-                "LEARNING APPROACH: Mix different learning methods based on the topic.\n",
+    }
+    if !adaptation.reasons.is_empty() {
+        let labels: Vec<&str> = adaptation.reasons.iter().map(|r| r.label()).collect();
+        println!(
+            "\n{} {}",
+            "Recent complaints:".yellow().bold(),
+            labels.join(", ")
+        );
+    }
+    if !adaptation.note_penalties.is_empty() {
+        println!(
+            "\n{}",
+            "Notes moved down in retrieval (used in rejected suggestions):"
+                .yellow()
+                .bold()
+        );
+        let ids: Vec<String> = adaptation
+            .note_penalties
+            .iter()
+            .map(|(id, _)| id.clone())
+            .collect();
+        let notes = ctx.store.notes_by_ids(&ids).unwrap_or_default();
+        for (id, penalty) in &adaptation.note_penalties {
+            let content = notes
+                .iter()
+                .find(|n| &n.id == id)
+                .map(|n| shorten(&n.content, QUERY_WIDTH))
+                .unwrap_or_default();
+            println!(
+                "  {} {} {}",
+                crate::note_handler::short_id(id).green(),
+                format!("-{penalty}").red(),
+                content
             );
         }
     }
 
-    // Add focus areas
-    if !context.preferences.focus_areas.is_empty() {
-        instruction.push_str(&format!(
-            "FOCUS PRIORITIES: Emphasize suggestions related to: {}\n",
-            context.preferences.focus_areas.join(", ")
-        ));
+    let query = query.unwrap_or(EXPLAIN_QUERY);
+    let intent = ctx
+        .intents
+        .classify(ctx.llm.as_ref(), query, None, &user_context.project_names());
+    let render = |adaptation: &Adaptation| {
+        build_suggestion_prompt(
+            &ctx.prompts,
+            &user_context,
+            query,
+            &intent,
+            &[],
+            adaptation,
+            ctx.prompt_budget(),
+        )
+        .0
+    };
+    let baseline = render(&Adaptation::default());
+    let adapted = render(&adaptation);
+    let before: Vec<&str> = baseline.lines().collect();
+    let added: Vec<&str> = adapted
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !before.contains(l))
+        .collect();
+    println!(
+        "\n{} \"{}\" ({})",
+        "Prompt changes for".yellow().bold(),
+        query,
+        intent.intent_type.replace('_', " ")
+    );
+    if added.is_empty() {
+        println!("  Feedback does not change the prompt for this request yet.");
+    } else {
+        for line in added {
+            println!("  {}", line.green());
+        }
     }
+}
 
-    // Add complexity guidance
-    match context.learning_style.complexity_preference.as_str() {
-        "beginner" => {
-            instruction.push_str("COMPLEXITY: Provide beginner-friendly suggestions with step-by-step guidance. Avoid advanced concepts without explanation.\n");
-        }
-        "advanced" => {
-            instruction.push_str("COMPLEXITY: Feel free to suggest advanced techniques and deep technical concepts. Assume strong foundational knowledge.\n");
-        }
-        "adaptive" => {
-            instruction.push_str("COMPLEXITY: Adapt complexity based on the topic and provide both beginner and advanced options when relevant.\n");
-        }
-        _ => {
-            instruction.push_str(
-                "COMPLEXITY: Use intermediate-level suggestions with clear explanations.\n",
-            );
+/// Ratings given on the command line; anything missing is asked for interactively.
+#[derive(Debug, Default)]
+pub struct FeedbackInput {
+    pub helpful: Option<bool>,
+    pub rating: Option<u8>,
+    pub reasons: Vec<FeedbackReason>,
+    pub comment: Option<String>,
+}
+
+/// Rates the suggestion with id `key` (the latest one when `None`) and feeds the rating
+/// into the personalization profile.
+pub fn give_feedback(ctx: &AppContext, key: Option<&str>, input: FeedbackInput) {
+    let record = match ctx.suggestions.find(key) {
+        Ok(record) => record,
+        Err(e) => {
+            println!("{}", format!("❌ {e}").red());
+            return;
         }
+    };
+
+    println!("{}", "📝 Suggestion Feedback".cyan().bold());
+    print_record_summary(ctx, &record);
+    if let Some(previous) = &record.feedback {
+        println!(
+            "{}",
+            format!(
+                "Already rated {}/5; a new rating replaces it.",
+                previous.rating
+            )
+            .bright_black()
+        );
     }
+    println!();
+
+    let feedback = match input.rating {
+        Some(rating) => Feedback {
+            helpful: input.helpful.unwrap_or(rating >= 4),
+            rating,
+            reasons: input.reasons,
+            comment: input.comment,
+            rated_at: Utc::now(),
+        },
+        None => match collect_suggestion_feedback(input) {
+            Ok(feedback) => feedback,
+            Err(e) => {
+                println!("{}", format!("❌ Feedback collection failed: {e}").red());
+                return;
+            }
+        },
+    };
 
-    // Add feedback preference
-    match context.learning_style.feedback_preference.as_str() {
-        "immediate" => {
-            instruction.push_str("TRACKING: Include ways to get immediate feedback and quick wins. Suggest daily or weekly check-ins.\n");
-        }
-        "milestone" => {
-            instruction.push_str("TRACKING: Focus on milestone-based progress tracking. Suggest monthly or quarterly reviews.\n");
+    let record = match ctx.suggestions.rate(&record.id, feedback) {
+        Ok(record) => record,
+        Err(e) => {
+            println!("{}", format!("❌ Could not save feedback: {e}").red());
+            return;
         }
-        _ => {
-            instruction
-                .push_str("TRACKING: Include periodic progress checks and feedback mechanisms.\n");
+    };
+    let feedback = record.feedback.as_ref().expect("just rated");
+    let mut user_context = UserContext::load_for(ctx.config.clone());
+    user_context.update_interaction_history(
+        &record.intent,
+        feedback.helpful,
+        feedback.satisfaction(),
+    );
+    if let Err(e) = user_context.save() {
+        println!(
+            "{}",
+            format!("Warning: Could not update personalization: {e}").yellow()
+        );
+    }
+    println!(
+        "{}",
+        "✅ Thank you for your feedback! We'll use this to improve future suggestions.".green()
+    );
+}
+
+/// Lists recent suggestions, newest first, with their ratings.
+pub fn show_history(ctx: &AppContext, count: usize, unrated: bool) {
+    let records = match ctx.suggestions.all() {
+        Ok(records) => records,
+        Err(e) => {
+            println!("{}", format!("❌ {e}").red());
+            return;
         }
+    };
+    let shown: Vec<&SuggestionRecord> = records
+        .iter()
+        .rev()
+        .filter(|r| !unrated || r.feedback.is_none())
+        .take(count)
+        .collect();
+    if shown.is_empty() {
+        println!("{}", "No suggestions to show.".yellow());
+        return;
     }
 
-    instruction.push_str("\n🎯 FINAL EXECUTION PROTOCOL:\n");
-    instruction.push_str("ANALYZE the query intent and map to relevant project context\n");
-    instruction.push_str("SYNTHESIZE 2-4 high-impact recommendations with:\n");
-    instruction.push_str("├─ SPECIFIC ACTIONS: What exactly to do\n");
-    instruction.push_str("├─ SUCCESS METRICS: How to measure progress\n");
-    instruction.push_str("├─ TIMELINE: When to complete each action\n");
-    instruction.push_str("├─ STAKEHOLDER IMPACT: Who benefits and how\n");
-    instruction.push_str("└─ FOLLOW-UP: Next review point or checkpoint\n\n");
-
-    instruction.push_str("Connect ALL advice to their documented:\n");
-    instruction.push_str(
-        "✓ Projects: Vena (high-pressure, team challenges) + logswise-cli (solo, learning)\n",
+    let zone = ctx.zone();
+    let now = Utc::now();
+    println!("{}", "💡 Suggestion History".cyan().bold());
+    for record in shown {
+        let rating = match &record.feedback {
+            Some(f) => format!(
+                "{}{}",
+                "★".repeat(f.rating.into()),
+                "☆".repeat(5 - usize::from(f.rating.min(5)))
+            )
+            .yellow(),
+            None => "unrated".bright_black(),
+        };
+        println!(
+            "{} {} {}  {}",
+            record.id.green(),
+            rating,
+            shorten(&record.query, QUERY_WIDTH),
+            format!(
+                "({}, {})",
+                record.intent,
+                zone.describe(&record.created_at.to_rfc3339(), now)
+            )
+            .bright_black()
+        );
+    }
+    println!(
+        "{}",
+        "Use 'suggestion show <id>' for the full text and 'feedback <id>' to rate one."
+            .bright_black()
     );
-    instruction.push_str("✓ Career Goals: Senior developer advancement\n");
-    instruction.push_str("✓ Current Challenges: Platform/business role clarity, team dynamics, skill development\n\n");
+}
 
-    instruction.push_str("EXCELLENCE STANDARD: Each recommendation should be:\n");
-    instruction.push_str("• Immediately actionable within 24-48 hours\n");
-    instruction.push_str("• Measurable with clear success criteria\n");
-    instruction.push_str("• Aligned with their career progression goals\n");
-    instruction.push_str("• Contextually relevant to their current situation\n");
+/// Prints a recorded suggestion in full, with its rating.
+pub fn show_suggestion(ctx: &AppContext, key: Option<&str>) {
+    let record = match ctx.suggestions.find(key) {
+        Ok(record) => record,
+        Err(e) => {
+            println!("{}", format!("❌ {e}").red());
+            return;
+        }
+    };
+    print_record_summary(ctx, &record);
+    println!(
+        "----------------------------------------\n{}\n----------------------------------------",
+        record.text
+    );
+    match &record.feedback {
+        Some(f) => {
+            let helpful = if f.helpful { "helpful" } else { "not helpful" };
+            println!("Rated {}/5 ({helpful})", f.rating);
+            if let Some(comment) = &f.comment {
+                println!("Comment: {comment}");
+            }
+        }
+        None => println!(
+            "{}",
+            format!("Not rated yet: 'logswise-cli feedback {}'", record.id).bright_black()
+        ),
+    }
+}
 
-    instruction
+fn print_record_summary(ctx: &AppContext, record: &SuggestionRecord) {
+    println!("Suggestion {}: \"{}\"", record.id.green(), record.query);
+    let project = record
+        .project
+        .as_deref()
+        .map(|p| format!(" · project {p}"))
+        .unwrap_or_default();
+    println!(
+        "{}",
+        format!(
+            "{} · {} · {}{project}",
+            ctx.zone()
+                .describe(&record.created_at.to_rfc3339(), Utc::now()),
+            record.intent,
+            record.model
+        )
+        .bright_black()
+    );
 }
 
-// Helper function to collect user feedback on suggestions
-pub fn collect_suggestion_feedback(_suggestion_category: &str) -> Result<(bool, f32), String> {
-    use dialoguer::{Confirm, Select};
+fn shorten(text: &str, width: usize) -> String {
+    let line = text.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > width {
+        let cut: String = line.chars().take(width - 1).collect();
+        format!("{cut}…")
+    } else {
+        line.to_string()
+    }
+}
 
-    let accepted = Confirm::new()
-        .with_prompt("Did you find this suggestion helpful?")
-        .default(true)
-        .interact()
-        .map_err(|e| format!("Input error: {e}"))?;
+/// Asks whether a suggestion helped, how satisfied the user was and, when they were
+/// not, what was wrong. Answers already given in `input` are not asked again.
+fn collect_suggestion_feedback(input: FeedbackInput) -> Result<Feedback, String> {
+    use dialoguer::{Confirm, Input, MultiSelect, Select};
+
+    let helpful = match input.helpful {
+        Some(helpful) => helpful,
+        None => Confirm::new()
+            .with_prompt("Did you find this suggestion helpful?")
+            .default(true)
+            .interact()
+            .map_err(|e| format!("Input error: {e}"))?,
+    };
 
     let satisfaction_options = vec![
         "Very satisfied (5/5)",
@@ -386,20 +609,53 @@ pub fn collect_suggestion_feedback(_suggestion_category: &str) -> Result<(bool,
         .interact()
         .map_err(|e| format!("Input error: {e}"))?;
 
-    let satisfaction = match satisfaction_idx {
-        0 => 1.0,
-        1 => 0.8,
-        2 => 0.6,
-        3 => 0.4,
-        4 => 0.2,
-        _ => 0.6,
+    let rating = 5 - satisfaction_idx.min(4) as u8;
+
+    let all_reasons = [
+        FeedbackReason::TooLong,
+        FeedbackReason::TooShort,
+        FeedbackReason::TooGeneric,
+        FeedbackReason::OffTopic,
+    ];
+    let reasons = if !input.reasons.is_empty() || rating > 3 {
+        input.reasons
+    } else {
+        let labels: Vec<&str> = all_reasons.iter().map(|r| r.label()).collect();
+        MultiSelect::new()
+            .with_prompt("What was wrong? (space selects, enter continues)")
+            .items(&labels)
+            .interact()
+            .map_err(|e| format!("Input error: {e}"))?
+            .into_iter()
+            .map(|i| all_reasons[i])
+            .collect()
+    };
+
+    let comment = match input.comment {
+        Some(comment) => Some(comment),
+        None => Some(
+            Input::<String>::new()
+                .with_prompt("Anything to add? (optional)")
+                .allow_empty(true)
+                .interact_text()
+                .map_err(|e| format!("Input error: {e}"))?,
+        )
+        .filter(|c| !c.trim().is_empty()),
     };
 
-    Ok((accepted, satisfaction))
+    Ok(Feedback {
+        helpful,
+        rating,
+        reasons,
+        comment,
+        rated_at: Utc::now(),
+    })
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::context::testing::{context, fake_llm, note, FakeStore};
     use crate::types::SupabaseConfig;
     use serde_json::json;
 
@@ -454,4 +710,26 @@ mod tests {
         assert!(full_prompt.contains("Recent Notes:"));
         assert!(full_prompt.contains("=== Quick Summary ==="));
     }
+
+    #[test]
+    fn test_suggest_builds_personalized_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        let llm = fake_llm("llama3", "1. Write an ADR");
+        let prompts = llm.prompts.clone();
+        let store = FakeStore::default();
+        store
+            .notes
+            .borrow_mut()
+            .push(note("1", "Discussed migration plan"));
+        let ctx = context(llm, store, dir.path());
+        let user_context = UserContext::from_profile(ctx.config.clone());
+
+        let reply = suggest(&ctx, &user_context, "How do I plan the migration?", |_| {}).unwrap();
+        assert!(
+            matches!(reply, SuggestionReply::Suggestions { ref reply, .. } if reply.answer == "1. Write an ADR")
+        );
+        let prompt = &prompts.borrow()[0];
+        assert!(prompt.contains("Discussed migration plan"));
+        assert!(prompt.contains("📋 Request: \"How do I plan the migration?\""));
+    }
 }
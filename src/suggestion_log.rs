@@ -0,0 +1,235 @@
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Suggestions kept in the log; older ones are dropped when a new one is recorded.
+const MAX_RECORDS: usize = 500;
+
+/// A suggestion as it was shown, with the rating the user gave it, if any.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestionRecord {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub query: String,
+    pub intent: String,
+    pub context: String,
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    pub text: String,
+    /// Notes given to the model as context, most relevant first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub note_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<Feedback>,
+}
+
+/// How the user rated a suggestion.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Feedback {
+    pub helpful: bool,
+    /// 1 (very dissatisfied) to 5 (very satisfied).
+    pub rating: u8,
+    /// What was wrong with it, if anything.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<FeedbackReason>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    pub rated_at: DateTime<Utc>,
+}
+
+/// Specific complaints about a suggestion, used to adjust later prompts.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackReason {
+    TooLong,
+    TooShort,
+    TooGeneric,
+    OffTopic,
+}
+
+impl FeedbackReason {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::TooLong => "too long",
+            Self::TooShort => "too short",
+            Self::TooGeneric => "too generic",
+            Self::OffTopic => "off topic",
+        }
+    }
+}
+
+impl Feedback {
+    /// Rating as a satisfaction score from 0.2 to 1.0.
+    pub fn satisfaction(&self) -> f32 {
+        f32::from(self.rating.clamp(1, 5)) / 5.0
+    }
+
+    /// Whether the suggestion counts as rejected: marked unhelpful or rated 2 or lower.
+    pub fn is_rejection(&self) -> bool {
+        !self.helpful || self.rating <= 2
+    }
+}
+
+impl SuggestionRecord {
+    pub fn new(query: &str, intent: &str, context: &str, model: &str, text: &str) -> Self {
+        Self {
+            id: Uuid::new_v4().simple().to_string()[..8].to_string(),
+            created_at: Utc::now(),
+            query: query.to_string(),
+            intent: intent.to_string(),
+            context: context.to_string(),
+            model: model.to_string(),
+            project: None,
+            text: text.to_string(),
+            note_ids: Vec::new(),
+            feedback: None,
+        }
+    }
+}
+
+/// Local history of suggestions in `~/.logswise/suggestions.jsonl`, one record per line.
+pub struct SuggestionLog {
+    path: PathBuf,
+}
+
+impl SuggestionLog {
+    /// Opens the log in the default location (`~/.logswise/suggestions.jsonl`).
+    pub fn open_default() -> Result<Self, String> {
+        let mut path = home_dir().ok_or("Could not determine home directory")?;
+        path.push(".logswise/suggestions.jsonl");
+        Ok(Self::at(path))
+    }
+
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Appends a suggestion, dropping the oldest ones beyond the retention limit.
+    pub fn record(&self, record: &SuggestionRecord) -> Result<(), String> {
+        let existing = self.all()?;
+        if existing.len() >= MAX_RECORDS {
+            let mut kept = existing[existing.len() + 1 - MAX_RECORDS..].to_vec();
+            kept.push(record.clone());
+            return self.rewrite(&kept);
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+        }
+        let line = serde_json::to_string(record)
+            .map_err(|e| format!("Failed to encode suggestion: {e}"))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open suggestion history: {e}"))?;
+        writeln!(file, "{line}").map_err(|e| format!("Failed to write suggestion history: {e}"))
+    }
+
+    /// All recorded suggestions, oldest first.
+    pub fn all(&self) -> Result<Vec<SuggestionRecord>, String> {
+        let data = match fs::read_to_string(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(format!("Failed to read suggestion history: {e}")),
+        };
+        Ok(data
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .collect())
+    }
+
+    /// The suggestion whose id starts with `key`, or the latest one when `key` is `None`.
+    pub fn find(&self, key: Option<&str>) -> Result<SuggestionRecord, String> {
+        let records = self.all()?;
+        let Some(key) = key.map(str::trim) else {
+            return records.last().cloned().ok_or_else(|| {
+                "No suggestions recorded yet. Run 'logswise-cli suggestion \"...\"' first."
+                    .to_string()
+            });
+        };
+        let matches: Vec<&SuggestionRecord> =
+            records.iter().filter(|r| r.id.starts_with(key)).collect();
+        match matches.as_slice() {
+            _ if key.is_empty() => Err("Suggestion id cannot be empty".to_string()),
+            [record] => Ok((*record).clone()),
+            [] => Err(format!(
+                "No suggestion with id '{key}'. Run 'logswise-cli suggestion history' to see ids."
+            )),
+            _ => Err(format!(
+                "'{key}' matches several suggestions; use more characters of the id."
+            )),
+        }
+    }
+
+    /// Stores `feedback` on the suggestion with `id`, replacing any earlier rating.
+    pub fn rate(&self, id: &str, feedback: Feedback) -> Result<SuggestionRecord, String> {
+        let mut records = self.all()?;
+        let record = records
+            .iter_mut()
+            .find(|r| r.id == id)
+            .ok_or_else(|| format!("No suggestion with id '{id}'"))?;
+        record.feedback = Some(feedback);
+        let updated = record.clone();
+        self.rewrite(&records)?;
+        Ok(updated)
+    }
+
+    fn rewrite(&self, records: &[SuggestionRecord]) -> Result<(), String> {
+        let mut data = String::new();
+        for record in records {
+            let line = serde_json::to_string(record)
+                .map_err(|e| format!("Failed to encode suggestion: {e}"))?;
+            data.push_str(&line);
+            data.push('\n');
+        }
+        // Write to a sibling file and rename so a crash never leaves a half-written log
+        let tmp = self.path.with_extension("jsonl.tmp");
+        fs::write(&tmp, data).map_err(|e| format!("Failed to write suggestion history: {e}"))?;
+        fs::rename(&tmp, &self.path).map_err(|e| format!("Failed to write suggestion history: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_find_and_rate() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = SuggestionLog::at(dir.path().join("suggestions.jsonl"));
+        assert!(log.find(None).is_err());
+
+        let mut first = SuggestionRecord::new("Plan Q4", "planning", "work", "llama3", "1. ...");
+        first.id = "3f2a9c1d".to_string();
+        let mut second = SuggestionRecord::new("Fix CI", "problem_solving", "work", "llama3", "-");
+        second.id = "b7e40a12".to_string();
+        log.record(&first).unwrap();
+        log.record(&second).unwrap();
+
+        assert_eq!(log.find(None).unwrap().id, "b7e40a12");
+        assert_eq!(log.find(Some("3f")).unwrap().query, "Plan Q4");
+        assert!(log.find(Some("ff")).is_err());
+
+        let feedback = Feedback {
+            helpful: true,
+            rating: 4,
+            reasons: vec![],
+            comment: Some("Concrete steps".to_string()),
+            rated_at: Utc::now(),
+        };
+        log.rate("3f2a9c1d", feedback.clone()).unwrap();
+        let records = log.all().unwrap();
+        assert_eq!(records[0].feedback.as_ref(), Some(&feedback));
+        assert!(records[1].feedback.is_none());
+        assert!((feedback.satisfaction() - 0.8).abs() < f32::EPSILON);
+    }
+}
@@ -0,0 +1,133 @@
+use clap::ValueEnum;
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::tokens;
+
+const OPEN: &str = "<think>";
+const CLOSE: &str = "</think>";
+
+/// What to do with the reasoning that thinking models emit before their answer.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ThinkingMode {
+    /// Print only the answer
+    #[default]
+    Hide,
+    /// Print the reasoning, dimmed, before the answer
+    Show,
+    /// Print a one-line note of how long the model thought
+    Collapse,
+}
+
+/// A model reply split into its answer and the reasoning around it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Reply {
+    pub answer: String,
+    /// Contents of each think block, in order.
+    pub thinking: Vec<String>,
+}
+
+impl Reply {
+    /// Splits `text` into answer and `<think>` blocks. Handles several blocks, a block
+    /// left open because generation stopped mid-thought, and a closing tag without an
+    /// opening one (some chat templates open the block in the prompt).
+    pub fn parse(text: &str) -> Self {
+        let mut reply = Self::default();
+        let mut answer = String::new();
+        let mut rest = text;
+        if let Some(close) = rest.find(CLOSE) {
+            if rest.find(OPEN).is_none_or(|open| open > close) {
+                reply.push_thought(&rest[..close]);
+                rest = &rest[close + CLOSE.len()..];
+            }
+        }
+        while let Some(start) = rest.find(OPEN) {
+            answer.push_str(&rest[..start]);
+            let inside = &rest[start + OPEN.len()..];
+            match inside.find(CLOSE) {
+                Some(end) => {
+                    reply.push_thought(&inside[..end]);
+                    rest = &inside[end + CLOSE.len()..];
+                }
+                None => {
+                    reply.push_thought(inside);
+                    rest = "";
+                }
+            }
+        }
+        answer.push_str(rest);
+        reply.answer = answer.trim().to_string();
+        reply
+    }
+
+    fn push_thought(&mut self, thought: &str) {
+        let thought = thought.trim();
+        if !thought.is_empty() {
+            self.thinking.push(thought.to_string());
+        }
+    }
+
+    /// All reasoning as one text, blocks separated by blank lines.
+    pub fn thinking_text(&self) -> String {
+        self.thinking.join("\n\n")
+    }
+
+    /// Prints the reasoning as `mode` asks; callers print the answer themselves.
+    pub fn print_thinking(&self, mode: ThinkingMode) {
+        if self.thinking.is_empty() {
+            return;
+        }
+        match mode {
+            ThinkingMode::Hide => {}
+            ThinkingMode::Show => {
+                println!("{}", "💭 Thinking".bright_black().bold());
+                println!("{}\n", self.thinking_text().bright_black());
+            }
+            ThinkingMode::Collapse => println!(
+                "{}",
+                format!(
+                    "💭 Thought for ~{} tokens (--thinking show to see it)",
+                    tokens::estimate(&self.thinking_text())
+                )
+                .bright_black()
+            ),
+        }
+    }
+}
+
+/// The answer in `text` without any reasoning.
+pub fn strip(text: &str) -> String {
+    Reply::parse(text).answer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_splits_blocks() {
+        let reply =
+            Reply::parse("<think>first</think>\nPart one. <think> second </think>Part two.");
+        assert_eq!(reply.answer, "Part one. Part two.");
+        assert_eq!(reply.thinking, vec!["first", "second"]);
+
+        let plain = Reply::parse("  Just an answer\n");
+        assert_eq!(plain.answer, "Just an answer");
+        assert!(plain.thinking.is_empty());
+    }
+
+    #[test]
+    fn test_parse_handles_unbalanced_tags() {
+        let unterminated = Reply::parse("Answer so far <think>still going");
+        assert_eq!(unterminated.answer, "Answer so far");
+        assert_eq!(unterminated.thinking, vec!["still going"]);
+
+        let unopened = Reply::parse("reasoning from the template</think>\n\nThe answer");
+        assert_eq!(unopened.answer, "The answer");
+        assert_eq!(unopened.thinking, vec!["reasoning from the template"]);
+
+        assert_eq!(strip("<think></think>Done"), "Done");
+        assert_eq!(strip("<think>cut off"), "");
+    }
+}
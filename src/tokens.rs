@@ -0,0 +1,42 @@
+/// Context window Ollama gives a model unless `num_ctx` is raised, whatever the model's
+/// own maximum is.
+pub const DEFAULT_CONTEXT_TOKENS: usize = 4096;
+
+/// Rough token count for budgeting: about four characters per token for English text.
+pub fn estimate(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Splits `items` into consecutive chunks whose estimated size stays within `budget`
+/// tokens. An item larger than the budget gets a chunk of its own.
+pub fn chunk_by_budget<T>(items: Vec<T>, budget: usize, size: impl Fn(&T) -> usize) -> Vec<Vec<T>> {
+    let mut chunks = vec![];
+    let mut current = vec![];
+    let mut used = 0;
+    for item in items {
+        let cost = size(&item);
+        if !current.is_empty() && used + cost > budget {
+            chunks.push(std::mem::take(&mut current));
+            used = 0;
+        }
+        used += cost;
+        current.push(item);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_by_budget() {
+        assert_eq!(estimate("12345678"), 2);
+        let chunks = chunk_by_budget(vec![3, 3, 3, 10, 1], 6, |n| *n);
+        assert_eq!(chunks, vec![vec![3, 3], vec![3], vec![10], vec![1]]);
+        assert!(chunk_by_budget(Vec::<usize>::new(), 6, |n| *n).is_empty());
+    }
+}
@@ -0,0 +1,575 @@
+use crate::chat_handler::{self, ChatReply, ChatTurn};
+use crate::context::AppContext;
+use crate::note_handler::{new_note, store_note, SaveOutcome};
+use crate::services::supabase::{NoteChanges, NoteQuery};
+use crate::types::Note;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::ListState;
+use std::time::{Duration, Instant};
+
+/// Notes loaded for browsing and keyword search.
+const NOTES_TO_LOAD: usize = 500;
+
+/// Notes returned by a semantic search.
+const SEMANTIC_RESULTS: usize = 20;
+
+/// Pause in typing before a semantic search runs, so not every keystroke is embedded.
+const SEMANTIC_DEBOUNCE: Duration = Duration::from_millis(400);
+
+/// How often the screen is redrawn while idle.
+const IDLE_TICK: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchMode {
+    /// Notes containing every typed word, filtered as you type.
+    Keyword,
+    /// Notes closest in meaning to the query, from Supabase.
+    Semantic,
+}
+
+/// What the keyboard currently drives.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Browse,
+    Search,
+    Edit,
+    Tag,
+    New,
+    ConfirmDelete,
+    Chat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Speaker {
+    You,
+    Assistant,
+}
+
+/// Work that talks to Supabase or Ollama, run after the screen shows a busy status.
+#[derive(Debug, PartialEq)]
+enum Pending {
+    Reload,
+    Search,
+    Save { id: String, content: String },
+    Tag { id: String, project: Option<String> },
+    Delete { id: String },
+    Create { content: String },
+    Chat { message: String },
+}
+
+/// State of the full-screen note browser.
+pub struct App {
+    /// Notes loaded from the store, newest first.
+    notes: Vec<Note>,
+    /// Notes shown in the list: all of them, or the search results.
+    pub results: Vec<Note>,
+    pub list: ListState,
+    pub mode: Mode,
+    pub search_mode: SearchMode,
+    pub query: String,
+    /// Text being typed for an edit, tag, new note or chat message.
+    pub input: String,
+    pub chat: Vec<(Speaker, String)>,
+    /// Lines the chat pane is scrolled up from the bottom.
+    pub chat_scroll: u16,
+    pub status: String,
+    /// Project the notes are scoped to, as for `recent`.
+    pub project: Option<String>,
+    pending: Option<Pending>,
+    search_due: Option<Instant>,
+    pub should_quit: bool,
+}
+
+impl App {
+    pub fn new(project: Option<&str>) -> Self {
+        Self {
+            notes: vec![],
+            results: vec![],
+            list: ListState::default(),
+            mode: Mode::Browse,
+            search_mode: SearchMode::Keyword,
+            query: String::new(),
+            input: String::new(),
+            chat: vec![],
+            chat_scroll: 0,
+            status: "Loading notes...".to_string(),
+            project: project.map(str::to_string),
+            pending: Some(Pending::Reload),
+            search_due: None,
+            should_quit: false,
+        }
+    }
+
+    pub fn selected(&self) -> Option<&Note> {
+        self.results.get(self.list.selected()?)
+    }
+
+    pub fn has_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// How long to wait for a key before the next redraw or due search.
+    pub fn poll_timeout(&self) -> Duration {
+        match self.search_due {
+            Some(due) => due.saturating_duration_since(Instant::now()).min(IDLE_TICK),
+            None => IDLE_TICK,
+        }
+    }
+
+    /// Starts a semantic search once typing has paused.
+    pub fn tick(&mut self) {
+        if self.search_due.is_some_and(|due| Instant::now() >= due) {
+            self.search_due = None;
+            self.status = "Searching by meaning...".to_string();
+            self.pending = Some(Pending::Search);
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.should_quit = true;
+            return;
+        }
+        match self.mode {
+            Mode::Browse => self.browse_key(key),
+            Mode::Search => self.search_key(key),
+            Mode::ConfirmDelete => self.confirm_delete_key(key),
+            Mode::Edit | Mode::Tag | Mode::New | Mode::Chat => self.input_key(key),
+        }
+    }
+
+    fn browse_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Esc if !self.query.is_empty() => {
+                self.query.clear();
+                self.refresh_results();
+            }
+            KeyCode::Esc => self.should_quit = true,
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::Home | KeyCode::Char('g') => self.select(0),
+            KeyCode::End | KeyCode::Char('G') => self.select(self.results.len().saturating_sub(1)),
+            KeyCode::Char('/') => self.mode = Mode::Search,
+            KeyCode::Tab => self.toggle_search_mode(),
+            KeyCode::Char('r') => {
+                self.status = "Reloading notes...".to_string();
+                self.pending = Some(Pending::Reload);
+            }
+            KeyCode::Char('a') | KeyCode::Char('n') => self.start_input(Mode::New, String::new()),
+            KeyCode::Char('c') => self.start_input(Mode::Chat, String::new()),
+            KeyCode::Enter | KeyCode::Char('e') => {
+                if let Some(note) = self.selected() {
+                    let content = note.content.clone();
+                    self.start_input(Mode::Edit, content);
+                }
+            }
+            KeyCode::Char('t') => {
+                if let Some(note) = self.selected() {
+                    let project = note.project.clone().unwrap_or_default();
+                    self.start_input(Mode::Tag, project);
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Delete if self.selected().is_some() => {
+                self.mode = Mode::ConfirmDelete;
+                self.status = "Delete this note? (y/n)".to_string();
+            }
+            _ => {}
+        }
+    }
+
+    fn search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.mode = Mode::Browse,
+            KeyCode::Esc => {
+                self.query.clear();
+                self.mode = Mode::Browse;
+                self.refresh_results();
+            }
+            KeyCode::Tab => self.toggle_search_mode(),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.refresh_results();
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.refresh_results();
+            }
+            _ => {}
+        }
+    }
+
+    fn confirm_delete_key(&mut self, key: KeyEvent) {
+        self.mode = Mode::Browse;
+        match (key.code, self.selected()) {
+            (KeyCode::Char('y') | KeyCode::Char('Y'), Some(note)) => {
+                let id = note.id.clone();
+                self.status = "Deleting note...".to_string();
+                self.pending = Some(Pending::Delete { id });
+            }
+            _ => self.status = "Delete cancelled".to_string(),
+        }
+    }
+
+    fn input_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.mode = Mode::Browse;
+                self.input.clear();
+                self.status.clear();
+            }
+            KeyCode::Enter if key.modifiers.contains(KeyModifiers::ALT) => {
+                if matches!(self.mode, Mode::Edit | Mode::New) {
+                    self.input.push('\n');
+                }
+            }
+            KeyCode::Enter => self.submit_input(),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::PageUp if self.mode == Mode::Chat => {
+                self.chat_scroll = self.chat_scroll.saturating_add(5)
+            }
+            KeyCode::PageDown if self.mode == Mode::Chat => {
+                self.chat_scroll = self.chat_scroll.saturating_sub(5)
+            }
+            KeyCode::Char(c) => self.input.push(c),
+            _ => {}
+        }
+    }
+
+    fn start_input(&mut self, mode: Mode, initial: String) {
+        self.mode = mode;
+        self.input = initial;
+        self.status.clear();
+    }
+
+    fn submit_input(&mut self) {
+        let text = std::mem::take(&mut self.input).trim().to_string();
+        let mode = self.mode;
+        // Chat stays open for the next message; everything else goes back to the list
+        if mode != Mode::Chat {
+            self.mode = Mode::Browse;
+        }
+        let id = self.selected().map(|n| n.id.clone());
+        self.pending = match (mode, id) {
+            (Mode::Chat, _) if !text.is_empty() => {
+                self.chat.push((Speaker::You, text.clone()));
+                self.chat_scroll = 0;
+                self.status = "Thinking...".to_string();
+                Some(Pending::Chat { message: text })
+            }
+            (Mode::New, _) if !text.is_empty() => {
+                self.status = "Saving note...".to_string();
+                Some(Pending::Create { content: text })
+            }
+            (Mode::Edit, Some(id)) if !text.is_empty() => {
+                self.status = "Saving note...".to_string();
+                Some(Pending::Save { id, content: text })
+            }
+            (Mode::Tag, Some(id)) => {
+                self.status = "Updating project...".to_string();
+                Some(Pending::Tag {
+                    id,
+                    project: Some(text).filter(|t| !t.is_empty()),
+                })
+            }
+            _ => None,
+        };
+    }
+
+    fn toggle_search_mode(&mut self) {
+        self.search_mode = match self.search_mode {
+            SearchMode::Keyword => SearchMode::Semantic,
+            SearchMode::Semantic => SearchMode::Keyword,
+        };
+        self.refresh_results();
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let current = self.list.selected().unwrap_or(0) as isize;
+        self.select(current.saturating_add(delta).max(0) as usize);
+    }
+
+    fn select(&mut self, index: usize) {
+        if self.results.is_empty() {
+            self.list.select(None);
+        } else {
+            self.list.select(Some(index.min(self.results.len() - 1)));
+        }
+    }
+
+    /// Shows the notes matching the query: right away for keyword search, after a typing
+    /// pause for semantic search.
+    fn refresh_results(&mut self) {
+        let query = self.query.trim().to_lowercase();
+        if query.is_empty() {
+            self.search_due = None;
+            self.results = self.notes.clone();
+        } else if self.search_mode == SearchMode::Semantic {
+            self.search_due = Some(Instant::now() + SEMANTIC_DEBOUNCE);
+            return;
+        } else {
+            self.search_due = None;
+            let words: Vec<&str> = query.split_whitespace().collect();
+            self.results = self
+                .notes
+                .iter()
+                .filter(|n| {
+                    let text = format!(
+                        "{} {}",
+                        n.content.to_lowercase(),
+                        n.project.as_deref().unwrap_or_default().to_lowercase()
+                    );
+                    words.iter().all(|w| text.contains(w))
+                })
+                .cloned()
+                .collect();
+        }
+        self.select(0);
+    }
+
+    /// Runs the queued store or LLM work and reports the outcome in the status line.
+    pub fn run_pending(&mut self, ctx: &AppContext) {
+        let Some(pending) = self.pending.take() else {
+            return;
+        };
+        let result = match pending {
+            Pending::Reload => self.reload(ctx),
+            Pending::Search => self.semantic_search(ctx),
+            Pending::Save { id, content } => self.save(ctx, &id, content),
+            Pending::Tag { id, project } => self.tag(ctx, &id, project),
+            Pending::Delete { id } => self.delete(ctx, &id),
+            Pending::Create { content } => self.create(ctx, content),
+            Pending::Chat { message } => self.send_chat(ctx, &message),
+        };
+        self.status = match result {
+            Ok(status) => status,
+            Err(e) => format!("Error: {e}"),
+        };
+    }
+
+    fn reload(&mut self, ctx: &AppContext) -> Result<String, String> {
+        self.notes = ctx.store.find_notes(&NoteQuery {
+            project: self.project.as_deref(),
+            limit: NOTES_TO_LOAD,
+            ..NoteQuery::default()
+        })?;
+        self.refresh_results();
+        if self.search_mode == SearchMode::Semantic && !self.query.trim().is_empty() {
+            self.pending = Some(Pending::Search);
+        }
+        Ok(format!("Loaded {} note(s)", self.notes.len()))
+    }
+
+    fn semantic_search(&mut self, ctx: &AppContext) -> Result<String, String> {
+        let embedding = ctx.llm.embed(self.query.trim())?;
+        self.results =
+            ctx.store
+                .semantic_search(&embedding, SEMANTIC_RESULTS, self.project.as_deref())?;
+        self.select(0);
+        Ok(format!("{} note(s) by meaning", self.results.len()))
+    }
+
+    fn save(&mut self, ctx: &AppContext, id: &str, content: String) -> Result<String, String> {
+        // Re-embed so semantic search matches the new text
+        let embedding = ctx.llm.embed(&content).ok();
+        let changes = NoteChanges {
+            content: Some((content.clone(), embedding)),
+            ..NoteChanges::default()
+        };
+        ctx.store.update_note(id, &changes)?;
+        self.update_local(id, |note| note.content = content.clone());
+        Ok("Note saved".to_string())
+    }
+
+    fn tag(
+        &mut self,
+        ctx: &AppContext,
+        id: &str,
+        project: Option<String>,
+    ) -> Result<String, String> {
+        let changes = NoteChanges {
+            project: Some(project.clone()),
+            ..NoteChanges::default()
+        };
+        ctx.store.update_note(id, &changes)?;
+        self.update_local(id, |note| note.project = project.clone());
+        Ok(match project {
+            Some(project) => format!("Tagged with {project}"),
+            None => "Project tag removed".to_string(),
+        })
+    }
+
+    fn delete(&mut self, ctx: &AppContext, id: &str) -> Result<String, String> {
+        ctx.store.delete_note(id)?;
+        self.notes.retain(|n| n.id != id);
+        self.results.retain(|n| n.id != id);
+        self.select(self.list.selected().unwrap_or(0));
+        Ok("Note deleted".to_string())
+    }
+
+    fn create(&mut self, ctx: &AppContext, content: String) -> Result<String, String> {
+        let mut note = new_note(&content, ctx.llm.embed(&content).ok());
+        note.project = self.project.clone();
+        let status = match store_note(ctx, &note)? {
+            SaveOutcome::Saved => "Note added".to_string(),
+            SaveOutcome::SavedWithoutProject => {
+                "Note added without its project tag (the notes table has no project column)"
+                    .to_string()
+            }
+            SaveOutcome::Queued { pending, .. } => {
+                format!("Supabase unreachable; note queued in the outbox ({pending} pending)")
+            }
+        };
+        self.notes.insert(0, note);
+        self.refresh_results();
+        Ok(status)
+    }
+
+    fn send_chat(&mut self, ctx: &AppContext, message: &str) -> Result<String, String> {
+        let reply = match chat_handler::chat(ctx, message, &self.chat_turns(), |_| {}) {
+            Ok(ChatReply::Answer { reply, .. }) => reply.answer,
+            Ok(ChatReply::Notes(notes)) if notes.is_empty() => {
+                "No related notes found.".to_string()
+            }
+            Ok(ChatReply::Notes(notes)) => {
+                let lines: Vec<String> = notes.iter().map(|n| format!("- {}", n.content)).collect();
+                format!("Related notes:\n{}", lines.join("\n"))
+            }
+            Err(e) => {
+                self.chat.push((Speaker::Assistant, e.clone()));
+                return Err(e);
+            }
+        };
+        self.chat.push((Speaker::Assistant, reply));
+        Ok(String::new())
+    }
+
+    /// Completed exchanges in the chat pane, oldest first.
+    fn chat_turns(&self) -> Vec<ChatTurn> {
+        self.chat
+            .windows(2)
+            .filter_map(|pair| match pair {
+                [(Speaker::You, user), (Speaker::Assistant, assistant)] => Some(ChatTurn {
+                    user: user.clone(),
+                    assistant: assistant.clone(),
+                    thinking: vec![],
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn update_local(&mut self, id: &str, change: impl Fn(&mut Note)) {
+        for note in self.notes.iter_mut().chain(self.results.iter_mut()) {
+            if note.id == id {
+                change(note);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::{context, fake_llm, note, FakeStore};
+
+    fn press(app: &mut App, codes: &[KeyCode]) {
+        for code in codes {
+            app.handle_key(KeyEvent::new(*code, KeyModifiers::NONE));
+        }
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            press(app, &[KeyCode::Char(c)]);
+        }
+    }
+
+    #[test]
+    fn test_keyword_search_filters_as_you_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FakeStore::default();
+        store.notes.borrow_mut().extend([
+            note("1", "Fixed the login bug"),
+            note("2", "Planning the Q3 roadmap"),
+            note("3", "Login page redesign"),
+        ]);
+        let ctx = context(fake_llm("llama3", ""), store, dir.path());
+        let mut app = App::new(None);
+        app.run_pending(&ctx);
+        assert_eq!(app.results.len(), 3);
+        assert_eq!(app.status, "Loaded 3 note(s)");
+
+        press(&mut app, &[KeyCode::Char('/')]);
+        type_text(&mut app, "LOGIN");
+        assert_eq!(app.results.len(), 2);
+        type_text(&mut app, " page");
+        assert_eq!(app.results.len(), 1);
+        assert_eq!(app.selected().unwrap().id, "3");
+
+        // Semantic mode waits for a pause in typing instead of filtering locally
+        press(&mut app, &[KeyCode::Tab]);
+        assert_eq!(app.search_mode, SearchMode::Semantic);
+        assert!(!app.has_pending());
+        press(&mut app, &[KeyCode::Esc]);
+        assert_eq!(app.results.len(), 3);
+        assert_eq!(app.mode, Mode::Browse);
+    }
+
+    #[test]
+    fn test_edit_tag_delete_and_chat_use_the_services() {
+        let dir = tempfile::tempdir().unwrap();
+        let llm = fake_llm("llama3", "Try smaller PRs.");
+        let store = FakeStore::default();
+        let notes = store.notes.clone();
+        notes
+            .borrow_mut()
+            .extend([note("1", "Old text"), note("2", "Second note")]);
+        let ctx = context(llm, store, dir.path());
+        let mut app = App::new(None);
+        app.run_pending(&ctx);
+
+        // Notes are newest first, so the first row is note 2
+        press(&mut app, &[KeyCode::Char('e')]);
+        assert_eq!(app.input, "Second note");
+        press(&mut app, &[KeyCode::Backspace; 4]);
+        type_text(&mut app, "text");
+        press(&mut app, &[KeyCode::Enter]);
+        app.run_pending(&ctx);
+        assert_eq!(app.status, "Note saved");
+        assert_eq!(notes.borrow()[1].content, "Second text");
+        assert!(notes.borrow()[1].embedding.is_some());
+
+        press(&mut app, &[KeyCode::Char('t')]);
+        type_text(&mut app, "Atlas");
+        press(&mut app, &[KeyCode::Enter]);
+        app.run_pending(&ctx);
+        assert_eq!(notes.borrow()[1].project.as_deref(), Some("Atlas"));
+
+        press(
+            &mut app,
+            &[KeyCode::Char('j'), KeyCode::Char('d'), KeyCode::Char('y')],
+        );
+        app.run_pending(&ctx);
+        assert_eq!(app.status, "Note deleted");
+        assert_eq!(notes.borrow().len(), 1);
+        assert_eq!(app.selected().unwrap().id, "2");
+
+        press(&mut app, &[KeyCode::Char('c')]);
+        type_text(&mut app, "How do I review faster?");
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!(app.status, "Thinking...");
+        app.run_pending(&ctx);
+        assert_eq!(app.mode, Mode::Chat);
+        assert_eq!(
+            app.chat.last(),
+            Some(&(Speaker::Assistant, "Try smaller PRs.".to_string()))
+        );
+    }
+}
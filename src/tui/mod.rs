@@ -0,0 +1,44 @@
+//! Full-screen note browser: a searchable note list, a preview pane, inline
+//! edit/tag/delete and a chat pane, on the same services as the CLI commands.
+
+mod app;
+mod ui;
+
+use crate::context::AppContext;
+use app::App;
+use chrono::Utc;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use ratatui::DefaultTerminal;
+
+/// Runs the browser until the user quits. The terminal is restored on exit and on panic.
+pub fn run(ctx: &AppContext) -> Result<(), String> {
+    let mut terminal =
+        ratatui::try_init().map_err(|e| format!("Could not start the terminal UI: {e}"))?;
+    let result = event_loop(&mut terminal, ctx);
+    ratatui::restore();
+    result.map_err(|e| format!("Terminal UI error: {e}"))
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, ctx: &AppContext) -> std::io::Result<()> {
+    let mut app = App::new(ctx.active_project());
+    let zone = ctx.zone();
+    loop {
+        terminal.draw(|frame| ui::draw(frame, &mut app, zone, Utc::now()))?;
+        // Slow work runs after a frame showing its status, so the UI never looks frozen
+        if app.has_pending() {
+            app.run_pending(ctx);
+            continue;
+        }
+        if app.should_quit {
+            return Ok(());
+        }
+        if event::poll(app.poll_timeout())? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    app.handle_key(key);
+                }
+            }
+        }
+        app.tick();
+    }
+}
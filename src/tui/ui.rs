@@ -0,0 +1,272 @@
+use super::app::{App, Mode, SearchMode, Speaker};
+use crate::dates::{self, Zone};
+use chrono::{DateTime, Utc};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, List, ListItem, Paragraph, Wrap};
+use ratatui::Frame;
+
+/// Length the note list cuts each note's first line to.
+const TITLE_WIDTH: usize = 60;
+
+pub fn draw(frame: &mut Frame, app: &mut App, zone: Zone, now: DateTime<Utc>) {
+    let input_height = match app.mode {
+        Mode::Edit | Mode::New => 6,
+        Mode::Tag | Mode::Chat => 3,
+        _ => 0,
+    };
+    let [search_area, main_area, input_area, status_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(5),
+        Constraint::Length(input_height),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [list_area, side_area] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .areas(main_area);
+
+    draw_search(frame, app, search_area);
+    draw_list(frame, app, list_area, now);
+    if app.mode == Mode::Chat || (!app.chat.is_empty() && app.selected().is_none()) {
+        draw_chat(frame, app, side_area);
+    } else {
+        draw_preview(frame, app, side_area, zone, now);
+    }
+    if input_height > 0 {
+        draw_input(frame, app, input_area);
+    }
+    draw_status(frame, app, status_area);
+}
+
+fn draw_search(frame: &mut Frame, app: &App, area: Rect) {
+    let mode = match app.search_mode {
+        SearchMode::Keyword => "keyword",
+        SearchMode::Semantic => "semantic",
+    };
+    let active = app.mode == Mode::Search;
+    let cursor = if active { "▏" } else { "" };
+    let text = if app.query.is_empty() && !active {
+        Line::from("Press / to search, Tab to switch keyword/semantic".dark_gray())
+    } else {
+        Line::from(format!("{}{cursor}", app.query))
+    };
+    let block = Block::bordered()
+        .title(format!(" Search ({mode}) "))
+        .border_style(focus_style(active));
+    frame.render_widget(Paragraph::new(text).block(block), area);
+}
+
+fn draw_list(frame: &mut Frame, app: &mut App, area: Rect, now: DateTime<Utc>) {
+    let items: Vec<ListItem> = app
+        .results
+        .iter()
+        .map(|note| {
+            let when = dates::parse_timestamp(&note.created_at)
+                .map(|t| dates::relative(t, now))
+                .unwrap_or_default();
+            let mut spans = vec![Span::raw(first_line(&note.content))];
+            spans.push(Span::styled(format!("  {when}"), Style::new().dark_gray()));
+            if let Some(project) = &note.project {
+                spans.push(Span::styled(format!(" [{project}]"), Style::new().cyan()));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let scope = app
+        .project
+        .as_deref()
+        .map(|p| format!(" · {p}"))
+        .unwrap_or_default();
+    let list = List::new(items)
+        .block(
+            Block::bordered()
+                .title(format!(" Notes ({}){scope} ", app.results.len()))
+                .border_style(focus_style(matches!(
+                    app.mode,
+                    Mode::Browse | Mode::ConfirmDelete
+                ))),
+        )
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(list, area, &mut app.list);
+}
+
+fn draw_preview(frame: &mut Frame, app: &App, area: Rect, zone: Zone, now: DateTime<Utc>) {
+    let block = Block::bordered().title(" Preview ");
+    let Some(note) = app.selected() else {
+        let empty =
+            Paragraph::new("No note selected. Press a to add one.".dark_gray()).block(block);
+        frame.render_widget(empty, area);
+        return;
+    };
+    let mut text = Text::default();
+    text.push_line(Line::from(vec![
+        Span::styled(
+            zone.describe(&note.created_at, now),
+            Style::new().dark_gray(),
+        ),
+        Span::styled(
+            format!("  id {}", note.id.split('-').next().unwrap_or(&note.id)),
+            Style::new().dark_gray(),
+        ),
+    ]));
+    if let Some(project) = &note.project {
+        text.push_line(Line::from(Span::styled(
+            format!("Project: {project}"),
+            Style::new().cyan(),
+        )));
+    }
+    text.push_line(Line::default());
+    for line in note.content.lines() {
+        text.push_line(Line::from(line.to_string()));
+    }
+    frame.render_widget(
+        Paragraph::new(text).block(block).wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+fn draw_chat(frame: &mut Frame, app: &App, area: Rect) {
+    let mut text = Text::default();
+    if app.chat.is_empty() {
+        text.push_line(Line::from(
+            "Ask about your notes. Answers use your profile and the most relevant notes."
+                .dark_gray(),
+        ));
+    }
+    for (speaker, message) in &app.chat {
+        let (label, style) = match speaker {
+            Speaker::You => ("You", Style::new().green().bold()),
+            Speaker::Assistant => ("Assistant", Style::new().cyan().bold()),
+        };
+        text.push_line(Line::from(Span::styled(label, style)));
+        for line in message.lines() {
+            text.push_line(Line::from(line.to_string()));
+        }
+        text.push_line(Line::default());
+    }
+
+    // Keep the newest message in view unless scrolled up
+    let inner_height = area.height.saturating_sub(2);
+    let inner_width = area.width.saturating_sub(2).max(1) as usize;
+    let lines: u16 = text
+        .lines
+        .iter()
+        .map(|l| (l.width().max(1).div_ceil(inner_width)) as u16)
+        .sum();
+    let bottom = lines.saturating_sub(inner_height);
+    let scroll = bottom.saturating_sub(app.chat_scroll);
+
+    let block = Block::bordered()
+        .title(" Chat ")
+        .border_style(focus_style(app.mode == Mode::Chat));
+    frame.render_widget(
+        Paragraph::new(text)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0)),
+        area,
+    );
+}
+
+fn draw_input(frame: &mut Frame, app: &App, area: Rect) {
+    let title = match app.mode {
+        Mode::Edit => " Edit note (Enter save · Alt+Enter new line · Esc cancel) ",
+        Mode::New => " New note (Enter save · Alt+Enter new line · Esc cancel) ",
+        Mode::Tag => " Project (Enter save · empty removes the tag · Esc cancel) ",
+        _ => " Message (Enter send · PgUp/PgDn scroll · Esc close) ",
+    };
+    let block = Block::bordered()
+        .title(title)
+        .border_style(focus_style(true));
+    let text = format!("{}▏", app.input);
+    frame.render_widget(
+        Paragraph::new(text).block(block).wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
+    let hints = match app.mode {
+        Mode::Browse => {
+            "↑↓ move  / search  Tab mode  e edit  t tag  d delete  a add  c chat  r reload  q quit"
+        }
+        Mode::Search => "type to search  ↑↓ move  Tab mode  Enter done  Esc clear",
+        Mode::ConfirmDelete => "y delete  any other key cancels",
+        _ => "",
+    };
+    let line = if app.status.is_empty() {
+        Line::from(hints.dark_gray())
+    } else {
+        let style = if app.status.starts_with("Error") {
+            Style::new().fg(Color::Red)
+        } else {
+            Style::new().fg(Color::Yellow)
+        };
+        Line::from(vec![
+            Span::styled(app.status.clone(), style),
+            Span::styled(format!("  {hints}"), Style::new().dark_gray()),
+        ])
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn focus_style(focused: bool) -> Style {
+    if focused {
+        Style::new().cyan()
+    } else {
+        Style::new().dark_gray()
+    }
+}
+
+/// First line of a note, shortened for the list.
+fn first_line(content: &str) -> String {
+    let line = content.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > TITLE_WIDTH {
+        let cut: String = line.chars().take(TITLE_WIDTH - 1).collect();
+        format!("{cut}…")
+    } else {
+        line.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::{context, fake_llm, note, FakeStore};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    #[test]
+    fn test_draws_list_preview_and_hints() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FakeStore::default();
+        let mut tagged = note("1", "Shipped the billing export\nwith CSV support");
+        tagged.project = Some("Atlas".to_string());
+        store.notes.borrow_mut().push(tagged);
+        let ctx = context(fake_llm("llama3", ""), store, dir.path());
+        let mut app = App::new(None);
+        app.run_pending(&ctx);
+
+        let mut terminal = Terminal::new(TestBackend::new(160, 20)).unwrap();
+        let now = dates::parse_timestamp("2026-10-01T11:00:00Z").unwrap();
+        terminal
+            .draw(|frame| draw(frame, &mut app, Zone::parse("UTC").unwrap(), now))
+            .unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("Notes (1)"));
+        assert!(screen.contains("> Shipped the billing export  2h ago [Atlas]"));
+        assert!(screen.contains("with CSV support"));
+        assert!(screen.contains("Oct 1, 09:00"));
+        assert!(screen.contains("Loaded 1 note(s)"));
+    }
+}
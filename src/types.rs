@@ -10,7 +10,11 @@ pub struct Note {
     /// Timestamp when the note was created.
     pub created_at: String,
     /// Embedding vector for semantic search (optional, not always present)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub embedding: Option<Vec<f32>>,
+    /// Project the note was captured for (see `project use`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
 }
 
 /// Configuration for connecting to Supabase.
@@ -35,6 +39,7 @@ mod tests {
             content: "Test note".to_string(),
             created_at: "2025-06-05T12:00:00Z".to_string(),
             embedding: None,
+            project: None,
         };
         assert_eq!(note.content, "Test note");
     }
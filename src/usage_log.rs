@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Commands kept in the log; the oldest are dropped once it grows past this.
+const MAX_EVENTS: usize = 5000;
+
+/// One command run, without its arguments.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UsageEvent {
+    pub command: String,
+    pub at: DateTime<Utc>,
+}
+
+/// Local log of when commands were run in `~/.logswise/usage.jsonl`, one event per line.
+///
+/// Only the command name and time are kept; it never leaves the machine and feeds the
+/// activity patterns learned by `personalize learn`.
+pub struct UsageLog {
+    path: PathBuf,
+}
+
+impl UsageLog {
+    /// Opens the log in the default location (`~/.logswise/usage.jsonl`).
+    pub fn open_default() -> Result<Self, String> {
+        let mut path = home_dir().ok_or("Could not determine home directory")?;
+        path.push(".logswise/usage.jsonl");
+        Ok(Self::at(path))
+    }
+
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Records that `command` ran now. Short aliases are stored under their full name.
+    pub fn record(&self, command: &str) -> Result<(), String> {
+        self.record_event(&UsageEvent {
+            command: canonical_command(command).to_string(),
+            at: Utc::now(),
+        })
+    }
+
+    fn record_event(&self, event: &UsageEvent) -> Result<(), String> {
+        let existing = self.all()?;
+        if existing.len() >= MAX_EVENTS {
+            let mut kept = existing[existing.len() + 1 - MAX_EVENTS..].to_vec();
+            kept.push(event.clone());
+            return self.rewrite(&kept);
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+        }
+        let line = serde_json::to_string(event)
+            .map_err(|e| format!("Failed to encode usage event: {e}"))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open usage log: {e}"))?;
+        writeln!(file, "{line}").map_err(|e| format!("Failed to write usage log: {e}"))
+    }
+
+    /// All recorded events, oldest first.
+    pub fn all(&self) -> Result<Vec<UsageEvent>, String> {
+        let data = match fs::read_to_string(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(format!("Failed to read usage log: {e}")),
+        };
+        Ok(data
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .collect())
+    }
+
+    /// Events recorded at or after `since`, oldest first.
+    pub fn since(&self, since: DateTime<Utc>) -> Result<Vec<UsageEvent>, String> {
+        let mut events = self.all()?;
+        events.retain(|e| e.at >= since);
+        Ok(events)
+    }
+
+    fn rewrite(&self, events: &[UsageEvent]) -> Result<(), String> {
+        let mut data = String::new();
+        for event in events {
+            let line = serde_json::to_string(event)
+                .map_err(|e| format!("Failed to encode usage event: {e}"))?;
+            data.push_str(&line);
+            data.push('\n');
+        }
+        let tmp = self.path.with_extension("jsonl.tmp");
+        fs::write(&tmp, data).map_err(|e| format!("Failed to write usage log: {e}"))?;
+        fs::rename(&tmp, &self.path).map_err(|e| format!("Failed to write usage log: {e}"))
+    }
+}
+
+/// Full name of a command given by its shortcut (`n`, `s`, `c`).
+fn canonical_command(command: &str) -> &str {
+    match command {
+        "n" => "note",
+        "s" => "suggestion",
+        "c" => "chat",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_record_and_filter_by_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = UsageLog::at(dir.path().join("usage.jsonl"));
+        assert!(log.all().unwrap().is_empty());
+
+        let old = UsageEvent {
+            command: "recent".to_string(),
+            at: Utc::now() - Duration::days(200),
+        };
+        log.record_event(&old).unwrap();
+        log.record("n").unwrap();
+
+        let all = log.all().unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1].command, "note");
+
+        let recent = log.since(Utc::now() - Duration::days(90)).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].command, "note");
+    }
+}
@@ -1,35 +1,27 @@
-use crate::types::SupabaseConfig;
-use dirs::home_dir;
+use indicatif::{ProgressBar, ProgressStyle};
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-pub fn load_supabase_config() -> Result<SupabaseConfig, String> {
-    let mut setup_path =
-        home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
-    setup_path.push(".logswise/setup.json");
-    let data = fs::read_to_string(&setup_path)
-        .map_err(|_| "Setup not found. Please run 'logswise-cli setup' first.".to_string())?;
-    let profile: serde_json::Value = serde_json::from_str(&data)
-        .map_err(|_| "Failed to parse setup.json. Please check the file format.".to_string())?;
-    let project_url = profile["supabaseUrl"]
-        .as_str()
-        .ok_or_else(|| "Missing 'supabaseUrl' in setup.json".to_string())?
-        .to_string();
-    let api_key = profile["supabaseApiKey"]
-        .as_str()
-        .ok_or_else(|| "Missing 'supabaseApiKey' in setup.json".to_string())?
-        .to_string();
-    Ok(SupabaseConfig {
-        project_url,
-        api_key,
-    })
+/// Copies `path` to `<path>.v<version>.bak` before a file is migrated to a newer schema.
+pub fn backup_file(path: &Path, version: u32) -> Result<PathBuf, String> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".v{version}.bak"));
+    let backup = PathBuf::from(backup);
+    fs::copy(path, &backup).map_err(|e| format!("Failed to back up {}: {e}", path.display()))?;
+    Ok(backup)
 }
 
-pub fn load_profile() -> Result<serde_json::Value, String> {
-    let mut setup_path =
-        home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
-    setup_path.push(".logswise/setup.json");
-    let data = fs::read_to_string(&setup_path)
-        .map_err(|_| "Setup not found. Please run 'logswise-cli setup' first.".to_string())?;
-    serde_json::from_str(&data)
-        .map_err(|_| "Failed to parse setup.json. Please check the file format.".to_string())
+/// Creates the spinner shown while waiting on Ollama or Supabase.
+pub fn spinner(message: &str) -> ProgressBar {
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(
+        ProgressStyle::default_spinner()
+            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
+            .template("{spinner:.cyan} {msg}")
+            .unwrap(),
+    );
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    spinner.set_message(message.to_string());
+    spinner
 }
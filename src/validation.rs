@@ -1,6 +1,6 @@
+use crate::config::Config;
 use crate::types::SupabaseConfig;
 use colored::*;
-use serde_json::Value;
 
 /// Validates a URL format
 #[allow(dead_code)] // Will be used in future improvements
@@ -24,59 +24,49 @@ pub fn validate_model_name(name: &str) -> bool {
 
 /// Validates the entire configuration
 #[allow(dead_code)] // Will be used in future improvements
-pub fn validate_config(config: &Value) -> Vec<String> {
+pub fn validate_config(config: &Config) -> Vec<String> {
     let mut errors = Vec::new();
 
     // Check required fields
     let required_fields = [
-        "profession",
-        "jobTitle",
-        "companyName",
-        "companySize",
-        "llmName",
-        "ollamaBaseUrl",
-        "embeddingModel",
-        "supabaseUrl",
-        "supabaseApiKey",
+        ("profession", &config.profession),
+        ("jobTitle", &config.job_title),
+        ("companyName", &config.company_name),
+        ("companySize", &config.company_size),
+        ("llmName", &config.llm_name),
+        ("ollamaBaseUrl", &config.ollama_base_url),
+        ("embeddingModel", &config.embedding_model),
+        ("supabaseUrl", &config.supabase_url),
+        ("supabaseApiKey", &config.supabase_api_key),
     ];
 
-    for field in &required_fields {
-        if config.get(field).is_none() || config[field].as_str().unwrap_or("").trim().is_empty() {
+    for (field, value) in &required_fields {
+        if value.trim().is_empty() {
             errors.push(format!("Missing or empty field: {field}"));
         }
     }
 
     // Validate URLs
-    if let Some(url) = config["supabaseUrl"].as_str() {
-        if !validate_url(url) {
-            errors.push("Invalid Supabase URL format".to_string());
-        }
+    if !config.supabase_url.is_empty() && !validate_url(&config.supabase_url) {
+        errors.push("Invalid Supabase URL format".to_string());
     }
 
-    if let Some(url) = config["ollamaBaseUrl"].as_str() {
-        if !validate_url(url) {
-            errors.push("Invalid Ollama base URL format".to_string());
-        }
+    if !config.ollama_base_url.is_empty() && !validate_url(&config.ollama_base_url) {
+        errors.push("Invalid Ollama base URL format".to_string());
     }
 
     // Validate API key
-    if let Some(key) = config["supabaseApiKey"].as_str() {
-        if !validate_api_key(key) {
-            errors.push("Invalid Supabase API key format".to_string());
-        }
+    if !config.supabase_api_key.is_empty() && !validate_api_key(&config.supabase_api_key) {
+        errors.push("Invalid Supabase API key format".to_string());
     }
 
     // Validate model names
-    if let Some(model) = config["llmName"].as_str() {
-        if !validate_model_name(model) {
-            errors.push("Invalid LLM name format".to_string());
-        }
+    if !config.llm_name.is_empty() && !validate_model_name(&config.llm_name) {
+        errors.push("Invalid LLM name format".to_string());
     }
 
-    if let Some(model) = config["embeddingModel"].as_str() {
-        if !validate_model_name(model) {
-            errors.push("Invalid embedding model name format".to_string());
-        }
+    if !config.embedding_model.is_empty() && !validate_model_name(&config.embedding_model) {
+        errors.push("Invalid embedding model name format".to_string());
     }
 
     errors
@@ -84,7 +74,7 @@ pub fn validate_config(config: &Value) -> Vec<String> {
 
 /// Checks if configuration is valid and prints helpful error messages
 #[allow(dead_code)] // Will be used in future improvements
-pub fn check_config_health(_config: &SupabaseConfig, profile: &Value) -> bool {
+pub fn check_config_health(_config: &SupabaseConfig, profile: &Config) -> bool {
     let validation_errors = validate_config(profile);
 
     if !validation_errors.is_empty() {